@@ -0,0 +1,58 @@
+// Where the server lives. The calls themselves are made through `flashcards_api`.
+//
+// The server is expected at `FLASHCARDS_API_URL` as set when the client was built, or at
+// http://localhost:3000 without it. A `config.json` served next to the page overrides that at
+// runtime, e.g. `{ "api_url": "/api" }` behind a reverse proxy.
+use std::cell::RefCell;
+use flashcards_api::{ApiClient, GlooTransport};
+use gloo_console::log;
+use gloo_net::http::Request;
+use serde::Deserialize;
+
+const DEFAULT_BASE_URL: &str = match option_env!("FLASHCARDS_API_URL") {
+    Some(url) => url,
+    None => "http://localhost:3000",
+};
+
+const CONFIG_PATH: &str = "config.json";
+
+thread_local! {
+    static BASE_URL: RefCell<String> = RefCell::new(DEFAULT_BASE_URL.trim_end_matches('/').to_string());
+}
+
+#[derive(Deserialize)]
+struct RuntimeConfig {
+    api_url: Option<String>,
+}
+
+// Picks up the base URL from `config.json`, if the page is served with one. Called once before
+// the app renders; without the file the build-time URL stays.
+pub async fn load_config() {
+
+    let Ok(response) = Request::get(CONFIG_PATH).send().await else {
+        return;
+    };
+
+    if !response.ok() {
+        return;
+    }
+
+    match response.json::<RuntimeConfig>().await {
+        Ok(RuntimeConfig { api_url: Some(api_url) }) => set_base_url(&api_url),
+        Ok(_) => {},
+        Err(error) => log!(format!("Ignoring {}: {}", CONFIG_PATH, error)),
+    }
+}
+
+pub fn base_url() -> String {
+    BASE_URL.with(|base_url| base_url.borrow().clone())
+}
+
+pub fn set_base_url(url: &str) {
+    BASE_URL.with(|base_url| *base_url.borrow_mut() = url.trim_end_matches('/').to_string());
+}
+
+// A client for the server, at the base URL as configured.
+pub fn client() -> ApiClient<GlooTransport> {
+    ApiClient::new(base_url(), GlooTransport)
+}
@@ -1,5 +1,5 @@
-use yew::{ UseStateHandle, MouseEvent, Callback };
-use flashcards_data::{Card, CardDifficulty, CardState};
+use yew::{ UseStateHandle, Callback };
+use flashcards_data::{Card, CardState};
 
 pub struct Callbacks;
 
@@ -33,23 +33,6 @@ impl Callbacks {
 
         })
     }
-    pub fn make_flip_card_emit_callback(
-            card_index: UseStateHandle<usize>,
-            cards: &Vec<CardState>,
-            flip_card: Callback<CardState> 
-        ) -> Callback<yew::MouseEvent> {
-
-        let card_index = card_index.clone();
-        let cards = cards.clone();
-        let flip_card = flip_card.clone();
-
-        Callback::from(move |_| {
-            let card = cards[*card_index].clone();
-            flip_card.emit(card);
-        })
-
-    }
-
     pub fn delete_card_emit_callback(
             cards: Vec<CardState>,
             delete_card: Callback<CardState>,
@@ -65,28 +48,6 @@ impl Callbacks {
             delete_card.emit(card);
         })
             
-    }
-    pub fn make_review_card_emit_factory(
-            card_index: UseStateHandle<usize>,
-            cards: Vec<CardState>,
-            review_card: Callback<(CardState, CardDifficulty)>
-        ) -> Box<dyn Fn(CardDifficulty) -> Callback<yew::MouseEvent>> {
-
-        let review_card = review_card.clone();
-        let cards = cards.clone();
-        let card_index = card_index.clone();
-
-        Box::new(move |difficulty: CardDifficulty| {
-
-            let review_card = review_card.clone();
-            let card = cards[*card_index].clone();
-            let difficulty = difficulty.clone();
-
-            Callback::from(move |_e: MouseEvent | {
-                review_card.emit((card.clone(), difficulty.clone()));
-            })
-        })
-
     }
     pub fn make_add_card_emit_callback(
             add_card: Callback<Card>,
@@ -1,13 +1,103 @@
-use yew::{use_reducer, use_state, UseReducerHandle, hook};
+use yew::{use_effect_with, use_reducer, use_state, Callback, UseReducerDispatcher, UseReducerHandle, hook};
+use wasm_bindgen::JsCast;
+use web_sys::KeyboardEvent;
 use yew::suspense::{Suspension, SuspensionResult};
 use std::rc::Rc;
-use flashcards_data::{Card, CardState};
-use gloo_net::http::Request;
+use flashcards_data::{BatchOperation, BatchPayload, BatchResult, Card, CardAnswerTimes, CardRevision, Deck, CardState, CreateCardPayload, CreateSessionPayload, DuplicateGroup, MergeCardsPayload, MergedCards, ReviewCardPayload, ReviewedCard, SchedulerSettings, SessionKind, UpdateCardStatusPayload};
+use gloo_console::log;
+use gloo_events::EventListener;
+use gloo_timers::callback::Interval;
+use chrono::{DateTime, Utc};
+use flashcards_api::{ApiError, CardFilters};
+use crate::api;
+use crate::settings::UserSettings;
+use crate::shortcuts::{ShortcutAction, ShortcutBindings};
 use crate::reducers::{
     flashcards::{FlashCardAction, FlashCardsState},
     newcard::{NewCardState},
+    session::{StudySessionAction, StudySessionState},
 };
 
+// The current time, refreshed every `period_ms` so components can react to cards becoming due.
+#[hook]
+pub fn use_now(period_ms: u32) -> DateTime<Utc> {
+
+    let now = use_state(Utc::now);
+
+    {
+        let now = now.clone();
+
+        use_effect_with(period_ms, move |period_ms| {
+            let interval = Interval::new(*period_ms, move || now.set(Utc::now()));
+            move || drop(interval)
+        });
+    }
+
+    *now
+}
+
+// Calls `on_key_down` for every key pressed anywhere on the page, as long as the component is
+// mounted.
+#[hook]
+pub fn use_key_down(on_key_down: Callback<KeyboardEvent>) {
+
+    use_effect_with(on_key_down, move |on_key_down| {
+        let on_key_down = on_key_down.clone();
+        let window = web_sys::window().unwrap();
+
+        let listener = EventListener::new(&window, "keydown", move |event| {
+            if let Some(event) = event.dyn_ref::<KeyboardEvent>() {
+                on_key_down.emit(event.clone());
+            }
+        });
+
+        move || drop(listener)
+    });
+}
+
+// The keyboard shortcuts from the user settings, and a callback that saves changed ones.
+#[hook]
+pub fn use_shortcut_bindings() -> (ShortcutBindings, Callback<ShortcutBindings>) {
+
+    let settings = use_state(UserSettings::load);
+
+    let change = {
+        let settings = settings.clone();
+
+        Callback::from(move |shortcuts: ShortcutBindings| {
+            let changed = UserSettings { shortcuts, ..(*settings).clone() };
+            changed.save();
+            settings.set(changed);
+        })
+    };
+
+    (settings.shortcuts.clone(), change)
+}
+
+// Whether a key goes into a form field rather than being meant as a shortcut.
+pub fn is_typing(event: &KeyboardEvent) -> bool {
+    event.target()
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+        .is_some_and(|element| matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA"))
+}
+
+// Calls `on_action` for the shortcut keys pressed anywhere on the page. Keys typed into a field
+// or pressed with a modifier are left alone, so they still type and browser shortcuts work.
+#[hook]
+pub fn use_shortcuts(bindings: ShortcutBindings, on_action: Callback<ShortcutAction>) {
+
+    use_key_down(Callback::from(move |event: KeyboardEvent| {
+        if event.ctrl_key() || event.meta_key() || event.alt_key() || is_typing(&event) {
+            return;
+        }
+
+        if let Some(action) = bindings.action_for(&event.key()) {
+            event.prevent_default();
+            on_action.emit(action);
+        }
+    }));
+}
+
 #[hook]
 pub fn use_new_card() -> (Rc<Card>, UseReducerHandle<NewCardState>) {
 
@@ -36,14 +126,7 @@ pub fn use_flash_cards() -> (SuspensionResult<Rc<Vec<CardState>>>, UseReducerHan
         let dispatcher = reducer.dispatcher();
         
         wasm_bindgen_futures::spawn_local(async move {
-            let fetched_cards: Vec<Card> = Request::get("http://localhost:3000/cards")
-                .send()
-                .await
-                .unwrap()
-                .json()
-                .await
-                .unwrap();
-           
+            let fetched_cards = logged(api::client().get_cards(&CardFilters::default()).await, "load the cards").unwrap_or_default();
 
             let fetched_cards: Vec<CardState> = fetched_cards.iter()
                 .map(move |card| CardState::new(card.clone()))
@@ -61,3 +144,157 @@ pub fn use_flash_cards() -> (SuspensionResult<Rc<Vec<CardState>>>, UseReducerHan
     (Ok(reducer.cards.clone()), reducer)
 
 }
+
+// Logs a failed call to the server as "Error: Could not <action>", and drops the error.
+pub fn logged<T>(result: Result<T, ApiError>, action: &str) -> Option<T> {
+    result.map_err(|error| log!(format!("Error: Could not {}: {}", action, error))).ok()
+}
+
+// What became of a new card: saved, or refused because its deck already has a card with that front.
+pub enum CreatedCard {
+    Saved(Card),
+    Duplicate,
+}
+
+pub async fn create_card(payload: &CreateCardPayload) -> Option<CreatedCard> {
+
+    match api::client().add_card(payload).await {
+        Ok(card) => Some(CreatedCard::Saved(card)),
+        Err(ApiError::Duplicate(_)) => Some(CreatedCard::Duplicate),
+        Err(error) => {
+            log!(format!("Error: Could not add card: {}", error));
+            None
+        }
+    }
+}
+
+// Cards sharing a front within their deck, grouped.
+pub async fn fetch_duplicates() -> Option<Vec<DuplicateGroup>> {
+    logged(api::client().get_duplicates(None).await, "load the duplicates")
+}
+
+// Keeps the most studied of the cards and moves the others to the trash.
+pub async fn merge_cards(card_ids: Vec<u32>) -> Option<MergedCards> {
+
+    let payload = MergeCardsPayload { card_ids };
+
+    logged(api::client().merge_cards(&payload).await, "merge the cards")
+}
+
+// Cards the server counts as leeches, forgotten so often they probably need rewriting.
+pub async fn fetch_leeches() -> Option<Vec<Card>> {
+    logged(api::client().get_cards(&CardFilters { leech: Some(true), ..Default::default() }).await, "load the leeches")
+}
+
+// The scheduler settings of the server, loaded once when the component mounts. `None` until
+// they arrive, or when they could not be loaded.
+#[hook]
+pub fn use_scheduler_settings() -> Option<SchedulerSettings> {
+
+    let settings = use_state(|| None::<SchedulerSettings>);
+
+    {
+        let settings = settings.clone();
+
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+
+                if let Some(loaded) = logged(api::client().get_scheduler().await, "load the scheduler settings") {
+                    settings.set(Some(loaded));
+                }
+            });
+        });
+    }
+
+    (*settings).clone()
+}
+
+// Average and longest answer time of every card answered with a timer.
+pub async fn fetch_answer_times() -> Option<Vec<CardAnswerTimes>> {
+    logged(api::client().get_answer_times(None).await, "load the answer times")
+}
+
+pub async fn fetch_decks() -> Option<Vec<Deck>> {
+    logged(api::client().get_decks().await, "load the decks")
+}
+
+// Sends many changes in one request. Returns what became of each, in order.
+pub async fn apply_batch(operations: Vec<BatchOperation>, editor: &str) -> Option<Vec<BatchResult>> {
+
+    let payload = BatchPayload { operations };
+
+    logged(api::client().batch_cards(&payload, editor).await, "apply the batch")
+}
+
+// Earlier content of a card, oldest first.
+pub async fn fetch_revisions(card_id: u32) -> Option<Vec<CardRevision>> {
+    logged(api::client().get_card_revisions(card_id).await, "load the history of the card")
+}
+
+// Puts the content of a revision back, recorded as an edit by `editor`.
+pub async fn revert_card(card_id: u32, revision_id: u32, editor: &str) -> Option<Card> {
+    logged(api::client().revert_card(card_id, revision_id, editor).await, "revert the card")
+}
+
+// Takes a deleted card back out of the trash. Returns the card as the server stored it.
+pub async fn restore_card(card_id: u32) -> Option<Card> {
+    logged(api::client().restore_card(card_id).await, "restore the card")
+}
+
+// Suspends, buries or flags a card. Returns the card as the server stored it.
+pub async fn update_card_status(card_id: u32, status: &UpdateCardStatusPayload) -> Option<Card> {
+    logged(api::client().patch_card_status(card_id, status).await, "change the status of the card")
+}
+
+// Records an answer to a card. Returns the review and the card as the server rescheduled it.
+pub async fn review_card(card_id: u32, review: &ReviewCardPayload) -> Option<ReviewedCard> {
+    logged(api::client().review_card(card_id, review).await, "review the card")
+}
+
+// Asks the server which card of the session comes next.
+pub async fn fetch_next_card(session_id: u32, dispatcher: UseReducerDispatcher<StudySessionState>) {
+
+    if let Some(next) = logged(api::client().get_session_next(session_id).await, "load the next card") {
+        dispatcher.dispatch(StudySessionAction::Next(next));
+    }
+}
+
+pub async fn finish_session(session_id: u32, dispatcher: UseReducerDispatcher<StudySessionState>) {
+
+    if let Some(summary) = logged(api::client().finish_session(session_id).await, "finish the session") {
+        dispatcher.dispatch(StudySessionAction::Finished(summary));
+    }
+}
+
+// Starts a study session of the default deck when the component mounts and loads its first
+// card. Picking another kind of session starts a new one.
+#[hook]
+pub fn use_study_session(kind: SessionKind) -> UseReducerHandle<StudySessionState> {
+
+    let reducer = use_reducer(StudySessionState::new);
+
+    {
+        let dispatcher = reducer.dispatcher();
+
+        use_effect_with(kind, move |kind| {
+            let kind = kind.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+
+                let session_payload = CreateSessionPayload {
+                    deck_id: None,
+                    kind,
+                };
+
+                if let Some(session) = logged(api::client().start_session(&session_payload).await, "start a study session") {
+                    let session_id = session.id;
+
+                    dispatcher.dispatch(StudySessionAction::Started(session));
+                    fetch_next_card(session_id, dispatcher).await;
+                }
+            });
+        });
+    }
+
+    reducer
+}
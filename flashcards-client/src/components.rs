@@ -3,3 +3,11 @@ pub mod managemode;
 pub mod studymode;
 pub mod card;
 pub mod actionbutton;
+pub mod toast;
+pub mod revisions;
+pub mod cardtable;
+pub mod duplicates;
+pub mod answer;
+pub mod quizmode;
+pub mod crampicker;
+pub mod shortcuthelp;
@@ -0,0 +1,98 @@
+use yew::{Callback, InputEvent, KeyboardEvent, MouseEvent, Properties, TargetCast, component, html, use_state, Html};
+use web_sys::HtmlInputElement;
+use flashcards_data::{AnswerCheck, AnswerOptions, DiffSegment};
+
+
+#[derive(Properties, PartialEq)]
+pub struct AnswerInputProperties {
+    pub options: AnswerOptions,
+    pub change_options: Callback<AnswerOptions>,
+    // Called with whatever was typed when the answer is checked.
+    pub check: Callback<String>,
+}
+
+// A field to type the back of a card into, checked with the button or Enter.
+#[component]
+pub fn AnswerInput(AnswerInputProperties { options, change_options, check }: &AnswerInputProperties) -> Html {
+
+    let typed = use_state(String::new);
+
+    let on_input = {
+        let typed = typed.clone();
+
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            typed.set(input.value());
+        })
+    };
+
+    let on_key_down = {
+        let typed = typed.clone();
+        let check = check.clone();
+
+        Callback::from(move |e: KeyboardEvent| {
+            if e.key() == "Enter" {
+                e.prevent_default();
+                check.emit((*typed).clone());
+            }
+        })
+    };
+
+    let on_check = {
+        let typed = typed.clone();
+        let check = check.clone();
+
+        Callback::from(move |_: MouseEvent| check.emit((*typed).clone()))
+    };
+
+    let toggle_case = {
+        let options = *options;
+        let change_options = change_options.clone();
+
+        Callback::from(move |_: MouseEvent| change_options.emit(AnswerOptions { ignore_case: !options.ignore_case, ..options }))
+    };
+
+    let toggle_accents = {
+        let options = *options;
+        let change_options = change_options.clone();
+
+        Callback::from(move |_: MouseEvent| change_options.emit(AnswerOptions { ignore_accents: !options.ignore_accents, ..options }))
+    };
+
+    html! {
+        <div class="answer-input">
+            <input type="text" aria-label="Your answer" placeholder="Type the answer" value={(*typed).clone()} oninput={on_input} onkeydown={on_key_down} />
+            <div class="answer-options">
+                <label><input type="checkbox" checked={options.ignore_case} onclick={toggle_case} />{ "Ignore case" }</label>
+                <label><input type="checkbox" checked={options.ignore_accents} onclick={toggle_accents} />{ "Ignore accents" }</label>
+            </div>
+            <button class="action-btn" onclick={on_check}>{ "Check" }</button>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct AnswerDiffProperties {
+    pub check: AnswerCheck,
+}
+
+// The typed answer against the back of the card: missing characters are marked in, typed
+// ones that do not belong are struck out.
+#[component]
+pub fn AnswerDiff(AnswerDiffProperties { check }: &AnswerDiffProperties) -> Html {
+
+    html! {
+        <div class="answer-diff">
+            <div class="diff">
+                { for check.segments.iter().map(|segment| match segment {
+                    DiffSegment::Same(text) => html! { <span class="diff-same">{ text }</span> },
+                    DiffSegment::Missing(text) => html! { <ins class="diff-missing">{ text }</ins> },
+                    DiffSegment::Extra(text) => html! { <del class="diff-extra">{ text }</del> },
+                }) }
+            </div>
+            <div class="description">
+                { format!("{:.0}% correct, suggested rating: {}", check.correctness * 100.0, check.suggested_difficulty().as_str()) }
+            </div>
+        </div>
+    }
+}
@@ -1,9 +1,9 @@
 use yew::prelude::*;
 use yew::{Html, component, html, Properties, Callback};
-use flashcards_data::{Card, CardSide, CardState};
+use flashcards_data::{Card, CardRevision, CardSide, CardState, UpdateCardStatusPayload};
 use crate::FlashCardMode;
 use crate::components::actionbutton::ActionButton;
-use chrono::DateTime;
+use crate::components::revisions::RevisionList;
 use web_sys::HtmlInputElement;
 
 type MouseCallback = Callback<yew::MouseEvent>;
@@ -18,6 +18,12 @@ pub struct CardProperties {
     pub flip: Option<Callback<yew::MouseEvent>>,
     #[prop_or(None)]
     pub save_callback: Option<Callback<Card>>,
+    #[prop_or(None)]
+    pub reset_callback: Option<Callback<Card>>,
+    #[prop_or(None)]
+    pub status_callback: Option<Callback<(Card, UpdateCardStatusPayload)>>,
+    #[prop_or(None)]
+    pub revert_callback: Option<Callback<(Card, CardRevision)>>,
 }
 
 fn render_for_study(card: &CardState, flip: Callback<yew::MouseEvent>) -> Html {
@@ -54,23 +60,25 @@ pub enum ManageMode {
     Edit,
 }
 
-fn render_for_manage(card: &CardState, card_for_edit: UseStateHandle<Card>, save_card: MouseCallback, edit_card: MouseCallback, manage_mode: ManageMode) -> Html {
-
-    let card = card.card();
-    let format = "%Y-%m-%d %H:%M:%S%.9f %Z";
-    //let format = "%Y-%m-%d %H:%M:%S%.9f %Z";
-
-    let dt = DateTime::parse_from_str(card.next_review(), format);
-    
-    let _review_date = match dt {
-        Ok(dt) => {
-            format!("{}", dt.format("%d-%m %H:%M"))
+// Whether the card is out of rotation and how it is flagged, for the manage view.
+fn describe_status(card: &Card) -> String {
+    let rotation = match (card.suspended(), card.buried_until()) {
+        (true, _) => String::from("Suspended"),
+        (false, Some(buried_until)) if card.is_buried_at(chrono::Utc::now()) => {
+            format!("Buried until {}", buried_until.format("%d-%m-%Y %H:%M"))
         },
-        Err(_err) => {
-            "Unknown".to_string()
-        }
+        (false, _) => String::from("Active"),
     };
 
+    format!("Status: {}, Flag: {}", rotation, card.flag().as_str())
+}
+
+fn render_for_manage(card: &CardState, card_for_edit: UseStateHandle<Card>, save_card: MouseCallback, edit_card: MouseCallback, reset_card: MouseCallback, status_actions: Html, history: Html, manage_mode: ManageMode) -> Html {
+
+    let card = card.card();
+    let review_date = card.next_review().format("%d-%m-%Y %H:%M");
+    let status = describe_status(card);
+
     let on_back_input = {
         let card_for_edit = card_for_edit.clone();
 
@@ -79,13 +87,9 @@ fn render_for_manage(card: &CardState, card_for_edit: UseStateHandle<Card>, save
             let input: HtmlInputElement = e.target_unchecked_into();
             let card_for_edit = card_for_edit.clone();
 
-            let front: String = (*card_for_edit.front()).to_string();
-
-            let new_card = Card::new(
-                *card_for_edit.id(),
-                front,
-                input.value(),
-            );
+            // Only the content changes, the scheduling fields are carried over as they are.
+            let mut new_card = (*card_for_edit).clone();
+            new_card.set_back(&input.value());
 
             card_for_edit.set(new_card);
 
@@ -100,13 +104,8 @@ fn render_for_manage(card: &CardState, card_for_edit: UseStateHandle<Card>, save
             let input: HtmlInputElement = e.target_unchecked_into();
             let card_for_edit = card_for_edit.clone();
 
-            let back: String = (*card_for_edit.back()).to_string();
-
-            let new_card = Card::new(
-                *card_for_edit.id(),
-                input.value(),
-                back,
-            );
+            let mut new_card = (*card_for_edit).clone();
+            new_card.set_front(&input.value());
 
             card_for_edit.set(new_card);
             
@@ -120,11 +119,12 @@ fn render_for_manage(card: &CardState, card_for_edit: UseStateHandle<Card>, save
                     <h2>{ format!("Card: {}", card.id()) }</h2>
                     <input value={card_for_edit.front().to_string()} oninput={on_front_input} type="text" />
                     <input value={card_for_edit.back().to_string()} oninput={on_back_input} type="text" />
-                    <div class="description">{ format!("Next Review: {}", card.next_review()) }</div>
-                    //<div class="description">{ format!("Next Review: {}", review_date) }</div>
+                    <div class="description">{ format!("Next Review: {}", review_date) }</div>
+                    <div class="description">{ format!("Interval: {} days", card.interval()) }</div>
+                    <div class="description">{ format!("Stage: {}", card.stage().as_str()) }</div>
                     <div class="description">{ format!("Front of Card: {}", card.front()) }</div>
                     <div class="description">{ format!("Back of Card: {}", card.back()) }</div>
-                    <div class="description">{ format!("Ease Factor: {}", card.ease_factor()) }</div>
+                    <div class="description">{ format!("Ease Factor: {:.2}", card.ease_factor()) }</div>
                     <div class={"card-actions"}>
                         <ActionButton aria_label="Save Card" onclick={save_card} icon={"S"} />
                     </div>
@@ -137,25 +137,46 @@ fn render_for_manage(card: &CardState, card_for_edit: UseStateHandle<Card>, save
         <div class={"card card--manage"} >
             <div class="card-content">
                 <h2>{ format!("Card: {}", card.id()) }</h2>
-                <div class="description">{ format!("Next Review: {}", card.next_review()) }</div>
-                //<div class="description">{ format!("Next Review: {}", review_date) }</div>
+                <div class="description">{ format!("Next Review: {}", review_date) }</div>
+                <div class="description">{ format!("Interval: {} days", card.interval()) }</div>
+                <div class="description">{ format!("Stage: {}", card.stage().as_str()) }</div>
                 <div class="description">{ format!("Front of Card: {}", card.front()) }</div>
                 <div class="description">{ format!("Back of Card: {}", card.back()) }</div>
-                <div class="description">{ format!("Ease Factor: {}", card.ease_factor()) }</div>
+                <div class="description">{ format!("Ease Factor: {:.2}", card.ease_factor()) }</div>
+                <div class="description">{ format!("Lapses: {}", card.lapses()) }</div>
+                <div class="description">{ status }</div>
                 <div class={"card-actions"}>
                     <ActionButton aria_label="Edit Card" onclick={edit_card} icon="\u{1F527}" />
+                    <ActionButton aria_label="Reset Progress" onclick={reset_card} icon="\u{21BA}" />
+                    { status_actions }
                 </div>
+                { history }
             </div>
         </div>
     }
 }
 
 #[component]
-pub fn CardDiv(CardProperties { mode, card, flip, edit, save_callback }: &CardProperties) -> Html {
+pub fn CardDiv(CardProperties { mode, card, flip, edit, save_callback, reset_callback, status_callback, revert_callback }: &CardProperties) -> Html {
 
     let manage_mode = use_state(|| ManageMode::View);
+    let show_history = use_state(|| false);
     let card_for_edit = use_state(|| Card::new(0, String::new(), String::new()));
 
+    // The parent can open the editor too, e.g. from a keyboard shortcut.
+    {
+        let manage_mode = manage_mode.clone();
+        let card_for_edit = card_for_edit.clone();
+        let card = card.card().clone();
+
+        use_effect_with(*edit, move |edit| {
+            if *edit {
+                manage_mode.set(ManageMode::Edit);
+                card_for_edit.set(card);
+            }
+        });
+    }
+
     let edit_card = {
         let manage_mode = manage_mode.clone();
         let card_for_edit = card_for_edit.clone();
@@ -178,24 +199,87 @@ pub fn CardDiv(CardProperties { mode, card, flip, edit, save_callback }: &CardPr
             let save_callback = save_callback.clone();
             log::info!("Card: {:?}", card_for_edit);
             if let Some(save_callback) = save_callback {
-                let card_for_edit = card_for_edit.clone();
-                let card_to_save = Card::new(
-                    *card_for_edit.id(),
-                    card_for_edit.front().to_string(),
-                    card_for_edit.back().to_string(),
-                );
+                let card_to_save = (*card_for_edit).clone();
                 save_callback.emit(card_to_save);
             }
             manage_mode.set(ManageMode::View);
         })
     };
 
+    let reset_card = {
+        let reset_callback = reset_callback.clone();
+        let card = card.clone();
+
+        Callback::from(move |_| {
+            if let Some(reset_callback) = reset_callback.clone() {
+                reset_callback.emit(card.card().clone());
+            }
+        })
+    };
+
+
+    let change_status = |status: UpdateCardStatusPayload| {
+        let status_callback = status_callback.clone();
+        let card = card.clone();
+
+        Callback::from(move |_| {
+            if let Some(status_callback) = status_callback.clone() {
+                status_callback.emit((card.card().clone(), status.clone()));
+            }
+        })
+    };
+
+    let toggle_history = {
+        let show_history = show_history.clone();
+
+        Callback::from(move |_| show_history.set(!*show_history))
+    };
+
+    let revert = {
+        let revert_callback = revert_callback.clone();
+        let card = card.clone();
+
+        Callback::from(move |revision: CardRevision| {
+            if let Some(revert_callback) = revert_callback.clone() {
+                revert_callback.emit((card.card().clone(), revision));
+            }
+        })
+    };
+
+    let shown_card = card.card();
+    let is_buried = shown_card.is_buried_at(chrono::Utc::now());
+
+    let status_actions = html! {
+        <>
+            <ActionButton
+                aria_label={if *shown_card.suspended() { "Unsuspend" } else { "Suspend" }}
+                onclick={change_status(UpdateCardStatusPayload { suspended: Some(!shown_card.suspended()), ..Default::default() })}
+                icon="\u{23F8}" />
+            <ActionButton
+                aria_label={if is_buried { "Unbury" } else { "Bury until tomorrow" }}
+                onclick={change_status(UpdateCardStatusPayload { buried: Some(!is_buried), ..Default::default() })}
+                icon="\u{23EC}" />
+            <ActionButton
+                aria_label={format!("Flag: {}", shown_card.flag().as_str())}
+                onclick={change_status(UpdateCardStatusPayload { flag: Some(shown_card.flag().next()), ..Default::default() })}
+                icon="\u{2691}" />
+            <ActionButton
+                aria_label={if *show_history { "Hide History" } else { "History" }}
+                onclick={toggle_history}
+                icon="\u{1F4DC}" />
+        </>
+    };
+
+    let history = match *show_history {
+        true => html! { <RevisionList card={shown_card.clone()} revert={revert} /> },
+        false => html! {},
+    };
 
     match mode {
         FlashCardMode::Manage => {
-            render_for_manage(&card, card_for_edit, save_card, edit_card, *manage_mode)
+            render_for_manage(&card, card_for_edit, save_card, edit_card, reset_card, status_actions, history, *manage_mode)
         },
-        FlashCardMode::Study => {
+        FlashCardMode::Study | FlashCardMode::Quiz => {
             let flip = flip.clone().unwrap();
             render_for_study(&card, flip)
         }
@@ -0,0 +1,203 @@
+use yew::{Callback, InputEvent, Properties, TargetCast, component, html, use_effect_with, use_state, Html, MouseEvent};
+use web_sys::HtmlInputElement;
+use flashcards_data::{normalize_tag, BatchOperation, CardFlag, CardState, Deck};
+use std::collections::BTreeSet;
+use crate::card_hooks::fetch_decks;
+use crate::components::actionbutton::ActionButton;
+
+
+#[derive(Properties, PartialEq)]
+pub struct CardTableProperties {
+    pub cards: Vec<CardState>,
+    pub apply_batch: Callback<Vec<BatchOperation>>,
+    pub close: Callback<MouseEvent>,
+}
+
+// Every card in one table, with checkboxes to pick the cards the actions below apply to. All
+// the picked cards are changed with a single batch request.
+#[component]
+pub fn CardTable(CardTableProperties { cards, apply_batch, close }: &CardTableProperties) -> Html {
+
+    let selected = use_state(BTreeSet::<u32>::new);
+    let decks = use_state(Vec::<Deck>::new);
+    let tag = use_state(String::new);
+
+    {
+        let decks = decks.clone();
+
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(loaded) = fetch_decks().await {
+                    decks.set(loaded);
+                }
+            });
+        });
+    }
+
+    let all_selected = !cards.is_empty() && selected.len() == cards.len();
+
+    let toggle_all = {
+        let selected = selected.clone();
+        let ids: BTreeSet<u32> = cards.iter().map(|card| *card.card().id()).collect();
+
+        Callback::from(move |_| {
+            match all_selected {
+                true => selected.set(BTreeSet::new()),
+                false => selected.set(ids.clone()),
+            }
+        })
+    };
+
+    // An action turns into one operation per picked card.
+    let batch = |operation: fn(u32) -> BatchOperation| {
+        let selected = selected.clone();
+        let apply_batch = apply_batch.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            if selected.is_empty() {
+                return;
+            }
+
+            apply_batch.emit(selected.iter().copied().map(operation).collect());
+            selected.set(BTreeSet::new());
+        })
+    };
+
+    let move_to = |deck_id: u32| {
+        let selected = selected.clone();
+        let apply_batch = apply_batch.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            apply_batch.emit(selected.iter().map(|id| BatchOperation::Move { id: *id, deck_id }).collect());
+            selected.set(BTreeSet::new());
+        })
+    };
+
+    let flag_with = |flag: CardFlag| {
+        let selected = selected.clone();
+        let apply_batch = apply_batch.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            apply_batch.emit(selected.iter().map(|id| BatchOperation::Flag { id: *id, flag }).collect());
+            selected.set(BTreeSet::new());
+        })
+    };
+
+    let on_tag_input = {
+        let tag = tag.clone();
+
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            tag.set(input.value());
+        })
+    };
+
+    // Adds the typed tag to the picked cards, or takes it off them.
+    let retag = |adding: bool| {
+        let selected = selected.clone();
+        let apply_batch = apply_batch.clone();
+        let tag = tag.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            let Some(name) = normalize_tag(&tag) else {
+                return;
+            };
+
+            apply_batch.emit(selected.iter()
+                .map(|id| match adding {
+                    true => BatchOperation::Tag { id: *id, add: vec![name.clone()], remove: Vec::new() },
+                    false => BatchOperation::Tag { id: *id, add: Vec::new(), remove: vec![name.clone()] },
+                })
+                .collect());
+            selected.set(BTreeSet::new());
+        })
+    };
+
+    let flags = std::iter::successors(Some(CardFlag::Unflagged), |flag| {
+        Some(flag.next()).filter(|next| *next != CardFlag::Unflagged)
+    });
+
+    let deck_name = |deck_id: u32| {
+        decks.iter()
+            .find(|deck| deck.id == deck_id)
+            .map_or_else(|| deck_id.to_string(), |deck| deck.name.clone())
+    };
+
+    let has_selection = !selected.is_empty();
+    let has_tag = normalize_tag(&tag).is_some();
+
+    html! {
+        <div class="content">
+            <table class="card-table">
+                <thead>
+                    <tr>
+                        <th><input type="checkbox" aria-label="Select all" checked={all_selected} onclick={toggle_all} /></th>
+                        <th>{ "Front" }</th>
+                        <th>{ "Back" }</th>
+                        <th>{ "Deck" }</th>
+                        <th>{ "Stage" }</th>
+                        <th>{ "Flag" }</th>
+                        <th>{ "Suspended" }</th>
+                        <th>{ "Tags" }</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    { for cards.iter().map(|card| {
+                        let card = card.card();
+                        let id = *card.id();
+                        let is_selected = selected.contains(&id);
+
+                        let toggle = {
+                            let selected = selected.clone();
+
+                            Callback::from(move |_| {
+                                let mut picked = (*selected).clone();
+                                if !picked.remove(&id) {
+                                    picked.insert(id);
+                                }
+                                selected.set(picked);
+                            })
+                        };
+
+                        html! {
+                            <tr key={id} class={if is_selected { "selected" } else { "" }}>
+                                <td><input type="checkbox" aria-label={format!("Select card {}", id)} checked={is_selected} onclick={toggle} /></td>
+                                <td>{ card.front() }</td>
+                                <td>{ card.back() }</td>
+                                <td>{ deck_name(*card.deck_id()) }</td>
+                                <td>{ card.stage().as_str() }</td>
+                                <td>{ card.flag().as_str() }</td>
+                                <td>{ if *card.suspended() { "Yes" } else { "No" } }</td>
+                                <td>{ card.tags().join(", ") }</td>
+                            </tr>
+                        }
+                    }) }
+                </tbody>
+            </table>
+            <div class="description">{ format!("{} of {} cards selected", selected.len(), cards.len()) }</div>
+            <div class="batch-actions">
+                <button class="action-btn" disabled={!has_selection} onclick={batch(|id| BatchOperation::Suspend { id, suspended: true })}>{ "Suspend" }</button>
+                <button class="action-btn" disabled={!has_selection} onclick={batch(|id| BatchOperation::Suspend { id, suspended: false })}>{ "Unsuspend" }</button>
+                <button class="action-btn" disabled={!has_selection} onclick={batch(|id| BatchOperation::Delete { id })}>{ "Delete" }</button>
+            </div>
+            <div class="batch-actions">
+                { for flags.map(|flag| html! {
+                    <button class="action-btn" disabled={!has_selection} onclick={flag_with(flag)}>{ format!("Flag: {}", flag.as_str()) }</button>
+                }) }
+            </div>
+            <div class="batch-actions">
+                { for decks.iter().map(|deck| html! {
+                    <button class="action-btn" disabled={!has_selection} onclick={move_to(deck.id)}>{ format!("Move to {}", deck.name) }</button>
+                }) }
+            </div>
+            <div class="batch-actions">
+                <input type="text" aria-label="Tag" placeholder="Tag" value={(*tag).clone()} oninput={on_tag_input} />
+                <button class="action-btn" disabled={!has_selection || !has_tag} onclick={retag(true)}>{ "Add tag" }</button>
+                <button class="action-btn" disabled={!has_selection || !has_tag} onclick={retag(false)}>{ "Remove tag" }</button>
+            </div>
+            <div class="button-container">
+                <ActionButton aria_label="Exit" onclick={close.clone()} icon="X" />
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,104 @@
+use yew::{Callback, InputEvent, MouseEvent, Properties, TargetCast, component, html, use_state, Html};
+use web_sys::HtmlInputElement;
+use flashcards_data::{normalize_tag, CardFlag, CramSelection, SessionKind};
+
+
+#[derive(Properties, PartialEq)]
+pub struct CramPickerProperties {
+    pub kind: SessionKind,
+    pub change_kind: Callback<SessionKind>,
+}
+
+fn describe(kind: &SessionKind) -> String {
+    match kind {
+        SessionKind::Scheduled => String::from("Studying the cards that are due"),
+        SessionKind::Cram(CramSelection::Deck) => String::from("Cramming the whole deck"),
+        SessionKind::Cram(CramSelection::Flag { flag }) => format!("Cramming cards flagged {}", flag.as_str()),
+        SessionKind::Cram(CramSelection::Tag { tag }) => format!("Cramming cards tagged {}", tag),
+        SessionKind::Cram(CramSelection::FailedToday) => String::from("Cramming the cards failed today"),
+        SessionKind::Cram(CramSelection::Ahead { days }) => format!("Cramming cards due in the next {} days", days),
+        SessionKind::Cram(CramSelection::Random { count }) => format!("Cramming {} random cards", count),
+    }
+}
+
+// Switches between the scheduled session and cram sessions, which drill cards regardless of
+// when they are due without changing their schedules.
+#[component]
+pub fn CramPicker(CramPickerProperties { kind, change_kind }: &CramPickerProperties) -> Html {
+
+    let open = use_state(|| false);
+    let days = use_state(|| 7u32);
+    let count = use_state(|| 20u32);
+    let tag = use_state(String::new);
+
+    let toggle = {
+        let open = open.clone();
+
+        Callback::from(move |_: MouseEvent| open.set(!*open))
+    };
+
+    let pick = |kind: SessionKind| {
+        let open = open.clone();
+        let change_kind = change_kind.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            open.set(false);
+            change_kind.emit(kind.clone());
+        })
+    };
+
+    let number_input = |value: yew::UseStateHandle<u32>| {
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(number) = input.value().parse::<u32>() {
+                value.set(number.max(1));
+            }
+        })
+    };
+
+    let on_tag_input = {
+        let tag = tag.clone();
+
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            tag.set(input.value());
+        })
+    };
+
+    let by_tag = normalize_tag(&tag).map(|tag| SessionKind::Cram(CramSelection::Tag { tag }));
+
+    let flags = std::iter::successors(Some(CardFlag::Red), |flag| {
+        Some(flag.next()).filter(|next| *next != CardFlag::Unflagged)
+    });
+
+    html! {
+        <div class="cram-picker">
+            <div class="description">
+                { describe(kind) }
+                <button class="action-btn" onclick={toggle}>{ if *open { "Close" } else { "Change" } }</button>
+            </div>
+            if *open {
+                <div class="cram-options">
+                    <button class="action-btn" onclick={pick(SessionKind::Scheduled)}>{ "Due cards" }</button>
+                    <button class="action-btn" onclick={pick(SessionKind::Cram(CramSelection::Deck))}>{ "Whole deck" }</button>
+                    <button class="action-btn" onclick={pick(SessionKind::Cram(CramSelection::FailedToday))}>{ "Failed today" }</button>
+                    { for flags.map(|flag| html! {
+                        <button class="action-btn" onclick={pick(SessionKind::Cram(CramSelection::Flag { flag }))}>{ format!("Flagged {}", flag.as_str()) }</button>
+                    }) }
+                    <div class="cram-option">
+                        <input type="text" aria-label="Tag" placeholder="Tag" value={(*tag).clone()} oninput={on_tag_input} />
+                        <button class="action-btn" disabled={by_tag.is_none()} onclick={by_tag.map(pick)}>{ "Tagged" }</button>
+                    </div>
+                    <div class="cram-option">
+                        <input type="number" min="1" aria-label="Days ahead" value={days.to_string()} oninput={number_input(days.clone())} />
+                        <button class="action-btn" onclick={pick(SessionKind::Cram(CramSelection::Ahead { days: *days }))}>{ "Days ahead" }</button>
+                    </div>
+                    <div class="cram-option">
+                        <input type="number" min="1" aria-label="Random cards" value={count.to_string()} oninput={number_input(count.clone())} />
+                        <button class="action-btn" onclick={pick(SessionKind::Cram(CramSelection::Random { count: *count }))}>{ "Random cards" }</button>
+                    </div>
+                </div>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,75 @@
+use yew::{Callback, MouseEvent, Properties, component, html, use_effect_with, use_state, Html};
+use flashcards_data::{DuplicateGroup, MergedCards};
+use crate::card_hooks::{fetch_duplicates, merge_cards};
+use crate::components::actionbutton::ActionButton;
+
+
+#[derive(Properties, PartialEq)]
+pub struct DuplicateListProperties {
+    pub merged: Callback<MergedCards>,
+    pub close: Callback<MouseEvent>,
+}
+
+// Cards sharing a front within their deck. Merging a group keeps its most studied card.
+#[component]
+pub fn DuplicateList(DuplicateListProperties { merged, close }: &DuplicateListProperties) -> Html {
+
+    let groups = use_state(|| None::<Vec<DuplicateGroup>>);
+
+    {
+        let groups = groups.clone();
+
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(loaded) = fetch_duplicates().await {
+                    groups.set(Some(loaded));
+                }
+            });
+        });
+    }
+
+    let list = match &*groups {
+        None => html! { <div class="description">{ "Looking for duplicates..." }</div> },
+        Some(loaded) if loaded.is_empty() => html! { <div class="description">{ "No duplicates, every front is unique." }</div> },
+        Some(loaded) => html! {
+            <ul class="duplicates">
+                { for loaded.iter().map(|group| {
+                    let merged = merged.clone();
+                    let groups = groups.clone();
+                    let card_ids: Vec<u32> = group.cards.iter().map(|card| *card.id()).collect();
+
+                    let onclick = Callback::from(move |_| {
+                        let merged = merged.clone();
+                        let groups = groups.clone();
+                        let card_ids = card_ids.clone();
+
+                        wasm_bindgen_futures::spawn_local(async move {
+                            if let Some(result) = merge_cards(card_ids).await {
+                                merged.emit(result);
+                                groups.set(fetch_duplicates().await);
+                            }
+                        });
+                    });
+
+                    html! {
+                        <li class="duplicate-group">
+                            { for group.cards.iter().map(|card| html! {
+                                <div class="description">{ format!("{} / {}", card.front(), card.back()) }</div>
+                            }) }
+                            <button class="action-btn" onclick={onclick}>{ "Merge" }</button>
+                        </li>
+                    }
+                }) }
+            </ul>
+        },
+    };
+
+    html! {
+        <div class="content">
+            { list }
+            <div class="button-container">
+                <ActionButton aria_label="Exit" onclick={close.clone()} icon="X" />
+            </div>
+        </div>
+    }
+}
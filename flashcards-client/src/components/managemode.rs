@@ -1,4 +1,5 @@
-use yew::{Properties, HtmlResult, use_state, Callback, component, html};
+use yew::{Properties, HtmlResult, InputEvent, TargetCast, use_node_ref, use_state, Callback, component, html};
+use web_sys::HtmlInputElement;
 use crate::{
     ActionButton,
     FlashCardMode,
@@ -8,34 +9,211 @@ use crate::{
     Card,
     CardState,
 };
+use crate::card_hooks::{fetch_answer_times, fetch_leeches, use_shortcut_bindings, use_shortcuts};
+use crate::components::shortcuthelp::ShortcutHelp;
+use crate::shortcuts::ShortcutAction;
+use crate::components::cardtable::CardTable;
+use crate::components::duplicates::DuplicateList;
+use flashcards_data::{BatchOperation, CardAnswerTimes, CardRevision, MergedCards, UpdateCardStatusPayload};
 
 #[derive(Properties, PartialEq)]
 pub struct ManageModeProperties {
     pub cards: Vec<CardState>,
     pub delete_card: Callback<CardState>,
     pub add_card: Callback<Card>,
-    pub update_card: Callback<Card>
+    pub update_card: Callback<Card>,
+    pub reset_card: Callback<Card>,
+    pub change_card_status: Callback<(Card, UpdateCardStatusPayload)>,
+    pub revert_card: Callback<(Card, CardRevision)>,
+    pub apply_batch: Callback<Vec<BatchOperation>>,
+    pub merged: Callback<MergedCards>,
 }
 
 #[derive(Clone, PartialEq)]
 pub enum ManageModeType {
     AddCard,
     ViewCards,
+    // All cards in a table, to change many at once.
+    Table,
+    // Cards sharing a front within their deck, to merge them.
+    Duplicates,
 }
 
 #[component]
-pub fn ManageMode(ManageModeProperties { add_card, delete_card, update_card, cards }: &ManageModeProperties) -> HtmlResult {
+pub fn ManageMode(ManageModeProperties { add_card, delete_card, update_card, reset_card, change_card_status, revert_card, apply_batch, merged, cards }: &ManageModeProperties) -> HtmlResult {
 
-    let card_index = use_state(|| 0);
-    let cards = cards.clone();
+    let card_index = use_state(|| 0usize);
     let manage_mode_type = use_state(|| ManageModeType::ViewCards);
+    // Ids of the leeches while only those are listed.
+    let leech_ids = use_state(|| None::<Vec<u32>>);
+    // Answer times while the cards are sorted slowest first.
+    let answer_times = use_state(|| None::<Vec<CardAnswerTimes>>);
+    let query = use_state(String::new);
+    let search_input = use_node_ref();
+    // Set from the edit shortcut, cleared once the card is saved or another one is shown.
+    let editing = use_state(|| false);
+    let show_help = use_state(|| false);
+    let (bindings, change_bindings) = use_shortcut_bindings();
 
-    let next_card = Callbacks::make_next_card_callback(card_index.clone(), cards.len().saturating_sub(1));
-    let prev_card = Callbacks::make_prev_card_callback(card_index.clone());
+    let lowercase_query = query.to_lowercase();
+
+    let times_of = |card_id: u32| {
+        answer_times.as_ref()
+            .and_then(|answer_times| answer_times.iter().find(|times| times.card_id == card_id))
+    };
+
+    let mut cards: Vec<CardState> = cards.iter()
+        .filter(|card| match &*leech_ids {
+            Some(leech_ids) => leech_ids.contains(card.card().id()),
+            None => true,
+        })
+        .filter(|card| {
+            lowercase_query.is_empty()
+                || card.card().front().to_lowercase().contains(&lowercase_query)
+                || card.card().back().to_lowercase().contains(&lowercase_query)
+        })
+        .cloned()
+        .collect();
+
+    // Cards never timed go last.
+    if answer_times.is_some() {
+        cards.sort_by_key(|card| std::cmp::Reverse(times_of(*card.card().id()).map(|times| times.average_ms)));
+    }
+
+    {
+        let card_index = card_index.clone();
+        let editing = editing.clone();
+        let show_help = show_help.clone();
+        let search_input = search_input.clone();
+        let viewing_cards = *manage_mode_type == ManageModeType::ViewCards && !cards.is_empty();
+        let last_index = cards.len().saturating_sub(1);
+
+        use_shortcuts(bindings.clone(), Callback::from(move |action: ShortcutAction| {
+            // The overlay takes the keys while it is open, the other views have none.
+            if *show_help || !viewing_cards {
+                return;
+            }
+
+            match action {
+                ShortcutAction::ShowHelp => show_help.set(true),
+                ShortcutAction::Previous => {
+                    editing.set(false);
+                    card_index.set((*card_index).saturating_sub(1));
+                },
+                ShortcutAction::Next => {
+                    editing.set(false);
+                    card_index.set((*card_index + 1).min(last_index));
+                },
+                ShortcutAction::Edit => editing.set(true),
+                ShortcutAction::Search => {
+                    if let Some(input) = search_input.cast::<HtmlInputElement>() {
+                        let _ = input.focus();
+                    }
+                },
+                _ => {},
+            }
+        }));
+    }
+
+    let on_search = {
+        let query = query.clone();
+        let card_index = card_index.clone();
+
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            card_index.set(0);
+            query.set(input.value());
+        })
+    };
+
+    let close_help = {
+        let show_help = show_help.clone();
+
+        Callback::from(move |_| show_help.set(false))
+    };
+
+    let help = html! {
+        if *show_help {
+            <ShortcutHelp bindings={bindings.clone()} change={change_bindings.clone()} close={close_help} />
+        }
+    };
+
+    let search = html! {
+        <input ref={search_input.clone()} class="card-search" type="search" aria-label="Search cards" placeholder="Search" value={(*query).clone()} oninput={on_search} />
+    };
+
+    let toggle_leeches = {
+        let leech_ids = leech_ids.clone();
+        let card_index = card_index.clone();
+
+        Callback::from(move |_| {
+            let leech_ids = leech_ids.clone();
+            card_index.set(0);
+
+            if leech_ids.is_some() {
+                leech_ids.set(None);
+                return;
+            }
+
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(leeches) = fetch_leeches().await {
+                    leech_ids.set(Some(leeches.iter().map(|card| *card.id()).collect()));
+                }
+            });
+        })
+    };
+
+    let toggle_slowest = {
+        let answer_times = answer_times.clone();
+        let card_index = card_index.clone();
+
+        Callback::from(move |_| {
+            let answer_times = answer_times.clone();
+            card_index.set(0);
+
+            if answer_times.is_some() {
+                answer_times.set(None);
+                return;
+            }
+
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(loaded) = fetch_answer_times().await {
+                    answer_times.set(Some(loaded));
+                }
+            });
+        })
+    };
+
+    let next_card = {
+        let next_card = Callbacks::make_next_card_callback(card_index.clone(), cards.len().saturating_sub(1));
+        let editing = editing.clone();
+
+        Callback::from(move |e| {
+            editing.set(false);
+            next_card.emit(e);
+        })
+    };
+    let prev_card = {
+        let prev_card = Callbacks::make_prev_card_callback(card_index.clone());
+        let editing = editing.clone();
+
+        Callback::from(move |e| {
+            editing.set(false);
+            prev_card.emit(e);
+        })
+    };
     let delete_card = Callbacks::delete_card_emit_callback(cards.clone(), delete_card.clone(), card_index.clone());
     //let add_card = Callbacks::make_add_card_emit_callback(add_card.clone());
 
-    let update_card = update_card.clone();
+    let update_card = {
+        let update_card = update_card.clone();
+        let editing = editing.clone();
+
+        Callback::from(move |card: Card| {
+            editing.set(false);
+            update_card.emit(card);
+        })
+    };
     let has_previous = *card_index > 0;
     let has_next = *card_index < cards.len().saturating_sub(1);
 
@@ -45,7 +223,7 @@ pub fn ManageMode(ManageModeProperties { add_card, delete_card, update_card, car
 
         Callback::from(move |_| {
             let type_to_set = match *manage_mode_type {
-                ManageModeType::AddCard => ManageModeType::ViewCards,
+                ManageModeType::AddCard | ManageModeType::Table | ManageModeType::Duplicates => ManageModeType::ViewCards,
                 ManageModeType::ViewCards => ManageModeType::AddCard,
             };
 
@@ -53,6 +231,18 @@ pub fn ManageMode(ManageModeProperties { add_card, delete_card, update_card, car
         })
     };
 
+    let show_table = {
+        let manage_mode_type = manage_mode_type.clone();
+
+        Callback::from(move |_| manage_mode_type.set(ManageModeType::Table))
+    };
+
+    let show_duplicates = {
+        let manage_mode_type = manage_mode_type.clone();
+
+        Callback::from(move |_| manage_mode_type.set(ManageModeType::Duplicates))
+    };
+
     let add_card = {
         let add_card = add_card.clone();
         let manage_mode_type = manage_mode_type.clone();
@@ -65,7 +255,41 @@ pub fn ManageMode(ManageModeProperties { add_card, delete_card, update_card, car
 
     
 
-    
+    if leech_ids.is_some() && cards.is_empty() {
+        return Ok(html! {
+            <div class="content">
+                <div>{ "No leeches, every card is sticking." }</div>
+                <div class="button-container">
+                    <ActionButton aria_label="All Cards" onclick={toggle_leeches} icon="\u{1F41B}" />
+                </div>
+            </div>
+        });
+    }
+
+    if *manage_mode_type == ManageModeType::Table {
+        return Ok(html! {
+            <CardTable cards={cards.clone()} apply_batch={apply_batch.clone()} close={switch.clone()} />
+        });
+    }
+
+    if *manage_mode_type == ManageModeType::Duplicates {
+        return Ok(html! {
+            <DuplicateList merged={merged.clone()} close={switch.clone()} />
+        });
+    }
+
+    if cards.is_empty() && !query.is_empty() && *manage_mode_type == ManageModeType::ViewCards {
+        return Ok(html! {
+            <div class="content">
+                { search }
+                <div>{ "No cards match the search." }</div>
+                <div class="button-container">
+                    <ActionButton aria_label="Add" onclick={switch.clone()} icon="\u{1F5CE}" />
+                </div>
+            </div>
+        });
+    }
+
     if cards.len() == 0 || *manage_mode_type == ManageModeType::AddCard {
         return Ok(html! {
             <div class="content">
@@ -78,17 +302,34 @@ pub fn ManageMode(ManageModeProperties { add_card, delete_card, update_card, car
     }
 
 
-    let card = &cards[*card_index];
+    let card = &cards[(*card_index).min(cards.len() - 1)];
+    let leeches_label = if leech_ids.is_some() { "All Cards" } else { "Leeches" };
+    let slowest_label = if answer_times.is_some() { "In Order" } else { "Slowest First" };
+    let card_times = times_of(*card.card().id()).map(|times| format!(
+        "Answer time: {:.1}s on average, {:.1}s at most, over {} answers",
+        times.average_ms as f64 / 1000.0,
+        times.maximum_ms as f64 / 1000.0,
+        times.answers,
+    ));
 
     Ok(html! {
         <div class="content">
-            <CardDiv save_callback={update_card} mode={FlashCardMode::Manage} card={card.clone()} />
+            { search }
+            <CardDiv edit={*editing} save_callback={update_card} reset_callback={reset_card.clone()} status_callback={change_card_status.clone()} revert_callback={revert_card.clone()} mode={FlashCardMode::Manage} card={card.clone()} />
+            if let Some(card_times) = card_times {
+                <div class="description">{ card_times }</div>
+            }
             <div class="button-container">
                 <ActionButton enabled={has_previous} aria_label="Previous" onclick={prev_card} icon="\u{2B05}" />
                 <ActionButton aria_label="Add" onclick={switch.clone()} icon="\u{1F5CE}" />
                 <ActionButton aria_label="Delete" onclick={delete_card} icon="\u{1F5D1}" />
+                <ActionButton aria_label={leeches_label} onclick={toggle_leeches} icon="\u{1F41B}" />
+                <ActionButton aria_label={slowest_label} onclick={toggle_slowest} icon="\u{23F1}" />
+                <ActionButton aria_label="Table" onclick={show_table} icon="\u{2637}" />
+                <ActionButton aria_label="Duplicates" onclick={show_duplicates} icon="\u{1F46F}" />
                 <ActionButton enabled={has_next} aria_label="Next" onclick={next_card} icon="\u{27A1}" />
             </div>
+            { help }
             //<h1>{ "Add Card" }</h1>
             /*<div>
                 <AddNewCardForm on_update={update_card} on_add={add_card} />
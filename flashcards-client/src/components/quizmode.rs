@@ -0,0 +1,225 @@
+use yew::{
+    html,
+    HtmlResult,
+    use_effect_with,
+    use_mut_ref,
+    use_state,
+    component,
+    Properties,
+    Callback,
+    MouseEvent,
+};
+use crate::{
+    card_hooks::{fetch_next_card, finish_session, review_card, use_now, use_study_session},
+    components::actionbutton::ActionButton,
+    components::studymode::{format_duration, render_summary},
+    reducers::session::StudySessionAction,
+};
+use flashcards_data::{
+    pick_distractors,
+    Card,
+    CardDifficulty,
+    CardState,
+    ReviewCardPayload,
+    SessionKind,
+};
+use chrono::Utc;
+
+// How many wrong answers are offered next to the right one.
+const DISTRACTORS: usize = 3;
+
+#[derive(Properties, PartialEq)]
+pub struct QuizModeProperties {
+    // Every card, the wrong answers are taken from the backs of those in the same deck.
+    pub cards: Vec<CardState>,
+    // Called with the card as the server stored it after each answer.
+    pub card_changed: Callback<Card>,
+    pub change_mode: Callback<yew::MouseEvent>,
+}
+
+// The options for a card, the right answer at a position that changes from card to card.
+fn quiz_options(card: &Card, cards: &[CardState]) -> Vec<String> {
+    let mut options = pick_distractors(card, cards.iter().map(CardState::card), DISTRACTORS);
+    let position = Utc::now().timestamp_subsec_nanos() as usize % (options.len() + 1);
+
+    options.insert(position, card.back().trim().to_string());
+    options
+}
+
+// Studies the session's cards by picking the back among a few, graded on the spot: a right
+// pick counts as remembered, a wrong one as forgotten.
+#[component]
+pub fn QuizMode(QuizModeProperties { cards, card_changed, change_mode }: &QuizModeProperties) -> HtmlResult {
+
+    let session = use_study_session(SessionKind::Scheduled);
+    let now = use_now(1_000);
+    let shown_at = use_mut_ref(Utc::now);
+    let options = use_state(Vec::<String>::new);
+    // The option picked for the card on screen, once it is answered.
+    let picked = use_state(|| None::<usize>);
+
+    // A new card gets new options and its own answer clock.
+    {
+        let shown_at = shown_at.clone();
+        let options = options.clone();
+        let picked = picked.clone();
+        let cards = cards.clone();
+        let shown_card = session.card.as_ref().map(|card| card.card().clone());
+        let shown_key = shown_card.as_ref().map(|card| (*card.id(), *card.next_review()));
+
+        use_effect_with(shown_key, move |_| {
+            *shown_at.borrow_mut() = Utc::now();
+            picked.set(None);
+            options.set(shown_card.map(|card| quiz_options(&card, &cards)).unwrap_or_default());
+        });
+    }
+
+    // A learning card came due while we were waiting on it, ask for it.
+    {
+        let dispatcher = session.dispatcher();
+        let session_id = session.session_id();
+        let due = session.card.is_none() && session.next_due_at.is_some_and(|due_at| due_at <= now);
+
+        use_effect_with((due, session.next_due_at), move |(due, _)| {
+            if let (true, Some(session_id)) = (*due, session_id) {
+                wasm_bindgen_futures::spawn_local(fetch_next_card(session_id, dispatcher));
+            }
+        });
+    }
+
+    // Every card of today's queue was answered, wrap the session up.
+    {
+        let dispatcher = session.dispatcher();
+        let session_id = session.session_id();
+        let done = session.is_done() && session.summary.is_none();
+
+        use_effect_with(done, move |done| {
+            if let (true, Some(session_id)) = (*done, session_id) {
+                wasm_bindgen_futures::spawn_local(finish_session(session_id, dispatcher));
+            }
+        });
+    }
+
+    let finish = {
+        let dispatcher = session.dispatcher();
+        let session_id = session.session_id();
+
+        Callback::from(move |_: MouseEvent| {
+            if let Some(session_id) = session_id {
+                wasm_bindgen_futures::spawn_local(finish_session(session_id, dispatcher.clone()));
+            }
+        })
+    };
+
+    if let Some(summary) = &session.summary {
+        return Ok(render_summary(summary, change_mode.clone()));
+    }
+
+    let Some(card) = session.card.clone() else {
+        let message = match session.next_due_at {
+            Some(next_review) => {
+                let wait = (next_review - now).num_seconds().max(0);
+                format!("Learning cards are due again in {}.", format_duration(wait))
+            },
+            None if session.has_pulled => String::from("You have no cards to review at this time."),
+            None => String::from("Loading..."),
+        };
+
+        return Ok(html! {
+            <div>
+                <div>{ message }</div>
+                <div class="button-container">
+                    <ActionButton aria_label="Study" onclick={change_mode.clone()} icon="\u{1F441}" />
+                    <ActionButton enabled={session.session_id().is_some()} aria_label="Finish" onclick={finish} icon="\u{23F9}" />
+                </div>
+            </div>
+        });
+    };
+
+    let right_answer = card.card().back().trim().to_string();
+
+    let pick = |index: usize| {
+        let dispatcher = session.dispatcher();
+        let session_id = session.session_id();
+        let card_changed = card_changed.clone();
+        let card_id = *card.card().id();
+        let shown_at = shown_at.clone();
+        let picked = picked.clone();
+        let is_right = options.get(index) == Some(&right_answer);
+
+        Callback::from(move |_: MouseEvent| {
+            if picked.is_some() {
+                return;
+            }
+
+            picked.set(Some(index));
+
+            let dispatcher = dispatcher.clone();
+            let card_changed = card_changed.clone();
+
+            let answer_time_ms = (Utc::now() - *shown_at.borrow()).num_milliseconds()
+                .clamp(0, u32::MAX as i64) as u32;
+
+            let review_payload = ReviewCardPayload {
+                difficulty: if is_right { CardDifficulty::Medium } else { CardDifficulty::Hard },
+                session_id,
+                answer_time_ms: Some(answer_time_ms),
+            };
+
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(reviewed) = review_card(card_id, &review_payload).await {
+                    card_changed.emit(reviewed.card);
+                    dispatcher.dispatch(StudySessionAction::Reviewed(reviewed.review_id));
+                }
+            });
+        })
+    };
+
+    let next = {
+        let dispatcher = session.dispatcher();
+        let session_id = session.session_id();
+
+        Callback::from(move |_: MouseEvent| {
+            if let Some(session_id) = session_id {
+                wasm_bindgen_futures::spawn_local(fetch_next_card(session_id, dispatcher.clone()));
+            }
+        })
+    };
+
+    let verdict = match *picked {
+        Some(index) if options.get(index) == Some(&right_answer) => html! { <div class="description">{ "Right!" }</div> },
+        Some(_) => html! { <div class="description">{ format!("Not quite, it is \"{}\".", right_answer) }</div> },
+        None => html! {},
+    };
+
+    Ok(html! {
+        <div>
+            <div class="description">{ format!("Cards left today: {}", session.remaining) }</div>
+            <div class="card card--study">
+                <div class="card-content">
+                    <h2 class="title">{ "Front" }</h2>
+                    <p class="description">{ card.card().front() }</p>
+                </div>
+            </div>
+            <div class="quiz-options">
+                { for options.iter().enumerate().map(|(index, option)| {
+                    let class = match *picked {
+                        Some(_) if *option == right_answer => "quiz-option quiz-option--right",
+                        Some(picked) if picked == index => "quiz-option quiz-option--wrong",
+                        _ => "quiz-option",
+                    };
+
+                    html! {
+                        <button class={class} disabled={picked.is_some()} onclick={pick(index)}>{ option }</button>
+                    }
+                }) }
+            </div>
+            { verdict }
+            <div class="button-container">
+                <ActionButton aria_label="Study" onclick={change_mode.clone()} icon="\u{1F441}" />
+                <ActionButton enabled={picked.is_some()} aria_label="Next" onclick={next} icon="\u{27A1}" />
+                <ActionButton aria_label="Finish" onclick={finish} icon="\u{23F9}" />
+            </div>
+        </div>
+    })
+}
@@ -0,0 +1,61 @@
+use yew::{Callback, Properties, component, html, use_effect_with, use_state, Html};
+use flashcards_data::{Card, CardRevision};
+use crate::card_hooks::fetch_revisions;
+
+
+#[derive(Properties, PartialEq)]
+pub struct RevisionListProperties {
+    pub card: Card,
+    pub revert: Callback<CardRevision>,
+}
+
+// Earlier content of a card, newest first, each with who replaced it and a way back to it.
+#[component]
+pub fn RevisionList(RevisionListProperties { card, revert }: &RevisionListProperties) -> Html {
+
+    let revisions = use_state(|| None::<Vec<CardRevision>>);
+
+    {
+        // Every edit, including a revert, adds a revision, so reload whenever the card changes.
+        let revisions = revisions.clone();
+
+        use_effect_with((*card.id(), *card.updated_at()), move |(card_id, _)| {
+            let card_id = *card_id;
+
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(mut loaded) = fetch_revisions(card_id).await {
+                    loaded.reverse();
+                    revisions.set(Some(loaded));
+                }
+            });
+        });
+    }
+
+    let Some(revisions) = &*revisions else {
+        return html! { <div class="description">{ "Loading history..." }</div> };
+    };
+
+    if revisions.is_empty() {
+        return html! { <div class="description">{ "This card was never edited." }</div> };
+    }
+
+    html! {
+        <ul class="revisions">
+            { for revisions.iter().map(|revision| {
+                let revert = revert.clone();
+                let reverted = revision.clone();
+                let onclick = Callback::from(move |_| revert.emit(reverted.clone()));
+
+                let edited_by = revision.editor.as_deref().unwrap_or("someone");
+
+                html! {
+                    <li class="revision">
+                        <div class="description">{ format!("{} / {}", revision.front, revision.back) }</div>
+                        <div class="description">{ format!("Replaced by {} on {}", edited_by, revision.created_at.format("%d-%m-%Y %H:%M")) }</div>
+                        <button class="action-btn" onclick={onclick}>{ "Revert" }</button>
+                    </li>
+                }
+            }) }
+        </ul>
+    }
+}
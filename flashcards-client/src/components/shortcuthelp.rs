@@ -0,0 +1,81 @@
+use yew::{Callback, KeyboardEvent, MouseEvent, Properties, component, html, use_state, Html};
+use crate::card_hooks::use_key_down;
+use crate::shortcuts::{describe_key, ShortcutAction, ShortcutBindings};
+
+
+#[derive(Properties, PartialEq)]
+pub struct ShortcutHelpProperties {
+    pub bindings: ShortcutBindings,
+    pub change: Callback<ShortcutBindings>,
+    pub close: Callback<()>,
+}
+
+// Lists the keyboard shortcuts. Changing one waits for the next key pressed, Escape keeps the
+// old key. Escape or the help key close the overlay.
+#[component]
+pub fn ShortcutHelp(ShortcutHelpProperties { bindings, change, close }: &ShortcutHelpProperties) -> Html {
+
+    let recording = use_state(|| None::<ShortcutAction>);
+
+    {
+        let recording = recording.clone();
+        let bindings = bindings.clone();
+        let change = change.clone();
+        let close = close.clone();
+
+        use_key_down(Callback::from(move |event: KeyboardEvent| {
+            let key = event.key();
+
+            match *recording {
+                Some(action) => {
+                    event.prevent_default();
+
+                    if key != "Escape" {
+                        let mut changed = bindings.clone();
+                        changed.bind(action, &key);
+                        change.emit(changed);
+                    }
+                    recording.set(None);
+                },
+                None if key == "Escape" || key == bindings.key(ShortcutAction::ShowHelp) => {
+                    event.prevent_default();
+                    close.emit(());
+                },
+                None => {},
+            }
+        }));
+    }
+
+    let on_close = close.reform(|_: MouseEvent| ());
+
+    html! {
+        <div class="shortcut-overlay" role="dialog" aria-label="Keyboard shortcuts">
+            <div class="shortcut-help">
+                <h2>{ "Keyboard shortcuts" }</h2>
+                <table>
+                    <tbody>
+                        { for ShortcutAction::ALL.iter().map(|action| {
+                            let action = *action;
+                            let is_recording = *recording == Some(action);
+
+                            let record = {
+                                let recording = recording.clone();
+
+                                Callback::from(move |_: MouseEvent| recording.set(Some(action)))
+                            };
+
+                            html! {
+                                <tr>
+                                    <td>{ action.label() }</td>
+                                    <td><kbd>{ if is_recording { String::from("Press a key...") } else { describe_key(bindings.key(action)) } }</kbd></td>
+                                    <td><button class="action-btn" onclick={record}>{ "Change" }</button></td>
+                                </tr>
+                            }
+                        }) }
+                    </tbody>
+                </table>
+                <button class="action-btn" onclick={on_close}>{ "Close" }</button>
+            </div>
+        </div>
+    }
+}
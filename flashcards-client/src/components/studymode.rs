@@ -1,87 +1,429 @@
 use yew::{
     html,
     HtmlResult,
+    use_effect_with,
+    use_mut_ref,
     use_state,
     component,
     Properties,
     Callback,
+    MouseEvent,
 };
 use crate::{
+    api,
+    card_hooks::{is_typing, logged, fetch_next_card, finish_session, review_card, update_card_status, use_key_down, use_now, use_scheduler_settings, use_shortcut_bindings, use_shortcuts, use_study_session},
     components::actionbutton::ActionButton,
+    components::answer::{AnswerDiff, AnswerInput},
+    components::crampicker::CramPicker,
+    components::shortcuthelp::ShortcutHelp,
+    shortcuts::ShortcutAction,
+    reducers::session::StudySessionAction,
     FlashCardMode,
-    Callbacks,
     CardDiv,
 };
 use flashcards_data::{
-    CardState,
+    check_answer,
+    format_interval,
+    AnswerCheck,
+    AnswerOptions,
+    Card,
     CardDifficulty,
+    CardState,
+    ReviewCardPayload,
+    SessionKind,
+    SessionSummary,
+    UpdateCardStatusPayload,
 };
+use chrono::Utc;
+use web_sys::KeyboardEvent;
 
 #[derive(Properties, PartialEq)]
 pub struct StudyModeProperties {
-    pub flip_card:  Callback<CardState>,
-    pub review_card: Callback<(CardState, CardDifficulty)>,
-    pub cards: Vec<CardState>,
+    // Called with the card as the server stored it whenever the session changed it: after a
+    // review, an undo, or when it was suspended, buried or flagged.
+    pub card_changed: Callback<Card>,
     pub change_mode: Callback<yew::MouseEvent>,
 }
 
+pub fn format_duration(seconds: i64) -> String {
+    format!("{}m {:02}s", seconds / 60, seconds % 60)
+}
+
+pub fn render_summary(summary: &SessionSummary, change_mode: Callback<yew::MouseEvent>) -> yew::Html {
+    html! {
+        <div>
+            <h2>{ "Session finished" }</h2>
+            <div class="description">{ format!("Cards studied: {}", summary.cards_studied) }</div>
+            <div class="description">{ format!("Time spent: {}", format_duration(summary.time_spent_seconds)) }</div>
+            <div class="description">{ format!("Accuracy: {:.0}%", summary.accuracy * 100.0) }</div>
+            if let Some(average) = summary.average_answer_time_ms {
+                <div class="description">{ format!("Average answer time: {:.1}s", average as f64 / 1000.0) }</div>
+            }
+            <div class="button-container">
+                <ActionButton aria_label="Manage" onclick={change_mode} icon="\u{2699}" />
+            </div>
+        </div>
+    }
+}
+
+// Shows the cards of a study session in the order the server hands them out.
 #[component]
-pub fn StudyMode(StudyModeProperties { change_mode, review_card, flip_card, cards }: &StudyModeProperties) -> HtmlResult {
+pub fn StudyMode(StudyModeProperties { change_mode, card_changed }: &StudyModeProperties) -> HtmlResult {
+
+    let session_kind = use_state(SessionKind::default);
+    let session = use_study_session((*session_kind).clone());
+    let (bindings, change_bindings) = use_shortcut_bindings();
+    let show_help = use_state(|| false);
+    let scheduler = use_scheduler_settings();
+    let now = use_now(1_000);
+    let shown_at = use_mut_ref(Utc::now);
+    // In answer mode the back is typed in and compared with the card rather than recalled.
+    let answer_mode = use_state(|| false);
+    let answer_options = use_state(AnswerOptions::default);
+    let answer_check = use_state(|| None::<AnswerCheck>);
+
+    // Answer times run from the front of a card being shown to the rating click. A card that
+    // is handed out again has a new review date, so its clock starts over too.
+    {
+        let shown_at = shown_at.clone();
+        let answer_check = answer_check.clone();
+        let shown_card = session.card.as_ref()
+            .map(|card| (*card.card().id(), *card.card().next_review()));
+
+        use_effect_with(shown_card, move |_| {
+            *shown_at.borrow_mut() = Utc::now();
+            answer_check.set(None);
+        });
+    }
+
+    // A learning card came due while we were waiting on it, ask for it.
+    {
+        let dispatcher = session.dispatcher();
+        let session_id = session.session_id();
+        let due = session.card.is_none() && session.next_due_at.is_some_and(|due_at| due_at <= now);
+
+        use_effect_with((due, session.next_due_at), move |(due, _)| {
+            if let (true, Some(session_id)) = (*due, session_id) {
+                wasm_bindgen_futures::spawn_local(fetch_next_card(session_id, dispatcher));
+            }
+        });
+    }
+
+    // Every card of today's queue was answered, wrap the session up.
+    {
+        let dispatcher = session.dispatcher();
+        let session_id = session.session_id();
+        let done = session.is_done() && session.summary.is_none();
+
+        use_effect_with(done, move |done| {
+            if let (true, Some(session_id)) = (*done, session_id) {
+                wasm_bindgen_futures::spawn_local(finish_session(session_id, dispatcher));
+            }
+        });
+    }
+
+    // Takes back the latest answer of this session and shows that card again.
+    let undo = {
+        let dispatcher = session.dispatcher();
+        let card_changed = card_changed.clone();
+        let last_review = session.undo_stack.last().copied()
+            .filter(|_| session.summary.is_none());
+
+        Callback::from(move |_: ()| {
+            let Some(review_id) = last_review else {
+                return;
+            };
+
+            let dispatcher = dispatcher.clone();
+            let card_changed = card_changed.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+
+                if let Some(card) = logged(api::client().undo_review(review_id).await, "undo the review") {
+                    card_changed.emit(card.clone());
+                    dispatcher.dispatch(StudySessionAction::Undone(card));
+                }
+            });
+        })
+    };
+
+    {
+        let undo = undo.clone();
+
+        use_key_down(Callback::from(move |event: KeyboardEvent| {
+            // Inside a typed answer the key undoes the typing, not the previous review.
+            if is_typing(&event) {
+                return;
+            }
+
+            if (event.ctrl_key() || event.meta_key()) && event.key().eq_ignore_ascii_case("z") {
+                event.prevent_default();
+                undo.emit(());
+            }
+        }));
+    }
+
+    // Answers the card on screen.
+    let rate_card = {
+        let dispatcher = session.dispatcher();
+        let session_id = session.session_id();
+        let card_changed = card_changed.clone();
+        let card_id = session.card.as_ref().map(|card| *card.card().id());
+        let shown_at = shown_at.clone();
+
+        Callback::from(move |difficulty: CardDifficulty| {
+            let Some(card_id) = card_id else {
+                return;
+            };
+
+            let dispatcher = dispatcher.clone();
+            let card_changed = card_changed.clone();
+
+            // The server caps this at its configured maximum.
+            let answer_time_ms = (Utc::now() - *shown_at.borrow()).num_milliseconds()
+                .clamp(0, u32::MAX as i64) as u32;
+
+            wasm_bindgen_futures::spawn_local(async move {
+
+                let review_payload = ReviewCardPayload {
+                    difficulty,
+                    session_id,
+                    answer_time_ms: Some(answer_time_ms),
+                };
 
-    log::info!("Cards: {:?}", cards);
-    let card_index = use_state(|| 0);
+                if let Some(reviewed) = review_card(card_id, &review_payload).await {
+                    card_changed.emit(reviewed.card);
+                    dispatcher.dispatch(StudySessionAction::Reviewed(reviewed.review_id));
 
-    let cards: Vec<CardState> = cards.iter()
-        .filter(|card| {
-            let card = card.card();
-            card.needs_review()
+                    if let Some(session_id) = session_id {
+                        fetch_next_card(session_id, dispatcher).await;
+                    }
+                }
+            });
         })
-        .cloned()
-        .collect();
+    };
 
-    let total_cards = cards.len();
+    {
+        let dispatcher = session.dispatcher();
+        let rate_card = rate_card.clone();
+        let show_help = show_help.clone();
+        let showing_front = session.card.as_ref().map(CardState::is_front);
+
+        use_shortcuts(bindings.clone(), Callback::from(move |action: ShortcutAction| {
+            // The overlay takes the keys while it is open.
+            if *show_help {
+                return;
+            }
+
+            match (action, showing_front) {
+                (ShortcutAction::ShowHelp, _) => show_help.set(true),
+                (ShortcutAction::Flip, Some(true)) => dispatcher.dispatch(StudySessionAction::FlipCard),
+                (ShortcutAction::RateHard, Some(false)) => rate_card.emit(CardDifficulty::Hard),
+                (ShortcutAction::RateMedium, Some(false)) => rate_card.emit(CardDifficulty::Medium),
+                (ShortcutAction::RateEasy, Some(false)) => rate_card.emit(CardDifficulty::Easy),
+                _ => {},
+            }
+        }));
+    }
+
+    let can_undo = !session.undo_stack.is_empty();
+    let undo_button = undo.reform(|_: MouseEvent| ());
+
+    let finish = {
+        let dispatcher = session.dispatcher();
+        let session_id = session.session_id();
+
+        Callback::from(move |_: MouseEvent| {
+            if let Some(session_id) = session_id {
+                wasm_bindgen_futures::spawn_local(finish_session(session_id, dispatcher.clone()));
+            }
+        })
+    };
+
+    let cram_picker = {
+        let change_kind = {
+            let session_kind = session_kind.clone();
+
+            Callback::from(move |kind: SessionKind| session_kind.set(kind))
+        };
+
+        let close_help = {
+            let show_help = show_help.clone();
+
+            Callback::from(move |_| show_help.set(false))
+        };
+
+        html! {
+            <>
+                <CramPicker kind={(*session_kind).clone()} change_kind={change_kind} />
+                if *show_help {
+                    <ShortcutHelp bindings={bindings.clone()} change={change_bindings.clone()} close={close_help} />
+                }
+            </>
+        }
+    };
+
+    if let Some(summary) = &session.summary {
+        return Ok(html! {
+            <div>
+                { cram_picker }
+                { render_summary(summary, change_mode.clone()) }
+            </div>
+        });
+    }
+
+    let Some(card) = session.card.clone() else {
+        let message = match session.next_due_at {
+            Some(next_review) => {
+                let wait = (next_review - now).num_seconds().max(0);
+                format!("Learning cards are due again in {}.", format_duration(wait))
+            },
+            None if session.has_pulled && session_kind.is_cram() => String::from("Nothing left to cram."),
+            None if session.has_pulled => String::from("You have no cards to review at this time.\n Add a card to get started."),
+            None => String::from("Loading..."),
+        };
 
-    if total_cards == 0 {
         return Ok(html! {
             <div>
-                <div>{ "You have no cards to review at this time.\n Add a card to get started." }</div>
+                { cram_picker }
+                <div>{ message }</div>
                 <div class="button-container">
                     <ActionButton aria_label="Manage" onclick={change_mode.clone()} icon="\u{2699}" />
+                    <ActionButton enabled={can_undo} aria_label="Undo" onclick={undo_button} icon="\u{21B6}" />
+                    <ActionButton enabled={session.session_id().is_some()} aria_label="Finish" onclick={finish} icon="\u{23F9}" />
                 </div>
             </div>
         });
-    }
+    };
 
-    let prev_card = Callbacks::make_prev_card_callback(card_index.clone());
-    let next_card = Callbacks::make_next_card_callback(card_index.clone(), cards.len() - 1);
-    let flip_card = Callbacks::make_flip_card_emit_callback(card_index.clone(), &cards, flip_card.clone());
-    let review_card = Callbacks::make_review_card_emit_factory(card_index.clone(), cards.clone(), review_card.clone());
-   
-    let has_previous = *card_index > 0;
-    let has_next = *card_index < cards.len() - 1;
+    let toggle_answer_mode = {
+        let answer_mode = answer_mode.clone();
+
+        Callback::from(move |_: MouseEvent| answer_mode.set(!*answer_mode))
+    };
+
+    let change_answer_options = {
+        let answer_options = answer_options.clone();
+
+        Callback::from(move |options: AnswerOptions| answer_options.set(options))
+    };
+
+    // Compares the typed answer with the back and turns the card over to show the difference.
+    let check_typed_answer = {
+        let dispatcher = session.dispatcher();
+        let answer_check = answer_check.clone();
+        let options = *answer_options;
+        let back = card.card().back().to_string();
+
+        Callback::from(move |typed: String| {
+            answer_check.set(Some(check_answer(&typed, &back, options)));
+            dispatcher.dispatch(StudySessionAction::FlipCard);
+        })
+    };
+
+    let flip_card = {
+        let dispatcher = session.dispatcher();
+        Callback::from(move |_| dispatcher.dispatch(StudySessionAction::FlipCard))
+    };
+
+    // Suspended and buried cards leave the session, the next card takes their place. A flag
+    // only changes the card on screen.
+    let change_status = |status: UpdateCardStatusPayload| {
+        let dispatcher = session.dispatcher();
+        let session_id = session.session_id();
+        let card_changed = card_changed.clone();
+        let card_id = *card.card().id();
+        let leaves_session = status.suspended == Some(true) || status.buried == Some(true);
+
+        Callback::from(move |_: MouseEvent| {
+            let dispatcher = dispatcher.clone();
+            let card_changed = card_changed.clone();
+            let status = status.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                let Some(changed_card) = update_card_status(card_id, &status).await else {
+                    return;
+                };
+
+                card_changed.emit(changed_card.clone());
+
+                match (leaves_session, session_id) {
+                    (true, Some(session_id)) => fetch_next_card(session_id, dispatcher).await,
+                    _ => dispatcher.dispatch(StudySessionAction::CardChanged(changed_card)),
+                }
+            });
+        })
+    };
+
+    let status_actions = html! {
+        <div class="button-container">
+            <ActionButton aria_label="Suspend" onclick={change_status(UpdateCardStatusPayload { suspended: Some(true), ..Default::default() })} icon="\u{23F8}" />
+            <ActionButton aria_label="Bury until tomorrow" onclick={change_status(UpdateCardStatusPayload { buried: Some(true), ..Default::default() })} icon="\u{23EC}" />
+            <ActionButton
+                aria_label={format!("Flag: {}", card.card().flag().as_str())}
+                onclick={change_status(UpdateCardStatusPayload { flag: Some(card.card().flag().next()), ..Default::default() })}
+                icon="\u{2691}" />
+        </div>
+    };
+
+    let remaining = html! {
+        <>
+            { cram_picker }
+            <div class="description">{ format!("Cards left today: {}", session.remaining) }</div>
+        </>
+    };
+
+    // Each rating shows when the card would come back, worked out with the server's own
+    // scheduler settings. Cram answers don't reschedule, so they get no preview.
+    let rating_button = |difficulty: CardDifficulty, icon: &'static str| {
+        let preview = scheduler.as_ref()
+            .filter(|_| !session_kind.is_cram())
+            .map(|scheduler| format_interval(scheduler.preview(card.card(), &difficulty, Utc::now())));
+        let label = difficulty.as_str();
+
+        html! {
+            <div class="rating">
+                <ActionButton aria_label={label} onclick={rate_card.reform(move |_| difficulty.clone())} icon={icon} />
+                if let Some(preview) = preview {
+                    <span class="interval-preview">{ preview }</span>
+                }
+            </div>
+        }
+    };
 
-    let card = &cards[*card_index];
+    let answer_mode_label = if *answer_mode { "Recall the answer" } else { "Type the answer" };
 
     if card.is_front() {
         return Ok(html! {
             <div>
+                { remaining }
                 <CardDiv flip={flip_card} mode={FlashCardMode::Study} card={card.clone()} />
+                if *answer_mode {
+                    <AnswerInput key={*card.card().id()} options={*answer_options} change_options={change_answer_options} check={check_typed_answer} />
+                }
                 <div class="button-container">
-                    <ActionButton enabled={has_previous} aria_label="Previous" onclick={prev_card} icon="\u{2B05}" />
-                    <ActionButton enabled={has_next} aria_label="Next" onclick={next_card} icon="\u{27A1}" />
+                    <ActionButton aria_label={answer_mode_label} onclick={toggle_answer_mode} icon="\u{2328}" />
+                    <ActionButton enabled={can_undo} aria_label="Undo" onclick={undo_button} icon="\u{21B6}" />
+                    <ActionButton aria_label="Finish" onclick={finish} icon="\u{23F9}" />
                 </div>
+                { status_actions }
             </div>
         })
     }
 
     Ok(html! {
         <div>
+            { remaining }
             <CardDiv flip={flip_card} mode={FlashCardMode::Study} card={card.clone()} />
+            if let Some(check) = &*answer_check {
+                <AnswerDiff check={check.clone()} />
+            }
             <div class="button-container">
-                <ActionButton enabled={true} aria_label="Easy" onclick={review_card(CardDifficulty::Easy)} icon="\u{2730}" />
-                <ActionButton enabled={true} aria_label="Medium" onclick={review_card(CardDifficulty::Medium)} icon="\u{272E}" />
-                <ActionButton enabled={true} aria_label="Hard" onclick={review_card(CardDifficulty::Hard)} icon="\u{272A}" />
+                { rating_button(CardDifficulty::Easy, "\u{2730}") }
+                { rating_button(CardDifficulty::Medium, "\u{272E}") }
+                { rating_button(CardDifficulty::Hard, "\u{272A}") }
             </div>
+            { status_actions }
         </div>
     })
     
@@ -0,0 +1,40 @@
+use yew::{Callback, Properties, component, html, use_effect_with, Html};
+use gloo_timers::callback::Timeout;
+
+
+#[derive(Properties, PartialEq)]
+pub struct ToastProperties {
+    pub message: String,
+    pub action_label: String,
+    pub on_action: Callback<()>,
+    pub on_dismiss: Callback<()>,
+    // How long the toast stays up before it dismisses itself.
+    #[prop_or(6000)]
+    pub timeout_ms: u32,
+}
+
+// A short notice at the bottom of the page with a single action, e.g. undoing a delete.
+#[component]
+pub fn Toast(ToastProperties { message, action_label, on_action, on_dismiss, timeout_ms }: &ToastProperties) -> Html {
+
+    {
+        // A new message restarts the countdown.
+        let on_dismiss = on_dismiss.clone();
+
+        use_effect_with((message.clone(), *timeout_ms), move |(_, timeout_ms)| {
+            let timeout = Timeout::new(*timeout_ms, move || on_dismiss.emit(()));
+            move || drop(timeout)
+        });
+    }
+
+    let on_action = on_action.reform(|_| ());
+    let on_dismiss = on_dismiss.reform(|_| ());
+
+    html! {
+        <div class="toast" role="status">
+            <span class="toast-message">{ message }</span>
+            <button class="toast-action" onclick={on_action}>{ action_label }</button>
+            <button class="toast-close" aria-label="Dismiss" title="Dismiss" onclick={on_dismiss}>{ "\u{2715}" }</button>
+        </div>
+    }
+}
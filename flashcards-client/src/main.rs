@@ -1,23 +1,28 @@
 use yew::prelude::*;
-use flashcards_data::{ CardDifficulty, ReviewCardPayload, CreateCardPayload, Card, CardState };
+use flashcards_data::{ BatchOperation, BatchResult, CreateCardPayload, MergedCards, UpdateCardPayload, UpdateCardStatusPayload, Card, CardRevision, CardState };
 use crate::reducers::flashcards::FlashCardAction;
 use crate::components::actionbutton::ActionButton;
 
+mod api;
 mod card_hooks;
 mod reducers;
 mod components;
 mod callbacks;
+mod settings;
+mod shortcuts;
 
 use callbacks::{Callbacks};
-use crate::card_hooks::{use_flash_cards};
+use crate::card_hooks::{use_flash_cards, logged, update_card_status, restore_card, revert_card, apply_batch, create_card, CreatedCard};
 use components::{
     add_card_form::{AddNewCardForm},
     managemode::{ManageMode},
     studymode::{StudyMode},
+    quizmode::{QuizMode},
     card::{CardDiv},
+    toast::{Toast},
 };
-use gloo_net::http::Request;
 use gloo_console::log;
+use web_sys::HtmlInputElement;
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
@@ -28,6 +33,8 @@ pub struct Props {
 pub enum FlashCardMode {
     Study,
     Manage,
+    // Picks the back of each card among a few.
+    Quiz,
 }
 
 #[component]
@@ -37,6 +44,12 @@ fn Content() -> HtmlResult {
 
     let current_mode = use_state(|| FlashCardMode::Study);
     let card_index = use_state(|| 0);
+    // The card deleted last, kept around so the delete can be undone from the toast.
+    let deleted_card = use_state(|| None::<Card>);
+    // A new card the server refused because its deck already has one with the same front.
+    let duplicate_card = use_state(|| None::<Card>);
+    // Sent along with edits so the card history shows who made them.
+    let editor = use_state(String::new);
    
     let change_mode = {
 
@@ -49,7 +62,7 @@ fn Content() -> HtmlResult {
                 FlashCardMode::Study => {
                     FlashCardMode::Manage
                 },
-                FlashCardMode::Manage => {
+                FlashCardMode::Manage | FlashCardMode::Quiz => {
                     FlashCardMode::Study
                 },
             };
@@ -61,62 +74,35 @@ fn Content() -> HtmlResult {
         })
     };
 
-    let flip_card = {
-        let cards = cards.clone();
-        let dispatcher = reducer.dispatcher();
-
-        Callback::from(move |card: CardState| {
-
-            let dispatcher = dispatcher.clone();
-            let cards = cards.clone();
-
-            let card_id = card.card().id();
-
-            let card = cards.iter()
-                .position(|card| {
-                    card.card().id() == card_id
-                });
-
-            if let Some(position) = card {
-                dispatcher.dispatch(FlashCardAction::FlipCard(position));
-            }
-        
-        })
+    let start_quiz = {
+        let current_mode = current_mode.clone();
 
+        Callback::from(move |_| current_mode.set(FlashCardMode::Quiz))
     };
 
-        
     let delete_card = {
 
         let dispatcher = reducer.dispatcher();
         let card_index = card_index.clone();
+        let deleted_card = deleted_card.clone();
 
         Callback::from(move |card: CardState| {
             let dispatcher = dispatcher.clone();
             let card = card.clone();
             let card_index = card_index.clone();
+            let deleted_card = deleted_card.clone();
 
             wasm_bindgen_futures::spawn_local(async move {
 
                 let card = card.clone();
                 let card = card.card();
                 
-                let delete_card_path = format!("http://localhost:3000/cards/{}", card.id());
-
-                let response = Request::delete(&delete_card_path)
-                    .send()
-                    .await;
-
-                match response {
-                    Ok(response) if response.ok() => {
-                        log!("Card was successfully removed!");
-                        let value: usize = *card_index;
-                        dispatcher.dispatch(FlashCardAction::RemoveCard(card.clone()));
-                        card_index.set(value.saturating_sub(1));
-                    },
-                    _ => {
-                        log!("Error: Could not remove card");
-                    }
+                if logged(api::client().remove_card(*card.id()).await, "remove card").is_some() {
+                    log!("Card was successfully removed!");
+                    let value: usize = *card_index;
+                    dispatcher.dispatch(FlashCardAction::RemoveCard(card.clone()));
+                    card_index.set(value.saturating_sub(1));
+                    deleted_card.set(Some(card.clone()));
                 }
 
             });
@@ -126,11 +112,13 @@ fn Content() -> HtmlResult {
     let update_card = {
         let dispatcher = reducer.dispatcher();
         let cards = cards.clone();
+        let editor = editor.clone();
         //let card_index = card_index.clone();
 
         Callback::from(move |card: Card| {
             let dispatcher = dispatcher.clone();
             let cards = cards.clone();
+            let editor = (*editor).clone();
             //let card_index = card_index.clone();
 
             wasm_bindgen_futures::spawn_local(async move {
@@ -143,107 +131,242 @@ fn Content() -> HtmlResult {
                 let current_card = cards.get(card_index).unwrap();
                 let current_card = current_card.card();
 
-                let card_payload = CreateCardPayload {
-                    front: card.front().to_string(),
-                    back: card.back().to_string(),
+                // PATCH only touches the content, so the card keeps its review history.
+                let card_payload = UpdateCardPayload {
+                    front: Some(card.front().to_string()),
+                    back: Some(card.back().to_string()),
                 };
 
-                let update_url = format!("http://localhost:3000/cards/{}", current_card.id());
-
-                let response = Request::put(&update_url)
-                    .json(&card_payload)
-                    .unwrap()
-                    .send()
-                    .await;
+                let updated_card = api::client().patch_card(*current_card.id(), &card_payload, &editor).await;
 
-                match response {
-                    Ok(response) if response.ok() => {
-                        let updated_card: Card = response.json().await.unwrap();
-                        dispatcher.dispatch(FlashCardAction::UpdateCard(updated_card));
-                    },
-                    _ => {
-                        log!("Error occurred updating the card");
-                    }
+                if let Some(updated_card) = logged(updated_card, "update the card") {
+                    dispatcher.dispatch(FlashCardAction::UpdateCard(updated_card));
                 }
             });
         })
     };
 
-    let add_card = {
+    let reset_card = {
         let dispatcher = reducer.dispatcher();
+
         Callback::from(move |card: Card| {
+            let dispatcher = dispatcher.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+
+                if let Some(reset_card) = logged(api::client().reset_card(*card.id()).await, "reset the card") {
+                    dispatcher.dispatch(FlashCardAction::UpdateCard(reset_card));
+                }
+            });
+        })
+    };
+
+    // Adds a card unless its deck already has one with the same front. Refused cards wait in
+    // `duplicate_card` until they are added anyway or dismissed.
+    let save_new_card = {
+        let dispatcher = reducer.dispatcher();
+        let duplicate_card = duplicate_card.clone();
 
+        move |card: Card, allow_duplicate: bool| {
             let dispatcher = dispatcher.clone();
-            //"http://localhost:3000/cards"
+            let duplicate_card = duplicate_card.clone();
+
             wasm_bindgen_futures::spawn_local(async move {
 
                 let card_payload = CreateCardPayload {
                     front: card.front().to_string(),
                     back: card.back().to_string(),
+                    deck_id: Some(*card.deck_id()),
+                    allow_duplicate,
                 };
 
-                let response = Request::post("http://localhost:3000/cards")
-                    .json(&card_payload)
-                    .unwrap()
-                    .send()
-                    .await;
-
-                match response {
-                    Ok(response) if response.ok() => {
-                        let saved_card: Card = response.json().await.unwrap();
+                match create_card(&card_payload).await {
+                    Some(CreatedCard::Saved(saved_card)) => {
+                        duplicate_card.set(None);
                         dispatcher.dispatch(FlashCardAction::AddCard(saved_card));
                     },
-                    _ => {
-                        log!("Error: Could not add card.");
-                    }
+                    Some(CreatedCard::Duplicate) => duplicate_card.set(Some(card)),
+                    None => {},
                 }
             });
+        }
+    };
+
+    let add_card = {
+        let save_new_card = save_new_card.clone();
+
+        Callback::from(move |card: Card| save_new_card(card, false))
+    };
+
+    let add_duplicate_anyway = {
+        let duplicate_card = duplicate_card.clone();
+
+        Callback::from(move |_| {
+            if let Some(card) = (*duplicate_card).clone() {
+                save_new_card(card, true);
+            }
         })
     };
 
-    let review_card = {
+    let dismiss_duplicate = {
+        let duplicate_card = duplicate_card.clone();
+
+        Callback::from(move |_| duplicate_card.set(None))
+    };
+
+    // The kept card may have changed, the others are in the trash now.
+    let cards_merged = {
         let dispatcher = reducer.dispatcher();
+        let cards = cards.clone();
 
-        Callback::from(move |(card, difficulty): (CardState, CardDifficulty)| {
+        Callback::from(move |merged: MergedCards| {
+            let trashed = cards.iter().filter(|card| merged.trashed.contains(card.card().id()));
+
+            for card in trashed {
+                dispatcher.dispatch(FlashCardAction::RemoveCard(card.card().clone()));
+            }
+            dispatcher.dispatch(FlashCardAction::UpdateCard(merged.kept));
+        })
+    };
+
+    let change_card_status = {
+        let dispatcher = reducer.dispatcher();
+
+        Callback::from(move |(card, status): (Card, UpdateCardStatusPayload)| {
             let dispatcher = dispatcher.clone();
 
             wasm_bindgen_futures::spawn_local(async move {
+                if let Some(changed_card) = update_card_status(*card.id(), &status).await {
+                    dispatcher.dispatch(FlashCardAction::UpdateCard(changed_card));
+                }
+            });
+        })
+    };
 
-                let review_payload = ReviewCardPayload {
-                    difficulty,
-                };
+    let revert_to_revision = {
+        let dispatcher = reducer.dispatcher();
+        let editor = editor.clone();
 
-                let url = format!("http://localhost:3000/cards/{}/review", card.card().id());
+        Callback::from(move |(card, revision): (Card, CardRevision)| {
+            let dispatcher = dispatcher.clone();
+            let editor = (*editor).clone();
 
-                let response = Request::post(&url)
-                    .json(&review_payload)
-                    .unwrap()
-                    .send()
-                    .await;
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(reverted_card) = revert_card(*card.id(), revision.id, &editor).await {
+                    dispatcher.dispatch(FlashCardAction::UpdateCard(reverted_card));
+                }
+            });
+        })
+    };
 
-                match response {
-                    Ok(response) if response.ok() => {
-                        let reviewed_card: Card = response.json().await.unwrap();
-                        log!(format!("Reviewed Card: {:?}", &reviewed_card));
-                        dispatcher.dispatch(FlashCardAction::UpdateCard(reviewed_card));
-                    },
-                    _ => {},
+    let apply_batch_operations = {
+        let dispatcher = reducer.dispatcher();
+        let editor = editor.clone();
+        let card_index = card_index.clone();
+
+        Callback::from(move |operations: Vec<BatchOperation>| {
+            let dispatcher = dispatcher.clone();
+            let editor = (*editor).clone();
+            let card_index = card_index.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                let Some(results) = apply_batch(operations, &editor).await else {
+                    return;
+                };
+
+                for result in results {
+                    match result {
+                        BatchResult::Done { card } if card.deleted_at().is_some() => {
+                            dispatcher.dispatch(FlashCardAction::RemoveCard(card));
+                        },
+                        BatchResult::Done { card } => dispatcher.dispatch(FlashCardAction::UpdateCard(card)),
+                        BatchResult::Failed { error } => log!(format!("Error: A batch operation failed: {}", error)),
+                    }
                 }
 
+                card_index.set(0);
             });
+        })
+    };
 
+    let change_editor = {
+        let editor = editor.clone();
+
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            editor.set(input.value());
         })
+    };
+
+    // Study mode talks to the server itself, the list only needs to pick up the changed cards.
+    let card_changed = {
+        let dispatcher = reducer.dispatcher();
 
+        Callback::from(move |card: Card| {
+            dispatcher.dispatch(FlashCardAction::UpdateCard(card));
+        })
     };
-    
+
+    // Deleted cards sit in the server's trash for a while, so the delete can still be undone.
+    let undo_delete = {
+        let dispatcher = reducer.dispatcher();
+        let deleted_card = deleted_card.clone();
+
+        Callback::from(move |_| {
+            let dispatcher = dispatcher.clone();
+            let deleted_card = deleted_card.clone();
+
+            let Some(card) = (*deleted_card).clone() else {
+                return;
+            };
+
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(restored_card) = restore_card(*card.id()).await {
+                    dispatcher.dispatch(FlashCardAction::RestoreCard(restored_card));
+                }
+                deleted_card.set(None);
+            });
+        })
+    };
+
+    let dismiss_toast = {
+        let deleted_card = deleted_card.clone();
+
+        Callback::from(move |_| deleted_card.set(None))
+    };
+
+    let toast = match (&*duplicate_card, &*deleted_card) {
+        (Some(card), _) => html! {
+            <Toast message={format!("A card \"{}\" already exists in this deck", card.front())} action_label="Add anyway" on_action={add_duplicate_anyway} on_dismiss={dismiss_duplicate} />
+        },
+        (None, Some(card)) => html! {
+            <Toast message={format!("Moved \"{}\" to the trash", card.front())} action_label="Undo" on_action={undo_delete} on_dismiss={dismiss_toast} />
+        },
+        (None, None) => html! {},
+    };
+
+    if *current_mode == FlashCardMode::Quiz {
+        return Ok(html! {
+            <div class="main main--study">
+                <header>
+                    <ActionButton aria_label="Study" onclick={change_mode.clone()} icon="\u{1F441}" />
+                </header>
+                <div class="content">
+                    <QuizMode cards={(*cards).clone()} change_mode={change_mode.clone()} card_changed={card_changed} />
+                </div>
+            </div>
+        });
+    }
+
     if *current_mode == FlashCardMode::Study {
         return Ok(html! {
             <div class="main main--study">
                 <header>
                     <ActionButton aria_label="Manage" onclick={change_mode.clone()} icon="\u{2699}" />
+                    <ActionButton aria_label="Quiz" onclick={start_quiz} icon="\u{2753}" />
                 </header>
                 <div class="content">
-                    <StudyMode change_mode={change_mode.clone()} cards={(*cards).clone()} review_card={review_card} flip_card={flip_card} />
+                    <StudyMode change_mode={change_mode.clone()} card_changed={card_changed} />
                 </div>
             </div>
         });
@@ -253,8 +376,10 @@ fn Content() -> HtmlResult {
         <div class="main main--manage">
             <header>
                 <ActionButton aria_label="Study" onclick={change_mode} icon="\u{1F441}" />
+                <input class="editor-name" aria-label="Your name" placeholder="Your name" value={(*editor).clone()} oninput={change_editor} type="text" />
             </header>
-            <ManageMode cards={(*cards).clone()} update_card={update_card} add_card={add_card} delete_card={delete_card} />
+            <ManageMode cards={(*cards).clone()} update_card={update_card} reset_card={reset_card} change_card_status={change_card_status} revert_card={revert_to_revision} apply_batch={apply_batch_operations} merged={cards_merged} add_card={add_card} delete_card={delete_card} />
+            { toast }
         </div>
     })
 
@@ -274,5 +399,11 @@ fn App() -> Html {
 
 fn main() {
     wasm_logger::init(wasm_logger::Config::default());
-    yew::Renderer::<App>::new().render();
+
+    // The server may live elsewhere than the build assumed, so the app only starts talking to
+    // it once `config.json` had its say.
+    wasm_bindgen_futures::spawn_local(async {
+        api::load_config().await;
+        yew::Renderer::<App>::new().render();
+    });
 }
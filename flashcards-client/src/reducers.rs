@@ -1,2 +1,3 @@
 pub mod flashcards;
 pub mod newcard;
+pub mod session;
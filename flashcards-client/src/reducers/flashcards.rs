@@ -20,10 +20,11 @@ impl FlashCardsState {
 
 pub enum FlashCardAction {
     SetData(Vec<CardState>),
-    FlipCard(usize),
     AddCard(Card),
     UpdateCard(Card),
     RemoveCard(Card),
+    // Puts a card taken out of the trash back where it was.
+    RestoreCard(Card),
 }
 
 impl Reducible for FlashCardsState {
@@ -85,24 +86,26 @@ impl Reducible for FlashCardsState {
                     has_pulled: true,
                 }.into()
             }
-            FlashCardAction::SetData(cards) => {
-                FlashCardsState {
-                    cards: Rc::new(cards),
-                    has_pulled: true,
-                }.into()
-            },
-            FlashCardAction::FlipCard(index) => {
+            FlashCardAction::RestoreCard(card) => {
                 let mut new_cards: Vec<CardState> = (*self.cards).clone();
 
-                if let Some(card) = new_cards.get_mut(index) {
-                    (*card).flip_card();
-                }
+                let card_position = new_cards.iter()
+                    .position(|current_card| current_card.card().id() > card.id())
+                    .unwrap_or(new_cards.len());
+
+                new_cards.insert(card_position, CardState::new(card));
 
                 FlashCardsState {
                     cards: Rc::new(new_cards),
                     has_pulled: true,
                 }.into()
             }
+            FlashCardAction::SetData(cards) => {
+                FlashCardsState {
+                    cards: Rc::new(cards),
+                    has_pulled: true,
+                }.into()
+            },
         }
     }
 }
@@ -0,0 +1,104 @@
+use std::rc::Rc;
+use yew::Reducible;
+use chrono::{DateTime, Utc};
+use flashcards_data::{Card, CardState, SessionCard, SessionSummary, StudySession};
+
+#[derive(Clone)]
+pub struct StudySessionState {
+    pub session: Option<StudySession>,
+    // The card on screen, `None` while loading, when waiting on learning cards or when done.
+    pub card: Option<CardState>,
+    pub next_due_at: Option<DateTime<Utc>>,
+    pub remaining: u32,
+    // Set once the first `SessionCard` came back, so an empty session can tell it is done.
+    pub has_pulled: bool,
+    pub summary: Option<SessionSummary>,
+    // Ids of the reviews given in this session, latest last, for undo.
+    pub undo_stack: Vec<u32>,
+}
+
+impl StudySessionState {
+    pub fn new() -> Self {
+        Self {
+            session: None,
+            card: None,
+            next_due_at: None,
+            remaining: 0,
+            has_pulled: false,
+            summary: None,
+            undo_stack: Vec::new(),
+        }
+    }
+    pub fn session_id(&self) -> Option<u32> {
+        self.session.as_ref().map(|session| session.id)
+    }
+    // Nothing left to show and nothing coming back later.
+    pub fn is_done(&self) -> bool {
+        self.has_pulled && self.card.is_none() && self.next_due_at.is_none()
+    }
+}
+
+pub enum StudySessionAction {
+    Started(StudySession),
+    Next(SessionCard),
+    FlipCard,
+    Reviewed(u32),
+    // The latest review was undone, the card is shown again as it was before.
+    Undone(Card),
+    // The card on screen changed without being answered, e.g. it was flagged.
+    CardChanged(Card),
+    Finished(SessionSummary),
+}
+
+impl Reducible for StudySessionState {
+    type Action = StudySessionAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut state = (*self).clone();
+
+        match action {
+            StudySessionAction::Started(session) => {
+                state = StudySessionState::new();
+                state.session = Some(session);
+            },
+            StudySessionAction::Next(next) => {
+                state.card = next.card.map(CardState::new);
+                state.next_due_at = next.next_due_at;
+                state.remaining = next.remaining;
+                state.has_pulled = true;
+            },
+            StudySessionAction::FlipCard => {
+                if let Some(card) = state.card.as_mut() {
+                    card.flip_card();
+                }
+            },
+            StudySessionAction::Reviewed(review_id) => {
+                state.undo_stack.push(review_id);
+            },
+            StudySessionAction::Undone(card) => {
+                state.undo_stack.pop();
+                state.card = Some(CardState::new(card));
+                state.next_due_at = None;
+            },
+            StudySessionAction::CardChanged(card) => {
+                if let Some(shown) = state.card.as_mut() {
+                    let was_back = shown.is_back();
+                    *shown = CardState::new(card);
+                    if was_back {
+                        shown.flip_card();
+                    }
+                }
+            },
+            StudySessionAction::Finished(summary) => {
+                state.session = Some(summary.session.clone());
+                state.card = None;
+                state.next_due_at = None;
+                state.remaining = 0;
+                state.has_pulled = true;
+                state.summary = Some(summary);
+            },
+        }
+
+        state.into()
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{Serialize, Deserialize};
+use gloo_storage::{LocalStorage, Storage};
+use gloo_console::log;
+use crate::shortcuts::ShortcutBindings;
+
+const SETTINGS_KEY: &str = "flashcards.settings";
+
+// Preferences kept in the browser, they are not sent to the server.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct UserSettings {
+    #[serde(default)]
+    pub shortcuts: ShortcutBindings,
+}
+
+impl UserSettings {
+    // Falls back to the defaults when nothing was saved yet or the saved settings can't be read.
+    pub fn load() -> Self {
+        LocalStorage::get(SETTINGS_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if LocalStorage::set(SETTINGS_KEY, self).is_err() {
+            log!("Error: Could not save the settings");
+        }
+    }
+}
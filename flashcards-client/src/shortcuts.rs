@@ -0,0 +1,97 @@
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+// Everything that can be done from the keyboard in the study and manage views.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ShortcutAction {
+    Flip,
+    RateHard,
+    RateMedium,
+    RateEasy,
+    Previous,
+    Next,
+    Edit,
+    Search,
+    ShowHelp,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 9] = [
+        ShortcutAction::Flip,
+        ShortcutAction::RateHard,
+        ShortcutAction::RateMedium,
+        ShortcutAction::RateEasy,
+        ShortcutAction::Previous,
+        ShortcutAction::Next,
+        ShortcutAction::Edit,
+        ShortcutAction::Search,
+        ShortcutAction::ShowHelp,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ShortcutAction::Flip => "Turn the card",
+            ShortcutAction::RateHard => "Rate Hard",
+            ShortcutAction::RateMedium => "Rate Medium",
+            ShortcutAction::RateEasy => "Rate Easy",
+            ShortcutAction::Previous => "Previous card",
+            ShortcutAction::Next => "Next card",
+            ShortcutAction::Edit => "Edit the card",
+            ShortcutAction::Search => "Search cards",
+            ShortcutAction::ShowHelp => "Show the shortcuts",
+        }
+    }
+
+    // Keys are compared with `KeyboardEvent::key`, so a space is " ".
+    pub fn default_key(&self) -> &'static str {
+        match self {
+            ShortcutAction::Flip => " ",
+            ShortcutAction::RateHard => "1",
+            ShortcutAction::RateMedium => "2",
+            ShortcutAction::RateEasy => "3",
+            ShortcutAction::Previous => "ArrowLeft",
+            ShortcutAction::Next => "ArrowRight",
+            ShortcutAction::Edit => "e",
+            ShortcutAction::Search => "/",
+            ShortcutAction::ShowHelp => "?",
+        }
+    }
+}
+
+// The keys the user picked. Actions they never rebound keep their default key.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShortcutBindings(BTreeMap<ShortcutAction, String>);
+
+impl ShortcutBindings {
+    pub fn key(&self, action: ShortcutAction) -> &str {
+        self.0.get(&action).map_or(action.default_key(), String::as_str)
+    }
+
+    pub fn action_for(&self, key: &str) -> Option<ShortcutAction> {
+        ShortcutAction::ALL.into_iter().find(|action| self.key(*action) == key)
+    }
+
+    // Binds `key` to `action`. An action that had the key already swaps to the old key of
+    // `action`, so every action keeps a key of its own.
+    pub fn bind(&mut self, action: ShortcutAction, key: &str) {
+        let old_key = self.key(action).to_string();
+
+        if let Some(other) = self.action_for(key).filter(|other| *other != action) {
+            self.0.insert(other, old_key);
+        }
+
+        self.0.insert(action, key.to_string());
+    }
+}
+
+// How a key is written in the shortcut overlay.
+pub fn describe_key(key: &str) -> String {
+    match key {
+        " " => String::from("Space"),
+        "ArrowLeft" => String::from("\u{2190}"),
+        "ArrowRight" => String::from("\u{2192}"),
+        "ArrowUp" => String::from("\u{2191}"),
+        "ArrowDown" => String::from("\u{2193}"),
+        key => key.to_string(),
+    }
+}
@@ -0,0 +1,6 @@
+-- Add migration script here
+CREATE TABLE flashcards (
+  id SERIAL PRIMARY KEY,
+  front_of_card TEXT NOT NULL,
+  back_of_card TEXT NOT NULL
+)
@@ -0,0 +1,9 @@
+--- Add migration script here
+ALTER TABLE flashcards
+  ADD COLUMN ease_factor INTEGER NOT NULL DEFAULT 3;
+
+ALTER TABLE flashcards
+  ADD COLUMN "interval" INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE flashcards
+  ADD COLUMN next_review TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP::TEXT;
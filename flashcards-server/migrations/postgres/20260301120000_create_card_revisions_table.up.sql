@@ -0,0 +1,9 @@
+CREATE TABLE card_revisions (
+  id SERIAL PRIMARY KEY,
+  card_id INTEGER NOT NULL REFERENCES flashcards(id) ON DELETE CASCADE,
+  front_of_card TEXT NOT NULL,
+  back_of_card TEXT NOT NULL,
+  created_at TEXT NOT NULL
+);
+
+CREATE INDEX card_revisions_card_id ON card_revisions(card_id);
@@ -0,0 +1,17 @@
+ALTER TABLE flashcards
+  DROP COLUMN updated_at;
+
+ALTER TABLE flashcards
+  DROP COLUMN created_at;
+
+ALTER TABLE card_revisions
+  ALTER COLUMN created_at TYPE TEXT USING created_at::TEXT;
+
+ALTER TABLE flashcards
+  ALTER COLUMN next_review DROP DEFAULT;
+
+ALTER TABLE flashcards
+  ALTER COLUMN next_review TYPE TEXT USING next_review::TEXT;
+
+ALTER TABLE flashcards
+  ALTER COLUMN next_review SET DEFAULT CURRENT_TIMESTAMP::TEXT;
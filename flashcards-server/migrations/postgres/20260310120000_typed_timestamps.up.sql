@@ -0,0 +1,20 @@
+-- Timestamps used to be stored as text in several formats. Postgres parses all of them
+-- ("2026-02-16 18:57:36.123456789 UTC", "2026-02-16 18:57:36+00", ...), so convert the
+-- columns to proper timestamps in place.
+ALTER TABLE flashcards
+  ALTER COLUMN next_review DROP DEFAULT;
+
+ALTER TABLE flashcards
+  ALTER COLUMN next_review TYPE TIMESTAMPTZ USING next_review::TIMESTAMPTZ;
+
+ALTER TABLE flashcards
+  ALTER COLUMN next_review SET DEFAULT CURRENT_TIMESTAMP;
+
+ALTER TABLE card_revisions
+  ALTER COLUMN created_at TYPE TIMESTAMPTZ USING created_at::TIMESTAMPTZ;
+
+ALTER TABLE flashcards
+  ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+ALTER TABLE flashcards
+  ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP;
@@ -0,0 +1,6 @@
+ALTER TABLE flashcards
+  ALTER COLUMN "interval" TYPE INTEGER USING LEAST("interval", 255);
+
+ALTER TABLE flashcards
+  ALTER COLUMN ease_factor TYPE INTEGER USING ROUND(ease_factor),
+  ALTER COLUMN ease_factor SET DEFAULT 3;
@@ -0,0 +1,6 @@
+ALTER TABLE flashcards
+  ALTER COLUMN ease_factor TYPE DOUBLE PRECISION,
+  ALTER COLUMN ease_factor SET DEFAULT 3.0;
+
+ALTER TABLE flashcards
+  ALTER COLUMN "interval" TYPE BIGINT;
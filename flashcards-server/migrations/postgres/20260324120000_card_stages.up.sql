@@ -0,0 +1,6 @@
+-- Cards that exist already have been on a daily schedule, so they start out as review cards.
+ALTER TABLE flashcards
+  ADD COLUMN stage TEXT NOT NULL DEFAULT 'Review';
+
+ALTER TABLE flashcards
+  ADD COLUMN step INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,6 @@
+DROP TABLE reviews;
+
+ALTER TABLE flashcards
+  DROP COLUMN deck_id;
+
+DROP TABLE decks;
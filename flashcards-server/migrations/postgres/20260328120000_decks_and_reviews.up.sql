@@ -0,0 +1,26 @@
+CREATE TABLE decks (
+  id SERIAL PRIMARY KEY,
+  name TEXT NOT NULL,
+  new_cards_per_day INTEGER NOT NULL,
+  reviews_per_day INTEGER NOT NULL
+);
+
+-- Every existing card moves into the default deck.
+INSERT INTO decks (id, name, new_cards_per_day, reviews_per_day) VALUES (1, 'Default', 20, 200);
+SELECT setval(pg_get_serial_sequence('decks', 'id'), (SELECT MAX(id) FROM decks));
+
+ALTER TABLE flashcards
+  ADD COLUMN deck_id INTEGER NOT NULL DEFAULT 1 REFERENCES decks(id);
+
+CREATE INDEX flashcards_deck_id ON flashcards(deck_id);
+
+CREATE TABLE reviews (
+  id SERIAL PRIMARY KEY,
+  card_id INTEGER NOT NULL REFERENCES flashcards(id) ON DELETE CASCADE,
+  difficulty TEXT NOT NULL,
+  stage TEXT NOT NULL,
+  reviewed_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX reviews_card_id ON reviews(card_id);
+CREATE INDEX reviews_reviewed_at ON reviews(reviewed_at);
@@ -0,0 +1,4 @@
+ALTER TABLE reviews
+  DROP COLUMN session_id;
+
+DROP TABLE study_sessions;
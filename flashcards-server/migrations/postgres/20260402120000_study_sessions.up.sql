@@ -0,0 +1,11 @@
+CREATE TABLE study_sessions (
+  id SERIAL PRIMARY KEY,
+  deck_id INTEGER NOT NULL REFERENCES decks(id),
+  started_at TIMESTAMPTZ NOT NULL,
+  ended_at TIMESTAMPTZ
+);
+
+ALTER TABLE reviews
+  ADD COLUMN session_id INTEGER REFERENCES study_sessions(id) ON DELETE SET NULL;
+
+CREATE INDEX reviews_session_id ON reviews(session_id);
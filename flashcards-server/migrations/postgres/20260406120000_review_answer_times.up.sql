@@ -0,0 +1,2 @@
+ALTER TABLE reviews
+  ADD COLUMN answer_time_ms INTEGER;
@@ -0,0 +1,5 @@
+ALTER TABLE reviews
+  DROP COLUMN previous_next_review,
+  DROP COLUMN previous_step,
+  DROP COLUMN previous_interval,
+  DROP COLUMN previous_ease_factor;
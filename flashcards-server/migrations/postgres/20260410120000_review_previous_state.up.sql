@@ -0,0 +1,6 @@
+-- What the card looked like before the answer, so a review can be undone.
+ALTER TABLE reviews
+  ADD COLUMN previous_ease_factor DOUBLE PRECISION,
+  ADD COLUMN previous_interval BIGINT,
+  ADD COLUMN previous_step INTEGER,
+  ADD COLUMN previous_next_review TIMESTAMPTZ;
@@ -0,0 +1,4 @@
+ALTER TABLE flashcards
+  DROP COLUMN flag,
+  DROP COLUMN buried_until,
+  DROP COLUMN suspended;
@@ -0,0 +1,4 @@
+ALTER TABLE flashcards
+  ADD COLUMN suspended BOOLEAN NOT NULL DEFAULT FALSE,
+  ADD COLUMN buried_until TIMESTAMPTZ,
+  ADD COLUMN flag TEXT NOT NULL DEFAULT 'Unflagged';
@@ -0,0 +1,6 @@
+ALTER TABLE reviews
+  DROP COLUMN previous_suspended,
+  DROP COLUMN previous_lapses;
+
+ALTER TABLE flashcards
+  DROP COLUMN lapses;
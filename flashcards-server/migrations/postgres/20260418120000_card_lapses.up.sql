@@ -0,0 +1,7 @@
+ALTER TABLE flashcards
+  ADD COLUMN lapses INTEGER NOT NULL DEFAULT 0;
+
+-- Undoing a review puts these back too.
+ALTER TABLE reviews
+  ADD COLUMN previous_lapses INTEGER,
+  ADD COLUMN previous_suspended BOOLEAN;
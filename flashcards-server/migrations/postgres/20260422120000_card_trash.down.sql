@@ -0,0 +1,3 @@
+DELETE FROM flashcards WHERE deleted_at IS NOT NULL;
+ALTER TABLE flashcards
+  DROP COLUMN deleted_at;
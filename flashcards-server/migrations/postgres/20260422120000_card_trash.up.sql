@@ -0,0 +1,3 @@
+-- Deleted cards stay in the trash until they are restored or purged.
+ALTER TABLE flashcards
+  ADD COLUMN deleted_at TIMESTAMPTZ;
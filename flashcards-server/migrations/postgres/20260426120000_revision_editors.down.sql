@@ -0,0 +1,2 @@
+ALTER TABLE card_revisions
+  DROP COLUMN editor;
@@ -0,0 +1,4 @@
+ALTER TABLE reviews
+  DROP COLUMN cram;
+ALTER TABLE study_sessions
+  DROP COLUMN kind;
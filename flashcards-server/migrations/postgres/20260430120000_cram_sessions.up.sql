@@ -0,0 +1,5 @@
+-- Scheduled sessions study today's queue, cram sessions drill cards without rescheduling them.
+ALTER TABLE study_sessions
+  ADD COLUMN kind TEXT NOT NULL DEFAULT 'Scheduled';
+ALTER TABLE reviews
+  ADD COLUMN cram BOOLEAN NOT NULL DEFAULT FALSE;
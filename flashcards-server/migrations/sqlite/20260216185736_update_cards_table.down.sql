@@ -0,0 +1,8 @@
+ALTER TABLE flashcards
+  DROP COLUMN next_review;
+
+ALTER TABLE flashcards
+  DROP COLUMN interval;
+
+ALTER TABLE flashcards
+  DROP COLUMN ease_factor;
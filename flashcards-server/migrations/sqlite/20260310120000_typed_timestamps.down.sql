@@ -0,0 +1,6 @@
+-- The normalised next_review values are kept, they are still valid timestamps.
+ALTER TABLE flashcards
+  DROP COLUMN updated_at;
+
+ALTER TABLE flashcards
+  DROP COLUMN created_at;
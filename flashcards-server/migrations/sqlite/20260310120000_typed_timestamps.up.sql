@@ -0,0 +1,24 @@
+-- Timestamps used to be written in several formats ("2026-02-16 18:57:36",
+-- "2026-02-16 18:57:36.123456789 UTC", ...). Rewrite them all as RFC 3339 in UTC, the
+-- format sqlx uses for DateTime<Utc>, so they sort and compare correctly as text.
+UPDATE flashcards
+  SET next_review = COALESCE(
+    strftime('%Y-%m-%dT%H:%M:%f+00:00', substr(next_review, 1, 19)),
+    strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+  );
+
+UPDATE card_revisions
+  SET created_at = COALESCE(
+    strftime('%Y-%m-%dT%H:%M:%f+00:00', substr(created_at, 1, 19)),
+    strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+  );
+
+ALTER TABLE flashcards
+  ADD COLUMN created_at TEXT;
+
+ALTER TABLE flashcards
+  ADD COLUMN updated_at TEXT;
+
+UPDATE flashcards
+  SET created_at = strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'),
+      updated_at = strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now');
@@ -0,0 +1,14 @@
+ALTER TABLE flashcards
+  ADD COLUMN ease_factor_int int DEFAULT 3;
+
+UPDATE flashcards
+  SET ease_factor_int = CAST(ROUND(ease_factor) AS INTEGER);
+
+ALTER TABLE flashcards
+  DROP COLUMN ease_factor;
+
+ALTER TABLE flashcards
+  RENAME COLUMN ease_factor_int TO ease_factor;
+
+UPDATE flashcards
+  SET interval = MIN(interval, 255);
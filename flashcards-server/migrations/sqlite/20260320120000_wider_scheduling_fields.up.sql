@@ -0,0 +1,13 @@
+-- The ease factor becomes fractional. SQLite can't change a column's type in place, so the
+-- column is rebuilt with REAL affinity. Intervals already fit in an INTEGER column.
+ALTER TABLE flashcards
+  ADD COLUMN ease_factor_real REAL NOT NULL DEFAULT 3.0;
+
+UPDATE flashcards
+  SET ease_factor_real = CAST(COALESCE(ease_factor, 3) AS REAL);
+
+ALTER TABLE flashcards
+  DROP COLUMN ease_factor;
+
+ALTER TABLE flashcards
+  RENAME COLUMN ease_factor_real TO ease_factor;
@@ -0,0 +1,5 @@
+ALTER TABLE flashcards
+  DROP COLUMN step;
+
+ALTER TABLE flashcards
+  DROP COLUMN stage;
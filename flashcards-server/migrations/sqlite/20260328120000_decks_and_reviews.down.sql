@@ -0,0 +1,8 @@
+DROP TABLE reviews;
+
+DROP INDEX flashcards_deck_id;
+
+ALTER TABLE flashcards
+  DROP COLUMN deck_id;
+
+DROP TABLE decks;
@@ -0,0 +1,27 @@
+CREATE TABLE decks (
+  id integer primary key autoincrement,
+  name TEXT NOT NULL,
+  new_cards_per_day INTEGER NOT NULL,
+  reviews_per_day INTEGER NOT NULL
+);
+
+-- Every existing card moves into the default deck.
+INSERT INTO decks (id, name, new_cards_per_day, reviews_per_day) VALUES (1, 'Default', 20, 200);
+
+-- SQLite can't add a column with both a REFERENCES clause and a non-null default, the
+-- handlers make sure the deck exists instead.
+ALTER TABLE flashcards
+  ADD COLUMN deck_id INTEGER NOT NULL DEFAULT 1;
+
+CREATE INDEX flashcards_deck_id ON flashcards(deck_id);
+
+CREATE TABLE reviews (
+  id integer primary key autoincrement,
+  card_id integer NOT NULL REFERENCES flashcards(id) ON DELETE CASCADE,
+  difficulty TEXT NOT NULL,
+  stage TEXT NOT NULL,
+  reviewed_at TEXT NOT NULL
+);
+
+CREATE INDEX reviews_card_id ON reviews(card_id);
+CREATE INDEX reviews_reviewed_at ON reviews(reviewed_at);
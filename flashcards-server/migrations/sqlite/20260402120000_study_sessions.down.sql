@@ -0,0 +1,6 @@
+DROP INDEX reviews_session_id;
+
+ALTER TABLE reviews
+  DROP COLUMN session_id;
+
+DROP TABLE study_sessions;
@@ -0,0 +1,11 @@
+CREATE TABLE study_sessions (
+  id integer primary key autoincrement,
+  deck_id integer NOT NULL REFERENCES decks(id),
+  started_at TEXT NOT NULL,
+  ended_at TEXT
+);
+
+ALTER TABLE reviews
+  ADD COLUMN session_id INTEGER REFERENCES study_sessions(id) ON DELETE SET NULL;
+
+CREATE INDEX reviews_session_id ON reviews(session_id);
@@ -0,0 +1,4 @@
+ALTER TABLE reviews DROP COLUMN previous_next_review;
+ALTER TABLE reviews DROP COLUMN previous_step;
+ALTER TABLE reviews DROP COLUMN previous_interval;
+ALTER TABLE reviews DROP COLUMN previous_ease_factor;
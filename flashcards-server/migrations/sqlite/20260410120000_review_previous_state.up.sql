@@ -0,0 +1,5 @@
+-- What the card looked like before the answer, so a review can be undone.
+ALTER TABLE reviews ADD COLUMN previous_ease_factor REAL;
+ALTER TABLE reviews ADD COLUMN previous_interval INTEGER;
+ALTER TABLE reviews ADD COLUMN previous_step INTEGER;
+ALTER TABLE reviews ADD COLUMN previous_next_review TEXT;
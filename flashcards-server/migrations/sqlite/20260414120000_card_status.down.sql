@@ -0,0 +1,3 @@
+ALTER TABLE flashcards DROP COLUMN flag;
+ALTER TABLE flashcards DROP COLUMN buried_until;
+ALTER TABLE flashcards DROP COLUMN suspended;
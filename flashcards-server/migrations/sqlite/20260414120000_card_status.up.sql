@@ -0,0 +1,3 @@
+ALTER TABLE flashcards ADD COLUMN suspended INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE flashcards ADD COLUMN buried_until TEXT;
+ALTER TABLE flashcards ADD COLUMN flag TEXT NOT NULL DEFAULT 'Unflagged';
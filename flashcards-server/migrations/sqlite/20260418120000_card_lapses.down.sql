@@ -0,0 +1,4 @@
+ALTER TABLE reviews DROP COLUMN previous_suspended;
+ALTER TABLE reviews DROP COLUMN previous_lapses;
+
+ALTER TABLE flashcards DROP COLUMN lapses;
@@ -0,0 +1,5 @@
+ALTER TABLE flashcards ADD COLUMN lapses INTEGER NOT NULL DEFAULT 0;
+
+-- Undoing a review puts these back too.
+ALTER TABLE reviews ADD COLUMN previous_lapses INTEGER;
+ALTER TABLE reviews ADD COLUMN previous_suspended INTEGER;
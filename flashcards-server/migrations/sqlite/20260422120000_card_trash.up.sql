@@ -0,0 +1,2 @@
+-- Deleted cards stay in the trash until they are restored or purged.
+ALTER TABLE flashcards ADD COLUMN deleted_at TEXT;
@@ -0,0 +1 @@
+ALTER TABLE card_revisions DROP COLUMN editor;
@@ -0,0 +1,2 @@
+-- Who made the edit that replaced this content. Older revisions don't know.
+ALTER TABLE card_revisions ADD COLUMN editor TEXT;
@@ -0,0 +1,2 @@
+ALTER TABLE reviews DROP COLUMN cram;
+ALTER TABLE study_sessions DROP COLUMN kind;
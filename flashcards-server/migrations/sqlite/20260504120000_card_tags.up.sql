@@ -0,0 +1,8 @@
+-- Free-form labels on cards, a card has each tag at most once.
+CREATE TABLE card_tags (
+  card_id integer NOT NULL REFERENCES flashcards(id) ON DELETE CASCADE,
+  tag TEXT NOT NULL,
+  PRIMARY KEY (card_id, tag)
+);
+
+CREATE INDEX card_tags_tag ON card_tags(tag);
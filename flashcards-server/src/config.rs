@@ -0,0 +1,305 @@
+use chrono_tz::Tz;
+use clap::{Parser, Subcommand};
+use flashcards_data::SchedulerSettings;
+use http::header::HeaderValue;
+use log::LevelFilter;
+use serde::Deserialize;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_CONFIG_FILE: &str = "flashcards.toml";
+
+#[derive(Parser, Debug)]
+#[command(version, about = "REST API for serving flashcards over HTTP")]
+pub struct Cli {
+    #[command(flatten)]
+    pub args: Args,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Serve the API (the default when no command is given)
+    Serve,
+    /// Inspect or change the database schema
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum MigrateCommand {
+    /// List every migration and whether it has been applied
+    Status,
+    /// Apply all pending migrations
+    Run,
+    /// Roll back the latest migration, or every migration newer than --target
+    Revert {
+        #[arg(long)]
+        target: Option<i64>,
+    },
+}
+
+// Command line flags. Every flag can also be given through the environment variable named
+// next to it; flags win over the environment, and both win over the config file.
+#[derive(clap::Args, Debug, Default)]
+pub struct Args {
+    /// Path to a TOML config file (defaults to ./flashcards.toml when present)
+    #[arg(long, env = "FLASHCARDS_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Address to listen on, e.g. 0.0.0.0:3000
+    #[arg(long, env = "FLASHCARDS_LISTEN_ADDRESS")]
+    pub listen_address: Option<String>,
+    /// Database url: sqlite://..., postgres://... or memory:
+    #[arg(long, env = "DATABASE_URL")]
+    pub database_url: Option<String>,
+    /// Maximum number of pooled database connections
+    #[arg(long, env = "FLASHCARDS_MAX_CONNECTIONS")]
+    pub max_connections: Option<u32>,
+    /// Origin allowed to call the API; repeat the flag (or comma separate) for several
+    #[arg(long = "allowed-origin", env = "FLASHCARDS_ALLOWED_ORIGINS", value_delimiter = ',')]
+    pub allowed_origins: Option<Vec<String>>,
+    /// Directory used to store uploaded media
+    #[arg(long, env = "FLASHCARDS_MEDIA_DIR")]
+    pub media_dir: Option<PathBuf>,
+    /// One of off, error, warn, info, debug, trace
+    #[arg(long, env = "FLASHCARDS_LOG_LEVEL")]
+    pub log_level: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: String::from("sqlite://flashcards.db"),
+            max_connections: 5,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StudyConfig {
+    // IANA name of the learner's timezone, e.g. `Europe/Berlin`.
+    pub timezone: String,
+    // Local hour at which a new study day begins, so a late night session still counts
+    // towards the day it started in.
+    pub rollover_hour: u32,
+    // Longer answer times are stored as this, a card left on screen during a break
+    // shouldn't count as one slow answer.
+    pub maximum_answer_seconds: u32,
+}
+
+impl Default for StudyConfig {
+    fn default() -> Self {
+        Self {
+            timezone: String::from("UTC"),
+            rollover_hour: 4,
+            maximum_answer_seconds: 60,
+        }
+    }
+}
+
+impl StudyConfig {
+    pub fn timezone(&self) -> Tz {
+        self.timezone.parse().unwrap_or(Tz::UTC)
+    }
+
+    pub fn cap_answer_time(&self, answer_time_ms: u32) -> u32 {
+        answer_time_ms.min(self.maximum_answer_seconds.saturating_mul(1000))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrashConfig {
+    // Deleted cards can be restored for this many days, after that they are purged for good.
+    pub retention_days: u32,
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            retention_days: 30,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub listen_address: String,
+    pub allowed_origins: Vec<String>,
+    pub media_dir: PathBuf,
+    pub log_level: String,
+    pub database: DatabaseConfig,
+    pub scheduler: SchedulerSettings,
+    pub study: StudyConfig,
+    pub trash: TrashConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            listen_address: String::from("0.0.0.0:3000"),
+            allowed_origins: vec![String::from("http://localhost:8080")],
+            media_dir: PathBuf::from("media"),
+            log_level: String::from("info"),
+            database: DatabaseConfig::default(),
+            scheduler: SchedulerSettings::default(),
+            study: StudyConfig::default(),
+            trash: TrashConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, error) => write!(f, "could not read {}: {}", path.display(), error),
+            ConfigError::Parse(path, error) => write!(f, "could not parse {}: {}", path.display(), error),
+            ConfigError::Invalid(problems) => {
+                writeln!(f, "invalid configuration:")?;
+                for problem in problems {
+                    writeln!(f, "  - {}", problem)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    // Builds the config from defaults, then the config file, then the environment and flags.
+    pub fn load(args: &Args) -> Result<Self, ConfigError> {
+        let mut config = match &args.config {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?,
+            None => Self::default(),
+        };
+
+        config.apply_args(args);
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| ConfigError::Read(path.to_path_buf(), error))?;
+
+        toml::from_str(&contents)
+            .map_err(|error| ConfigError::Parse(path.to_path_buf(), error))
+    }
+
+    fn apply_args(&mut self, args: &Args) {
+        if let Some(listen_address) = &args.listen_address {
+            self.listen_address = listen_address.clone();
+        }
+        if let Some(database_url) = &args.database_url {
+            self.database.url = database_url.clone();
+        }
+        if let Some(max_connections) = args.max_connections {
+            self.database.max_connections = max_connections;
+        }
+        if let Some(allowed_origins) = &args.allowed_origins {
+            self.allowed_origins = allowed_origins.clone();
+        }
+        if let Some(media_dir) = &args.media_dir {
+            self.media_dir = media_dir.clone();
+        }
+        if let Some(log_level) = &args.log_level {
+            self.log_level = log_level.clone();
+        }
+    }
+
+    // Collects every problem rather than stopping at the first, so a broken deployment can
+    // be fixed in one go.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+
+        if self.listen_address.parse::<SocketAddr>().is_err() {
+            problems.push(format!("listen_address `{}` is not a valid socket address", self.listen_address));
+        }
+
+        let scheme = self.database.url.split(':').next().unwrap_or_default();
+        if !["sqlite", "postgres", "postgresql", "memory"].contains(&scheme) {
+            problems.push(format!("database.url `{}` must start with sqlite:, postgres: or memory:", self.database.url));
+        }
+
+        if self.database.max_connections == 0 {
+            problems.push(String::from("database.max_connections must be at least 1"));
+        }
+
+        if self.allowed_origins.is_empty() {
+            problems.push(String::from("allowed_origins must contain at least one origin"));
+        }
+
+        for origin in &self.allowed_origins {
+            let is_http = origin.starts_with("http://") || origin.starts_with("https://");
+            if !is_http || origin.parse::<HeaderValue>().is_err() {
+                problems.push(format!("allowed origin `{}` must be an http(s) url", origin));
+            }
+        }
+
+        if self.media_dir.as_os_str().is_empty() {
+            problems.push(String::from("media_dir must not be empty"));
+        }
+
+        if self.log_level.parse::<LevelFilter>().is_err() {
+            problems.push(format!("log_level `{}` must be one of off, error, warn, info, debug, trace", self.log_level));
+        }
+
+        for problem in self.scheduler.problems() {
+            problems.push(format!("scheduler.{}", problem));
+        }
+
+        if self.study.timezone.parse::<Tz>().is_err() {
+            problems.push(format!("study.timezone `{}` is not a known timezone", self.study.timezone));
+        }
+
+        if self.study.rollover_hour > 23 {
+            problems.push(String::from("study.rollover_hour must be between 0 and 23"));
+        }
+
+        if !(1..=3600).contains(&self.study.maximum_answer_seconds) {
+            problems.push(String::from("study.maximum_answer_seconds must be between 1 and 3600"));
+        }
+
+        if !(1..=3650).contains(&self.trash.retention_days) {
+            problems.push(String::from("trash.retention_days must be between 1 and 3650"));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+
+    pub fn log_level(&self) -> LevelFilter {
+        self.log_level.parse().unwrap_or(LevelFilter::Info)
+    }
+
+    pub fn allowed_origins(&self) -> Vec<HeaderValue> {
+        self.allowed_origins.iter()
+            .filter_map(|origin| origin.parse().ok())
+            .collect()
+    }
+}
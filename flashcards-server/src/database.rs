@@ -1,19 +1,28 @@
-
-use flashcards_data::Card;
-use async_std::task;
-use sqlx::{
-    migrate::MigrateDatabase,
-    sqlite::SqlitePoolOptions,
-    Sqlite,
-    QueryBuilder,
-    Pool,
-};
-use crate::DB_URL;
+use flashcards_data::{Card, CardFlag, CardReview, CardRevision, Deck, StudySession, UpdateCardPayload};
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use std::fmt;
+use std::sync::Arc;
+
+mod memory;
+mod migrations;
+mod postgres;
+mod sqlite;
 
-#[derive(Debug, Default)]
+pub use memory::MemoryStore;
+pub use postgres::PostgresStore;
+pub use sqlite::SqliteStore;
+
+#[derive(Clone, Debug, Default)]
 pub struct GetCardFilters {
-    from: Option<DateTime<Utc>>
+    from: Option<DateTime<Utc>>,
+    deck_id: Option<u32>,
+    suspended: Option<bool>,
+    buried: Option<(bool, DateTime<Utc>)>,
+    flag: Option<CardFlag>,
+    leech: Option<(bool, u32)>,
+    // Lists the trash instead of the live cards.
+    trashed: bool,
 }
 
 impl GetCardFilters {
@@ -21,185 +30,273 @@ impl GetCardFilters {
         self.from = Some(from);
         self
     }
+    pub fn add_deck(mut self, deck_id: u32) -> Self {
+        self.deck_id = Some(deck_id);
+        self
+    }
+    pub fn add_suspended(mut self, suspended: bool) -> Self {
+        self.suspended = Some(suspended);
+        self
+    }
+    // Only cards that are (or aren't) buried at `at`.
+    pub fn add_buried(mut self, buried: bool, at: DateTime<Utc>) -> Self {
+        self.buried = Some((buried, at));
+        self
+    }
+    pub fn add_flag(mut self, flag: CardFlag) -> Self {
+        self.flag = Some(flag);
+        self
+    }
+    // Only cards that are (or aren't) leeches, i.e. have at least `threshold` lapses.
+    pub fn add_leech(mut self, leech: bool, threshold: u32) -> Self {
+        self.leech = Some((leech, threshold));
+        self
+    }
+    pub fn add_trashed(mut self) -> Self {
+        self.trashed = true;
+        self
+    }
+    // Cards that can be studied at `now`: neither suspended nor buried.
+    pub fn add_studyable(self, now: DateTime<Utc>) -> Self {
+        self.add_suspended(false)
+            .add_buried(false, now)
+    }
+    pub fn from(&self) -> Option<DateTime<Utc>> {
+        self.from
+    }
+    pub fn deck_id(&self) -> Option<u32> {
+        self.deck_id
+    }
+    pub fn suspended(&self) -> Option<bool> {
+        self.suspended
+    }
+    pub fn buried(&self) -> Option<(bool, DateTime<Utc>)> {
+        self.buried
+    }
+    pub fn flag(&self) -> Option<CardFlag> {
+        self.flag
+    }
+    pub fn leech(&self) -> Option<(bool, u32)> {
+        self.leech
+    }
+    pub fn trashed(&self) -> bool {
+        self.trashed
+    }
 }
 
-#[derive(Debug, Default)]
-pub struct Database {
-    pool: Option<Pool<Sqlite>>,
+#[derive(Clone, Debug, Default)]
+pub struct GetReviewFilters {
+    deck_id: Option<u32>,
+    card_id: Option<u32>,
+    session_id: Option<u32>,
+    since: Option<DateTime<Utc>>,
 }
 
-impl Database {
-
-    pub fn remove_card(&self, card_id: u32) {
-        task::block_on(async {
-            if let Some(pool) = self.pool.clone() {
-                let result = sqlx::query("DELETE FROM flashcards WHERE id = ?")
-                    .bind(card_id)
-                    .execute(&pool)
-                    .await;
-
-                println!("Result {:?}", result);
-            }
-        });
-    }
-
-    pub fn update_card(&self, card: &Card) {
-        task::block_on(async {
-            if let Some(pool) = self.pool.clone() {
-                let result = sqlx::query(
-                        "UPDATE flashcards SET 
-                            front_of_card = ?,
-                            back_of_card = ?,
-                            interval = ?,
-                            ease_factor = ?,
-                            next_review = ?
-                        WHERE id = ?"
-                    )
-                    .bind(card.front())
-                    .bind(card.back())
-                    .bind(card.interval())
-                    .bind(card.ease_factor())
-                    .bind(card.next_review())
-                    .bind(card.id())
-                    .execute(&pool)
-                    .await;
-
-                println!("Result {:?}", result);
-            }
-        });
-    }
-
-    pub fn add_card(&self, card: &Card) {
-        task::block_on(async {
-            if let Some(pool) = self.pool.clone() {
-                let result = sqlx::query("INSERT INTO flashcards (
-                        front_of_card,
-                        back_of_card,
-                        next_review
-                    ) VALUES (
-                        ?, 
-                        ?,
-                        ?
-                    )")
-                    .bind(card.front())
-                    .bind(card.back())
-                    .bind(card.next_review())
-                    .execute(&pool)
-                    .await;
-
-                println!("Result {:?}", result);
-            }
-        });
-    }
-
-    pub fn get_card(&self, id: u32) -> Option<Card> {
-        task::block_on(async {
-            if let Some(pool) = self.pool.clone() {
-                let card = sqlx::query_as::<_, Card>(
-                        "SELECT id, front_of_card as front, back_of_card as back, ease_factor, interval, next_review FROM flashcards WHERE id = ?"
-                    )
-                    .bind(id)
-                    .fetch_one(&pool).await.unwrap();
-
-                return Some(card);
-
-            }
-
-            None
-
-        })
-
-    }
-
-    pub fn get_cards(&self, filters: GetCardFilters) -> Vec<Card> {
-        task::block_on(async {
-            if let Some(pool) = self.pool.clone() {
-
-                let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new("
-                    SELECT id, front_of_card as front, back_of_card as back, ease_factor, interval, next_review
-                    FROM flashcards
-                    WHERE 1=1 
-                ");
-
-                if let Some(from) = filters.from {
-                    query_builder.push(" AND next_review < ");
-                    query_builder.push_bind(format!("{}", from));
-                }
-
-                //println!("{:?}", query_builder);
-
-
-                let query = query_builder.build_query_as::<Card>();
-                let cards = query
-                    .fetch_all(&pool)
-                    .await
-                    .unwrap();
-                
-                return cards;
-
-            }
-
-            vec![]
-        })
-    }
-
-    async fn migrate_db(&mut self) {
-        if let Some(pool) = self.pool.clone() {
-
-            let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
-
-            println!("Crate Dir {}", crate_dir);
-            let migrations = std::path::Path::new(&crate_dir).join("migrations");
-
-            println!("Migrations Dir: {:?}", migrations);
-
-            let migration_result = sqlx::migrate::Migrator::new(migrations)
-                .await
-                .unwrap()
-                .run(&pool)
-                .await;
-               
-            match migration_result {
-                Ok(_) => println!("Migration success!"),
-                Err(error) => panic!("Migration Error: {}", error),
-            }
+impl GetReviewFilters {
+    pub fn add_deck(mut self, deck_id: u32) -> Self {
+        self.deck_id = Some(deck_id);
+        self
+    }
+    pub fn add_card(mut self, card_id: u32) -> Self {
+        self.card_id = Some(card_id);
+        self
+    }
+    pub fn add_session(mut self, session_id: u32) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+    // Only reviews made at or after `since`.
+    pub fn add_since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+    pub fn deck_id(&self) -> Option<u32> {
+        self.deck_id
+    }
+    pub fn card_id(&self) -> Option<u32> {
+        self.card_id
+    }
+    pub fn session_id(&self) -> Option<u32> {
+        self.session_id
+    }
+    pub fn since(&self) -> Option<DateTime<Utc>> {
+        self.since
+    }
+}
 
-        }
+// Status changes to an existing card. Fields left out are kept as they are.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CardEdit {
+    pub deck_id: Option<u32>,
+    pub flag: Option<CardFlag>,
+    pub suspended: Option<bool>,
+}
 
+impl CardEdit {
+    pub fn apply(&self, card: &mut Card) {
+        if let Some(deck_id) = self.deck_id {
+            card.set_deck_id(deck_id);
+        }
+        if let Some(flag) = self.flag {
+            card.set_flag(flag);
+        }
+        if let Some(suspended) = self.suspended {
+            card.set_suspended(suspended);
+        }
     }
+}
+
+// One change of a batch. New cards are built by the handler, the store only saves them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CardChange {
+    Create(Card),
+    UpdateContent(u32, UpdateCardPayload),
+    Trash(u32),
+    Edit(u32, CardEdit),
+    // Tags to add, then tags to remove, both already normalized.
+    Tag(u32, Vec<String>, Vec<String>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub applied: bool,
+    // False when the database has a migration applied that this binary doesn't ship.
+    pub known: bool,
+}
 
-    pub fn new() -> Self {
-        task::block_on(async {
-            if !Sqlite::database_exists(DB_URL).await.unwrap_or(false) {
-                println!("Creating DB {}", DB_URL);
-                match Sqlite::create_database(DB_URL).await {
-                    Ok(_) => println!("Created DB"),
-                    Err(error) => panic!("Error: {}", error),
-                }
-            } else {
-                println!("DB already exists");
-            }
+#[derive(Debug)]
+pub enum StoreError {
+    NotFound,
+    // The request can't be carried out as asked, e.g. an empty deck name.
+    Invalid(String),
+    // A new card would repeat these cards.
+    Duplicate(Vec<Card>),
+    UnsupportedUrl(String),
+    SchemaAhead { database: i64, binary: i64 },
+    Database(sqlx::Error),
+    Migration(sqlx::migrate::MigrateError),
+}
 
-            let pool = SqlitePoolOptions::new()
-                .max_connections(5)
-                .connect(DB_URL).await;
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::NotFound => write!(f, "not found"),
+            StoreError::Invalid(reason) => write!(f, "{}", reason),
+            StoreError::Duplicate(_) => write!(f, "the deck already has a card with this front"),
+            StoreError::UnsupportedUrl(url) => write!(f, "unsupported database url: {}", url),
+            StoreError::SchemaAhead { database, binary } => write!(
+                f,
+                "database schema is at migration {} but this binary only knows up to {}, upgrade the server",
+                database,
+                binary,
+            ),
+            StoreError::Database(error) => write!(f, "database error: {}", error),
+            StoreError::Migration(error) => write!(f, "migration error: {}", error),
+        }
+    }
+}
 
+impl std::error::Error for StoreError {}
 
-            if let Ok(pool) = pool {
+impl StoreError {
+    // Whether the request was turned down, as opposed to the store failing. A batch carries on
+    // past rejected changes.
+    pub fn is_rejection(&self) -> bool {
+        matches!(self, StoreError::NotFound | StoreError::Invalid(_) | StoreError::Duplicate(_))
+    }
+}
 
-                let mut new_db = Self {
-                    pool: Some(pool)
-                };
+impl From<sqlx::Error> for StoreError {
+    fn from(error: sqlx::Error) -> Self {
+        match error {
+            sqlx::Error::RowNotFound => StoreError::NotFound,
+            error => StoreError::Database(error),
+        }
+    }
+}
 
-                Self::migrate_db(&mut new_db).await;
+impl From<sqlx::migrate::MigrateError> for StoreError {
+    fn from(error: sqlx::migrate::MigrateError) -> Self {
+        StoreError::Migration(error)
+    }
+}
 
-                new_db
+pub type StoreResult<T> = Result<T, StoreError>;
+
+// Storage backend for cards.
+//
+// Every backend (SQLite, Postgres and the in-memory store used for tests) implements this
+// trait, so the handlers only ever talk to a `dyn CardStore` and never to a concrete pool.
+#[async_trait]
+pub trait CardStore: Send + Sync {
+    // Cards in the trash are only found by `get_cards` with `add_trashed`, everything else
+    // treats them as gone.
+    async fn get_card(&self, id: u32) -> StoreResult<Card>;
+    async fn get_cards(&self, filters: GetCardFilters) -> StoreResult<Vec<Card>>;
+    // Inserts the card and returns the stored row; the id on `card` is ignored and the
+    // backend assigns a new one.
+    async fn add_card(&self, card: &Card) -> StoreResult<Card>;
+    async fn update_card(&self, card: &Card) -> StoreResult<()>;
+    // Moves the card to the trash, keeping its history.
+    async fn remove_card(&self, id: u32, deleted_at: DateTime<Utc>) -> StoreResult<()>;
+    // Takes a card out of the trash and returns it.
+    async fn restore_card(&self, id: u32) -> StoreResult<Card>;
+    // Deletes the cards that went into the trash before `deleted_before`, together with their
+    // revisions and reviews. Returns how many cards were purged.
+    async fn purge_trash(&self, deleted_before: DateTime<Utc>) -> StoreResult<u64>;
+    // Changes only the content of a card, keeping its scheduling state, and records the
+    // previous content as a revision made by `editor`. Returns the updated card.
+    async fn update_card_content(&self, id: u32, update: &UpdateCardPayload, editor: Option<&str>) -> StoreResult<Card>;
+    // Applies every change in a single transaction and returns the outcome of each, in order.
+    // Rejected changes (a missing card or deck) are skipped and reported, any other error rolls
+    // the whole batch back.
+    async fn apply_changes(&self, changes: &[CardChange], editor: Option<&str>, now: DateTime<Utc>) -> StoreResult<Vec<StoreResult<Card>>>;
+    // Earlier content of a card, oldest first.
+    async fn get_card_revisions(&self, card_id: u32) -> StoreResult<Vec<CardRevision>>;
+
+    // Saves the new scheduling state of a card together with the answer that produced it.
+    // The id on `review` is ignored, the stored review is returned.
+    async fn record_review(&self, card: &Card, review: &CardReview) -> StoreResult<CardReview>;
+    async fn get_review(&self, id: u32) -> StoreResult<CardReview>;
+    // Puts the restored scheduling state of a card back and deletes the review that is undone.
+    async fn undo_review(&self, card: &Card, review_id: u32) -> StoreResult<()>;
+    // Oldest first.
+    async fn get_reviews(&self, filters: GetReviewFilters) -> StoreResult<Vec<CardReview>>;
+
+    async fn get_decks(&self) -> StoreResult<Vec<Deck>>;
+    async fn get_deck(&self, id: u32) -> StoreResult<Deck>;
+    // Like `add_card`, the id on `deck` is ignored.
+    async fn add_deck(&self, deck: &Deck) -> StoreResult<Deck>;
+    async fn update_deck(&self, deck: &Deck) -> StoreResult<()>;
+
+    // The id on `session` is ignored.
+    async fn add_session(&self, session: &StudySession) -> StoreResult<StudySession>;
+    async fn get_session(&self, id: u32) -> StoreResult<StudySession>;
+    async fn finish_session(&self, id: u32, ended_at: DateTime<Utc>) -> StoreResult<StudySession>;
+
+    // Schema management. Backends without a schema (the in-memory store) keep the defaults.
+    async fn migration_status(&self) -> StoreResult<Vec<MigrationStatus>> {
+        Ok(vec![])
+    }
+    async fn run_migrations(&self) -> StoreResult<()> {
+        Ok(())
+    }
+    async fn revert_migrations(&self, _target: Option<i64>) -> StoreResult<()> {
+        Ok(())
+    }
+}
 
-                
+// Picks a backend from the scheme of the database url, e.g. `sqlite://flashcards.db`,
+// `postgres://user@host/flashcards` or `memory:`.
+pub async fn connect(url: &str, max_connections: u32) -> StoreResult<Arc<dyn CardStore>> {
+    let scheme = url.split(':').next().unwrap_or_default();
 
-            } else {
-                panic!("could not create db");
-            }
-        })
+    match scheme {
+        "sqlite" => Ok(Arc::new(SqliteStore::connect(url, max_connections).await?)),
+        "postgres" | "postgresql" => Ok(Arc::new(PostgresStore::connect(url, max_connections).await?)),
+        "memory" => Ok(Arc::new(MemoryStore::default())),
+        _ => Err(StoreError::UnsupportedUrl(url.to_string())),
     }
 }
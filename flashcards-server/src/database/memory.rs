@@ -0,0 +1,467 @@
+use flashcards_data::{Card, CardReview, CardRevision, Deck, StudySession, UpdateCardPayload, DEFAULT_DECK_ID};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use crate::database::{CardChange, CardStore, GetCardFilters, GetReviewFilters, StoreError, StoreResult};
+
+#[derive(Debug)]
+struct MemoryTables {
+    last_id: u32,
+    cards: BTreeMap<u32, Card>,
+    last_revision_id: u32,
+    revisions: Vec<CardRevision>,
+    last_review_id: u32,
+    reviews: Vec<CardReview>,
+    last_deck_id: u32,
+    decks: BTreeMap<u32, Deck>,
+    last_session_id: u32,
+    sessions: BTreeMap<u32, StudySession>,
+}
+
+impl Default for MemoryTables {
+    // Like the migrations, start out with the default deck.
+    fn default() -> Self {
+        let default_deck = Deck::new(DEFAULT_DECK_ID, String::from("Default"));
+
+        Self {
+            last_id: 0,
+            cards: BTreeMap::new(),
+            last_revision_id: 0,
+            revisions: Vec::new(),
+            last_review_id: 0,
+            reviews: Vec::new(),
+            last_deck_id: DEFAULT_DECK_ID,
+            decks: BTreeMap::from([(DEFAULT_DECK_ID, default_deck)]),
+            last_session_id: 0,
+            sessions: BTreeMap::new(),
+        }
+    }
+}
+
+// The parts of the card methods that batches need as well, run with the lock already held.
+impl MemoryTables {
+    fn live_card_mut(&mut self, id: u32) -> StoreResult<&mut Card> {
+        self.cards.get_mut(&id)
+            .filter(|card| card.deleted_at().is_none())
+            .ok_or(StoreError::NotFound)
+    }
+
+    fn require_deck(&self, deck_id: u32) -> StoreResult<()> {
+        match self.decks.contains_key(&deck_id) {
+            true => Ok(()),
+            false => Err(StoreError::Invalid(format!("deck {} does not exist", deck_id))),
+        }
+    }
+
+    fn insert_card(&mut self, card: &Card) -> Card {
+        // Mirror AUTOINCREMENT: ids are never reused, even after a delete.
+        self.last_id += 1;
+        let id = self.last_id;
+
+        let mut new_card = Card::new(id, card.front().to_string(), card.back().to_string());
+        new_card.set_deck_id(*card.deck_id());
+        new_card.set_ease_factor(*card.ease_factor());
+        new_card.set_interval(*card.interval());
+        new_card.set_stage(*card.stage());
+        new_card.set_step(*card.step());
+        new_card.set_lapses(*card.lapses());
+        new_card.set_next_review(*card.next_review());
+        new_card.set_created_at(*card.created_at());
+        new_card.set_updated_at(*card.updated_at());
+        new_card.set_suspended(*card.suspended());
+        new_card.set_buried_until(*card.buried_until());
+        new_card.set_flag(*card.flag());
+        new_card.set_deleted_at(*card.deleted_at());
+        new_card.set_tags(card.tags().to_vec());
+
+        self.cards.insert(id, new_card.clone());
+
+        new_card
+    }
+
+    fn update_content(&mut self, id: u32, update: &UpdateCardPayload, editor: Option<&str>) -> StoreResult<Card> {
+        let card = self.live_card_mut(id)?.clone();
+
+        if !update.changes(&card) {
+            return Ok(card);
+        }
+
+        self.last_revision_id += 1;
+
+        let revision = CardRevision {
+            id: self.last_revision_id,
+            card_id: id,
+            front: card.front().to_string(),
+            back: card.back().to_string(),
+            created_at: Utc::now(),
+            editor: editor.map(String::from),
+        };
+        self.revisions.push(revision);
+
+        let mut updated_card = card;
+        updated_card.set_updated_at(Utc::now());
+        if let Some(front) = &update.front {
+            updated_card.set_front(front);
+        }
+        if let Some(back) = &update.back {
+            updated_card.set_back(back);
+        }
+
+        self.cards.insert(id, updated_card.clone());
+
+        Ok(updated_card)
+    }
+
+    fn trash_card(&mut self, id: u32, deleted_at: DateTime<Utc>) -> StoreResult<Card> {
+        let card = self.live_card_mut(id)?;
+
+        card.set_deleted_at(Some(deleted_at));
+
+        Ok(card.clone())
+    }
+
+    fn apply_change(&mut self, change: &CardChange, editor: Option<&str>, now: DateTime<Utc>) -> StoreResult<Card> {
+        match change {
+            CardChange::Create(card) => {
+                self.require_deck(*card.deck_id())?;
+                Ok(self.insert_card(card))
+            },
+            CardChange::UpdateContent(id, update) => self.update_content(*id, update, editor),
+            CardChange::Trash(id) => self.trash_card(*id, now),
+            CardChange::Edit(id, card_edit) => {
+                if let Some(deck_id) = card_edit.deck_id {
+                    self.require_deck(deck_id)?;
+                }
+
+                let card = self.live_card_mut(*id)?;
+                card_edit.apply(card);
+
+                Ok(card.clone())
+            },
+            CardChange::Tag(id, add, remove) => {
+                let card = self.live_card_mut(*id)?;
+                let tags = card.tags().iter()
+                    .chain(add)
+                    .filter(|tag| !remove.contains(tag))
+                    .cloned()
+                    .collect();
+                card.set_tags(tags);
+
+                Ok(card.clone())
+            },
+        }
+    }
+}
+
+// Keeps every card in a map behind a mutex. Nothing is persisted, which makes it a good fit
+// for handler tests and quick local experiments (`memory:` as the database url).
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    tables: Mutex<MemoryTables>,
+}
+
+#[async_trait]
+impl CardStore for MemoryStore {
+
+    async fn remove_card(&self, card_id: u32, deleted_at: DateTime<Utc>) -> StoreResult<()> {
+        let mut tables = self.tables.lock().unwrap();
+
+        tables.trash_card(card_id, deleted_at)?;
+
+        Ok(())
+    }
+
+    async fn restore_card(&self, card_id: u32) -> StoreResult<Card> {
+        let mut tables = self.tables.lock().unwrap();
+
+        let card = tables.cards.get_mut(&card_id)
+            .filter(|card| card.deleted_at().is_some())
+            .ok_or(StoreError::NotFound)?;
+
+        card.set_deleted_at(None);
+
+        Ok(card.clone())
+    }
+
+    async fn purge_trash(&self, deleted_before: DateTime<Utc>) -> StoreResult<u64> {
+        let mut tables = self.tables.lock().unwrap();
+
+        let purged: Vec<u32> = tables.cards.values()
+            .filter(|card| card.deleted_at().is_some_and(|deleted_at| deleted_at < deleted_before))
+            .map(|card| *card.id())
+            .collect();
+
+        tables.cards.retain(|id, _| !purged.contains(id));
+        tables.revisions.retain(|revision| !purged.contains(&revision.card_id));
+        tables.reviews.retain(|review| !purged.contains(&review.card_id));
+
+        Ok(purged.len() as u64)
+    }
+
+    async fn update_card(&self, card: &Card) -> StoreResult<()> {
+        let mut tables = self.tables.lock().unwrap();
+
+        let stored_card = tables.cards.get_mut(card.id())
+            .ok_or(StoreError::NotFound)?;
+
+        *stored_card = card.clone();
+
+        Ok(())
+    }
+
+    async fn add_card(&self, card: &Card) -> StoreResult<Card> {
+        let mut tables = self.tables.lock().unwrap();
+
+        Ok(tables.insert_card(card))
+    }
+
+    async fn get_card(&self, id: u32) -> StoreResult<Card> {
+        let tables = self.tables.lock().unwrap();
+
+        tables.cards.get(&id)
+            .filter(|card| card.deleted_at().is_none())
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    async fn get_cards(&self, filters: GetCardFilters) -> StoreResult<Vec<Card>> {
+        let tables = self.tables.lock().unwrap();
+        let cards = tables.cards.values()
+            .filter(|card| card.deleted_at().is_some() == filters.trashed())
+            .filter(|card| {
+                match filters.from() {
+                    Some(from) => card.is_due_at(from),
+                    None => true,
+                }
+            })
+            .filter(|card| {
+                match filters.deck_id() {
+                    Some(deck_id) => *card.deck_id() == deck_id,
+                    None => true,
+                }
+            })
+            .filter(|card| {
+                match filters.suspended() {
+                    Some(suspended) => *card.suspended() == suspended,
+                    None => true,
+                }
+            })
+            .filter(|card| {
+                match filters.buried() {
+                    Some((buried, at)) => card.is_buried_at(at) == buried,
+                    None => true,
+                }
+            })
+            .filter(|card| {
+                match filters.flag() {
+                    Some(flag) => *card.flag() == flag,
+                    None => true,
+                }
+            })
+            .filter(|card| {
+                match filters.leech() {
+                    Some((leech, threshold)) => (*card.lapses() >= threshold) == leech,
+                    None => true,
+                }
+            })
+            .cloned()
+            .collect();
+
+        Ok(cards)
+    }
+
+    async fn update_card_content(&self, id: u32, update: &UpdateCardPayload, editor: Option<&str>) -> StoreResult<Card> {
+        let mut tables = self.tables.lock().unwrap();
+
+        tables.update_content(id, update, editor)
+    }
+
+    // Holding the lock for the whole batch makes it as atomic as a transaction, and nothing in
+    // here fails other than by rejecting a change.
+    async fn apply_changes(&self, changes: &[CardChange], editor: Option<&str>, now: DateTime<Utc>) -> StoreResult<Vec<StoreResult<Card>>> {
+        let mut tables = self.tables.lock().unwrap();
+
+        let outcomes = changes.iter()
+            .map(|change| tables.apply_change(change, editor, now))
+            .collect();
+
+        Ok(outcomes)
+    }
+
+    async fn get_card_revisions(&self, card_id: u32) -> StoreResult<Vec<CardRevision>> {
+        let tables = self.tables.lock().unwrap();
+
+        let revisions = tables.revisions.iter()
+            .filter(|revision| revision.card_id == card_id)
+            .cloned()
+            .collect();
+
+        Ok(revisions)
+    }
+
+    async fn record_review(&self, card: &Card, review: &CardReview) -> StoreResult<CardReview> {
+        let mut tables = self.tables.lock().unwrap();
+
+        let stored_card = tables.cards.get_mut(card.id())
+            .ok_or(StoreError::NotFound)?;
+
+        stored_card.set_interval(*card.interval());
+        stored_card.set_ease_factor(*card.ease_factor());
+        stored_card.set_stage(*card.stage());
+        stored_card.set_step(*card.step());
+        stored_card.set_lapses(*card.lapses());
+        stored_card.set_suspended(*card.suspended());
+        stored_card.set_next_review(*card.next_review());
+
+        tables.last_review_id += 1;
+
+        let review = CardReview {
+            id: tables.last_review_id,
+            card_id: *card.id(),
+            ..review.clone()
+        };
+        tables.reviews.push(review.clone());
+
+        Ok(review)
+    }
+
+    async fn get_review(&self, id: u32) -> StoreResult<CardReview> {
+        let tables = self.tables.lock().unwrap();
+
+        tables.reviews.iter()
+            .find(|review| review.id == id)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    async fn undo_review(&self, card: &Card, review_id: u32) -> StoreResult<()> {
+        let mut tables = self.tables.lock().unwrap();
+
+        let position = tables.reviews.iter()
+            .position(|review| review.id == review_id)
+            .ok_or(StoreError::NotFound)?;
+
+        let stored_card = tables.cards.get_mut(card.id())
+            .ok_or(StoreError::NotFound)?;
+
+        stored_card.set_interval(*card.interval());
+        stored_card.set_ease_factor(*card.ease_factor());
+        stored_card.set_stage(*card.stage());
+        stored_card.set_step(*card.step());
+        stored_card.set_lapses(*card.lapses());
+        stored_card.set_suspended(*card.suspended());
+        stored_card.set_next_review(*card.next_review());
+
+        tables.reviews.remove(position);
+
+        Ok(())
+    }
+
+    async fn get_reviews(&self, filters: GetReviewFilters) -> StoreResult<Vec<CardReview>> {
+        let tables = self.tables.lock().unwrap();
+
+        let reviews = tables.reviews.iter()
+            .filter(|review| {
+                match filters.deck_id() {
+                    Some(deck_id) => tables.cards.get(&review.card_id)
+                        .is_some_and(|card| *card.deck_id() == deck_id),
+                    None => true,
+                }
+            })
+            .filter(|review| {
+                match filters.card_id() {
+                    Some(card_id) => review.card_id == card_id,
+                    None => true,
+                }
+            })
+            .filter(|review| {
+                match filters.session_id() {
+                    Some(session_id) => review.session_id == Some(session_id),
+                    None => true,
+                }
+            })
+            .filter(|review| {
+                match filters.since() {
+                    Some(since) => review.reviewed_at >= since,
+                    None => true,
+                }
+            })
+            .cloned()
+            .collect();
+
+        Ok(reviews)
+    }
+
+    async fn get_decks(&self) -> StoreResult<Vec<Deck>> {
+        let tables = self.tables.lock().unwrap();
+
+        Ok(tables.decks.values().cloned().collect())
+    }
+
+    async fn get_deck(&self, id: u32) -> StoreResult<Deck> {
+        let tables = self.tables.lock().unwrap();
+
+        tables.decks.get(&id)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    async fn add_deck(&self, deck: &Deck) -> StoreResult<Deck> {
+        let mut tables = self.tables.lock().unwrap();
+
+        tables.last_deck_id += 1;
+
+        let deck = Deck {
+            id: tables.last_deck_id,
+            ..deck.clone()
+        };
+        tables.decks.insert(deck.id, deck.clone());
+
+        Ok(deck)
+    }
+
+    async fn update_deck(&self, deck: &Deck) -> StoreResult<()> {
+        let mut tables = self.tables.lock().unwrap();
+
+        let stored_deck = tables.decks.get_mut(&deck.id)
+            .ok_or(StoreError::NotFound)?;
+
+        *stored_deck = deck.clone();
+
+        Ok(())
+    }
+
+    async fn add_session(&self, session: &StudySession) -> StoreResult<StudySession> {
+        let mut tables = self.tables.lock().unwrap();
+
+        tables.last_session_id += 1;
+
+        let session = StudySession {
+            id: tables.last_session_id,
+            ..session.clone()
+        };
+        tables.sessions.insert(session.id, session.clone());
+
+        Ok(session)
+    }
+
+    async fn get_session(&self, id: u32) -> StoreResult<StudySession> {
+        let tables = self.tables.lock().unwrap();
+
+        tables.sessions.get(&id)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    async fn finish_session(&self, id: u32, ended_at: DateTime<Utc>) -> StoreResult<StudySession> {
+        let mut tables = self.tables.lock().unwrap();
+
+        let session = tables.sessions.get_mut(&id)
+            .ok_or(StoreError::NotFound)?;
+
+        session.ended_at.get_or_insert(ended_at);
+
+        Ok(session.clone())
+    }
+}
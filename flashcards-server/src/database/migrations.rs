@@ -0,0 +1,113 @@
+use sqlx::{
+    migrate::{Migrate, Migrator},
+    Database,
+    Pool,
+};
+use crate::database::{MigrationStatus, StoreError, StoreResult};
+
+// Helpers shared by the SQL backends. The migrations themselves are embedded into the binary
+// with `sqlx::migrate!`, so the server no longer needs the source tree next to it.
+
+pub async fn status<DB>(migrator: &Migrator, pool: &Pool<DB>) -> StoreResult<Vec<MigrationStatus>>
+where
+    DB: Database,
+    DB::Connection: Migrate,
+{
+    let mut connection = pool.acquire().await?;
+    connection.ensure_migrations_table().await?;
+
+    let applied = connection.list_applied_migrations().await?;
+
+    let mut statuses: Vec<MigrationStatus> = migrator.iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            description: migration.description.to_string(),
+            applied: applied.iter().any(|applied| applied.version == migration.version),
+            known: true,
+        })
+        .collect();
+
+    // Versions in the database that this binary has never heard of.
+    for applied in applied.iter().filter(|applied| !migrator.version_exists(applied.version)) {
+        statuses.push(MigrationStatus {
+            version: applied.version,
+            description: String::new(),
+            applied: true,
+            known: false,
+        });
+    }
+
+    statuses.sort_by_key(|status| status.version);
+
+    Ok(statuses)
+}
+
+// Refuses to touch a database that was migrated by a newer build of the server.
+pub async fn ensure_not_ahead<DB>(migrator: &Migrator, pool: &Pool<DB>) -> StoreResult<()>
+where
+    DB: Database,
+    DB::Connection: Migrate,
+{
+    let statuses = status(migrator, pool).await?;
+
+    if let Some(unknown) = statuses.iter().rev().find(|status| !status.known) {
+        let latest = migrator.iter()
+            .map(|migration| migration.version)
+            .max()
+            .unwrap_or_default();
+
+        return Err(StoreError::SchemaAhead {
+            database: unknown.version,
+            binary: latest,
+        });
+    }
+
+    Ok(())
+}
+
+pub async fn run<DB>(migrator: &Migrator, pool: &Pool<DB>) -> StoreResult<()>
+where
+    DB: Database,
+    DB::Connection: Migrate,
+{
+    ensure_not_ahead(migrator, pool).await?;
+    migrator.run(pool).await?;
+
+    log::info!("Migration success!");
+
+    Ok(())
+}
+
+// Reverts every applied migration newer than `target`. Without a target only the latest
+// applied migration is reverted.
+pub async fn revert<DB>(migrator: &Migrator, pool: &Pool<DB>, target: Option<i64>) -> StoreResult<()>
+where
+    DB: Database,
+    DB::Connection: Migrate,
+{
+    ensure_not_ahead(migrator, pool).await?;
+
+    let target = match target {
+        Some(target) => target,
+        None => {
+            let applied: Vec<i64> = status(migrator, pool).await?
+                .into_iter()
+                .filter(|status| status.applied)
+                .map(|status| status.version)
+                .collect();
+
+            match applied.len() {
+                0 => return Ok(()),
+                1 => 0,
+                len => applied[len - 2],
+            }
+        }
+    };
+
+    migrator.undo(pool, target).await?;
+
+    log::info!("Reverted migrations newer than {}", target);
+
+    Ok(())
+}
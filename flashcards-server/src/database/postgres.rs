@@ -0,0 +1,806 @@
+use flashcards_data::{Card, CardDifficulty, CardFlag, CardReview, CardRevision, CardStage, Deck, SessionKind, StudySession, UpdateCardPayload};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{
+    migrate::{MigrateDatabase, Migrator},
+    postgres::{PgConnection, PgPoolOptions},
+    FromRow,
+    Postgres,
+    QueryBuilder,
+    Pool,
+};
+use crate::database::{migrations, CardChange, CardStore, GetCardFilters, GetReviewFilters, MigrationStatus, StoreError, StoreResult};
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+// Postgres has no unsigned integer types, so rows are read into signed columns and converted
+// into a `Card` afterwards.
+#[derive(FromRow)]
+struct CardRow {
+    id: i32,
+    deck_id: i32,
+    front: String,
+    back: String,
+    ease_factor: f64,
+    interval: i64,
+    #[sqlx(try_from = "String")]
+    stage: CardStage,
+    step: i32,
+    lapses: i32,
+    next_review: DateTime<Utc>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    suspended: bool,
+    buried_until: Option<DateTime<Utc>>,
+    #[sqlx(try_from = "String")]
+    flag: CardFlag,
+    deleted_at: Option<DateTime<Utc>>,
+    tags: Vec<String>,
+}
+
+impl From<CardRow> for Card {
+    fn from(row: CardRow) -> Self {
+        let mut card = Card::new(row.id as u32, row.front, row.back);
+        card.set_deck_id(row.deck_id as u32);
+        card.set_ease_factor(row.ease_factor);
+        card.set_interval(u32::try_from(row.interval).unwrap_or(u32::MAX));
+        card.set_stage(row.stage);
+        card.set_step(row.step as u32);
+        card.set_lapses(row.lapses as u32);
+        card.set_next_review(row.next_review);
+        card.set_created_at(row.created_at);
+        card.set_updated_at(row.updated_at);
+        card.set_suspended(row.suspended);
+        card.set_buried_until(row.buried_until);
+        card.set_flag(row.flag);
+        card.set_deleted_at(row.deleted_at);
+        card.set_tags(row.tags);
+        card
+    }
+}
+
+#[derive(FromRow)]
+struct RevisionRow {
+    id: i32,
+    card_id: i32,
+    front: String,
+    back: String,
+    created_at: DateTime<Utc>,
+    editor: Option<String>,
+}
+
+impl From<RevisionRow> for CardRevision {
+    fn from(row: RevisionRow) -> Self {
+        CardRevision {
+            id: row.id as u32,
+            card_id: row.card_id as u32,
+            front: row.front,
+            back: row.back,
+            created_at: row.created_at,
+            editor: row.editor,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct ReviewRow {
+    id: i32,
+    card_id: i32,
+    #[sqlx(try_from = "String")]
+    difficulty: CardDifficulty,
+    #[sqlx(try_from = "String")]
+    stage: CardStage,
+    reviewed_at: DateTime<Utc>,
+    session_id: Option<i32>,
+    answer_time_ms: Option<i32>,
+    previous_ease_factor: Option<f64>,
+    previous_interval: Option<i64>,
+    previous_step: Option<i32>,
+    previous_next_review: Option<DateTime<Utc>>,
+    previous_lapses: Option<i32>,
+    previous_suspended: Option<bool>,
+    cram: bool,
+}
+
+impl From<ReviewRow> for CardReview {
+    fn from(row: ReviewRow) -> Self {
+        CardReview {
+            id: row.id as u32,
+            card_id: row.card_id as u32,
+            difficulty: row.difficulty,
+            stage: row.stage,
+            reviewed_at: row.reviewed_at,
+            session_id: row.session_id.map(|session_id| session_id as u32),
+            answer_time_ms: row.answer_time_ms.map(|answer_time_ms| answer_time_ms as u32),
+            previous_ease_factor: row.previous_ease_factor,
+            previous_interval: row.previous_interval.map(|interval| interval as u32),
+            previous_step: row.previous_step.map(|step| step as u32),
+            previous_next_review: row.previous_next_review,
+            previous_lapses: row.previous_lapses.map(|lapses| lapses as u32),
+            previous_suspended: row.previous_suspended,
+            cram: row.cram,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct DeckRow {
+    id: i32,
+    name: String,
+    new_cards_per_day: i32,
+    reviews_per_day: i32,
+}
+
+impl From<DeckRow> for Deck {
+    fn from(row: DeckRow) -> Self {
+        Deck {
+            id: row.id as u32,
+            name: row.name,
+            new_cards_per_day: row.new_cards_per_day as u32,
+            reviews_per_day: row.reviews_per_day as u32,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct SessionRow {
+    id: i32,
+    deck_id: i32,
+    started_at: DateTime<Utc>,
+    ended_at: Option<DateTime<Utc>>,
+    #[sqlx(try_from = "String")]
+    kind: SessionKind,
+}
+
+impl From<SessionRow> for StudySession {
+    fn from(row: SessionRow) -> Self {
+        StudySession {
+            id: row.id as u32,
+            deck_id: row.deck_id as u32,
+            started_at: row.started_at,
+            ended_at: row.ended_at,
+            kind: row.kind,
+        }
+    }
+}
+
+const REVIEW_COLUMNS: &str = "id, card_id, difficulty, stage, reviewed_at, session_id, answer_time_ms, previous_ease_factor, previous_interval, previous_step, previous_next_review, previous_lapses, previous_suspended, cram";
+
+const SESSION_COLUMNS: &str = "id, deck_id, started_at, ended_at, kind";
+
+const DECK_COLUMNS: &str = "id, name, new_cards_per_day, reviews_per_day";
+
+// Tags come along as an array, sorted.
+const CARD_COLUMNS: &str = "id, deck_id, front_of_card as front, back_of_card as back, ease_factor, \"interval\", stage, step, lapses, next_review, created_at, updated_at, suspended, buried_until, flag, deleted_at,
+    ARRAY(SELECT tag FROM card_tags WHERE card_tags.card_id = flashcards.id ORDER BY tag) as tags";
+
+// Writes the scheduling fields of `card`, as done when a review is recorded or undone. A review
+// can suspend a leech, so `suspended` is part of them.
+async fn save_schedule(connection: &mut PgConnection, card: &Card) -> StoreResult<()> {
+    let result = sqlx::query(
+            "UPDATE flashcards SET
+                \"interval\" = $1,
+                ease_factor = $2,
+                stage = $3,
+                step = $4,
+                lapses = $5,
+                suspended = $6,
+                next_review = $7
+            WHERE id = $8"
+        )
+        .bind(*card.interval() as i64)
+        .bind(*card.ease_factor())
+        .bind(card.stage().as_str())
+        .bind(*card.step() as i32)
+        .bind(*card.lapses() as i32)
+        .bind(*card.suspended())
+        .bind(card.next_review())
+        .bind(*card.id() as i32)
+        .execute(connection)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(StoreError::NotFound);
+    }
+
+    Ok(())
+}
+
+// Writes every field of `card` except its trash state.
+async fn save_card(connection: &mut PgConnection, card: &Card) -> StoreResult<()> {
+    let result = sqlx::query(
+            "UPDATE flashcards SET
+                front_of_card = $1,
+                back_of_card = $2,
+                \"interval\" = $3,
+                ease_factor = $4,
+                stage = $5,
+                step = $6,
+                next_review = $7,
+                updated_at = $8,
+                deck_id = $9,
+                suspended = $10,
+                buried_until = $11,
+                flag = $12,
+                lapses = $13
+            WHERE id = $14"
+        )
+        .bind(card.front())
+        .bind(card.back())
+        .bind(*card.interval() as i64)
+        .bind(*card.ease_factor())
+        .bind(card.stage().as_str())
+        .bind(*card.step() as i32)
+        .bind(card.next_review())
+        .bind(card.updated_at())
+        .bind(*card.deck_id() as i32)
+        .bind(*card.suspended())
+        .bind(card.buried_until())
+        .bind(card.flag().as_str())
+        .bind(*card.lapses() as i32)
+        .bind(*card.id() as i32)
+        .execute(connection)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(StoreError::NotFound);
+    }
+
+    Ok(())
+}
+
+async fn insert_card(connection: &mut PgConnection, card: &Card) -> StoreResult<Card> {
+    let row = sqlx::query_as::<_, CardRow>(&format!("INSERT INTO flashcards (
+            front_of_card,
+            back_of_card,
+            ease_factor,
+            \"interval\",
+            stage,
+            step,
+            next_review,
+            created_at,
+            updated_at,
+            deck_id,
+            suspended,
+            buried_until,
+            flag,
+            lapses,
+            deleted_at
+        ) VALUES (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5,
+            $6,
+            $7,
+            $8,
+            $9,
+            $10,
+            $11,
+            $12,
+            $13,
+            $14,
+            $15
+        )
+        RETURNING {CARD_COLUMNS}"))
+        .bind(card.front())
+        .bind(card.back())
+        .bind(*card.ease_factor())
+        .bind(*card.interval() as i64)
+        .bind(card.stage().as_str())
+        .bind(*card.step() as i32)
+        .bind(card.next_review())
+        .bind(card.created_at())
+        .bind(card.updated_at())
+        .bind(*card.deck_id() as i32)
+        .bind(*card.suspended())
+        .bind(card.buried_until())
+        .bind(card.flag().as_str())
+        .bind(*card.lapses() as i32)
+        .bind(card.deleted_at())
+        .fetch_one(connection)
+        .await?;
+
+    Ok(row.into())
+}
+
+// Locks the row, the caller is about to change it.
+async fn fetch_card(connection: &mut PgConnection, id: u32) -> StoreResult<Card> {
+    let row = sqlx::query_as::<_, CardRow>(&format!("SELECT {CARD_COLUMNS} FROM flashcards WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"))
+        .bind(id as i32)
+        .fetch_one(connection)
+        .await?;
+
+    Ok(row.into())
+}
+
+// Replaces the content of a live card and keeps the old content as a revision.
+async fn update_content(connection: &mut PgConnection, id: u32, update: &UpdateCardPayload, editor: Option<&str>) -> StoreResult<Card> {
+    let card = fetch_card(&mut *connection, id).await?;
+
+    if !update.changes(&card) {
+        return Ok(card);
+    }
+
+    sqlx::query("INSERT INTO card_revisions (card_id, front_of_card, back_of_card, created_at, editor) VALUES ($1, $2, $3, $4, $5)")
+        .bind(id as i32)
+        .bind(card.front())
+        .bind(card.back())
+        .bind(Utc::now())
+        .bind(editor)
+        .execute(&mut *connection)
+        .await?;
+
+    let row = sqlx::query_as::<_, CardRow>(&format!("
+            UPDATE flashcards SET
+                front_of_card = $1,
+                back_of_card = $2,
+                updated_at = $3
+            WHERE id = $4
+            RETURNING {CARD_COLUMNS}
+        "))
+        .bind(update.front.as_deref().unwrap_or(card.front()))
+        .bind(update.back.as_deref().unwrap_or(card.back()))
+        .bind(Utc::now())
+        .bind(id as i32)
+        .fetch_one(&mut *connection)
+        .await?;
+
+    Ok(row.into())
+}
+
+async fn trash_card(connection: &mut PgConnection, id: u32, deleted_at: DateTime<Utc>) -> StoreResult<Card> {
+    let row = sqlx::query_as::<_, CardRow>(&format!("UPDATE flashcards
+            SET deleted_at = $1
+            WHERE id = $2 AND deleted_at IS NULL
+            RETURNING {CARD_COLUMNS}"))
+        .bind(deleted_at)
+        .bind(id as i32)
+        .fetch_one(connection)
+        .await?;
+
+    Ok(row.into())
+}
+
+// Checked up front: a foreign key violation would abort the whole transaction.
+async fn require_deck(connection: &mut PgConnection, deck_id: u32) -> StoreResult<()> {
+    sqlx::query("SELECT id FROM decks WHERE id = $1")
+        .bind(deck_id as i32)
+        .fetch_optional(connection)
+        .await?
+        .ok_or_else(|| StoreError::Invalid(format!("deck {} does not exist", deck_id)))?;
+
+    Ok(())
+}
+
+async fn tag_card(connection: &mut PgConnection, id: u32, add: &[String], remove: &[String]) -> StoreResult<Card> {
+    fetch_card(&mut *connection, id).await?;
+
+    for tag in add {
+        sqlx::query("INSERT INTO card_tags (card_id, tag) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(id as i32)
+            .bind(tag)
+            .execute(&mut *connection)
+            .await?;
+    }
+
+    for tag in remove {
+        sqlx::query("DELETE FROM card_tags WHERE card_id = $1 AND tag = $2")
+            .bind(id as i32)
+            .bind(tag)
+            .execute(&mut *connection)
+            .await?;
+    }
+
+    fetch_card(connection, id).await
+}
+
+async fn apply_change(connection: &mut PgConnection, change: &CardChange, editor: Option<&str>, now: DateTime<Utc>) -> StoreResult<Card> {
+    match change {
+        CardChange::Create(card) => {
+            require_deck(&mut *connection, *card.deck_id()).await?;
+            insert_card(connection, card).await
+        },
+        CardChange::UpdateContent(id, update) => update_content(connection, *id, update, editor).await,
+        CardChange::Trash(id) => trash_card(connection, *id, now).await,
+        CardChange::Edit(id, card_edit) => {
+            if let Some(deck_id) = card_edit.deck_id {
+                require_deck(&mut *connection, deck_id).await?;
+            }
+
+            let mut card = fetch_card(&mut *connection, *id).await?;
+            card_edit.apply(&mut card);
+            save_card(connection, &card).await?;
+
+            Ok(card)
+        },
+        CardChange::Tag(id, add, remove) => tag_card(connection, *id, add, remove).await,
+    }
+}
+
+#[derive(Debug)]
+pub struct PostgresStore {
+    pool: Pool<Postgres>,
+}
+
+impl PostgresStore {
+
+    pub async fn connect(url: &str, max_connections: u32) -> StoreResult<Self> {
+        if !Postgres::database_exists(url).await.unwrap_or(false) {
+            log::info!("Creating DB {}", url);
+            Postgres::create_database(url).await?;
+        }
+
+        let pool = PgPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(url).await?;
+
+        Ok(Self {
+            pool
+        })
+    }
+}
+
+#[async_trait]
+impl CardStore for PostgresStore {
+
+    async fn remove_card(&self, card_id: u32, deleted_at: DateTime<Utc>) -> StoreResult<()> {
+        let mut connection = self.pool.acquire().await?;
+
+        trash_card(&mut connection, card_id, deleted_at).await?;
+
+        Ok(())
+    }
+
+    async fn restore_card(&self, card_id: u32) -> StoreResult<Card> {
+        let row = sqlx::query_as::<_, CardRow>(&format!("UPDATE flashcards
+                SET deleted_at = NULL
+                WHERE id = $1 AND deleted_at IS NOT NULL
+                RETURNING {CARD_COLUMNS}"))
+            .bind(card_id as i32)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.into())
+    }
+
+    // Revisions and reviews go with the cards through ON DELETE CASCADE.
+    async fn purge_trash(&self, deleted_before: DateTime<Utc>) -> StoreResult<u64> {
+        let result = sqlx::query("DELETE FROM flashcards WHERE deleted_at < $1")
+            .bind(deleted_before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn update_card(&self, card: &Card) -> StoreResult<()> {
+        let mut connection = self.pool.acquire().await?;
+
+        save_card(&mut connection, card).await
+    }
+
+    async fn add_card(&self, card: &Card) -> StoreResult<Card> {
+        let mut connection = self.pool.acquire().await?;
+
+        insert_card(&mut connection, card).await
+    }
+
+    async fn get_card(&self, id: u32) -> StoreResult<Card> {
+        let row = sqlx::query_as::<_, CardRow>(&format!("SELECT {CARD_COLUMNS} FROM flashcards WHERE id = $1 AND deleted_at IS NULL"))
+            .bind(id as i32)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.into())
+    }
+
+    async fn get_cards(&self, filters: GetCardFilters) -> StoreResult<Vec<Card>> {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(format!("
+            SELECT {CARD_COLUMNS}
+            FROM flashcards
+            WHERE 1=1
+        "));
+
+        if filters.trashed() {
+            query_builder.push(" AND deleted_at IS NOT NULL");
+        } else {
+            query_builder.push(" AND deleted_at IS NULL");
+        }
+
+        if let Some(from) = filters.from() {
+            query_builder.push(" AND next_review < ");
+            query_builder.push_bind(from);
+        }
+
+        if let Some(deck_id) = filters.deck_id() {
+            query_builder.push(" AND deck_id = ");
+            query_builder.push_bind(deck_id as i32);
+        }
+
+        if let Some(suspended) = filters.suspended() {
+            query_builder.push(" AND suspended = ");
+            query_builder.push_bind(suspended);
+        }
+
+        // Cards that were never buried have no date and count as not buried.
+        if let Some((buried, at)) = filters.buried() {
+            query_builder.push(" AND coalesce(buried_until > ");
+            query_builder.push_bind(at);
+            query_builder.push(", FALSE) = ");
+            query_builder.push_bind(buried);
+        }
+
+        if let Some(flag) = filters.flag() {
+            query_builder.push(" AND flag = ");
+            query_builder.push_bind(flag.as_str());
+        }
+
+        if let Some((leech, threshold)) = filters.leech() {
+            query_builder.push(" AND (lapses >= ");
+            query_builder.push_bind(threshold as i64);
+            query_builder.push(") = ");
+            query_builder.push_bind(leech);
+        }
+
+        query_builder.push(" ORDER BY id");
+
+        let rows = query_builder
+            .build_query_as::<CardRow>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(Card::from).collect())
+    }
+
+    async fn update_card_content(&self, id: u32, update: &UpdateCardPayload, editor: Option<&str>) -> StoreResult<Card> {
+        let mut transaction = self.pool.begin().await?;
+
+        let card = update_content(&mut transaction, id, update, editor).await?;
+
+        transaction.commit().await?;
+
+        Ok(card)
+    }
+
+    async fn apply_changes(&self, changes: &[CardChange], editor: Option<&str>, now: DateTime<Utc>) -> StoreResult<Vec<StoreResult<Card>>> {
+        let mut transaction = self.pool.begin().await?;
+        let mut outcomes = Vec::with_capacity(changes.len());
+
+        for change in changes {
+            match apply_change(&mut transaction, change, editor, now).await {
+                Err(error) if !error.is_rejection() => return Err(error),
+                outcome => outcomes.push(outcome),
+            }
+        }
+
+        transaction.commit().await?;
+
+        Ok(outcomes)
+    }
+
+    async fn get_card_revisions(&self, card_id: u32) -> StoreResult<Vec<CardRevision>> {
+        let rows = sqlx::query_as::<_, RevisionRow>(
+                "SELECT id, card_id, front_of_card as front, back_of_card as back, created_at, editor
+                FROM card_revisions
+                WHERE card_id = $1
+                ORDER BY id"
+            )
+            .bind(card_id as i32)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(CardRevision::from).collect())
+    }
+
+    async fn record_review(&self, card: &Card, review: &CardReview) -> StoreResult<CardReview> {
+        let mut transaction = self.pool.begin().await?;
+
+        save_schedule(&mut transaction, card).await?;
+
+        let row = sqlx::query_as::<_, ReviewRow>(&format!("
+                INSERT INTO reviews (
+                    card_id, difficulty, stage, reviewed_at, session_id, answer_time_ms,
+                    previous_ease_factor, previous_interval, previous_step, previous_next_review,
+                    previous_lapses, previous_suspended, cram
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                RETURNING {REVIEW_COLUMNS}
+            "))
+            .bind(*card.id() as i32)
+            .bind(review.difficulty.as_str())
+            .bind(review.stage.as_str())
+            .bind(review.reviewed_at)
+            .bind(review.session_id.map(|session_id| session_id as i32))
+            .bind(review.answer_time_ms.map(|answer_time_ms| answer_time_ms as i32))
+            .bind(review.previous_ease_factor)
+            .bind(review.previous_interval.map(|interval| interval as i64))
+            .bind(review.previous_step.map(|step| step as i32))
+            .bind(review.previous_next_review)
+            .bind(review.previous_lapses.map(|lapses| lapses as i32))
+            .bind(review.previous_suspended)
+            .bind(review.cram)
+            .fetch_one(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(row.into())
+    }
+
+    async fn get_review(&self, id: u32) -> StoreResult<CardReview> {
+        let row = sqlx::query_as::<_, ReviewRow>(&format!("SELECT {REVIEW_COLUMNS} FROM reviews WHERE id = $1"))
+            .bind(id as i32)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.into())
+    }
+
+    async fn undo_review(&self, card: &Card, review_id: u32) -> StoreResult<()> {
+        let mut transaction = self.pool.begin().await?;
+
+        save_schedule(&mut transaction, card).await?;
+
+        let result = sqlx::query("DELETE FROM reviews WHERE id = $1")
+            .bind(review_id as i32)
+            .execute(&mut *transaction)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(StoreError::NotFound);
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_reviews(&self, filters: GetReviewFilters) -> StoreResult<Vec<CardReview>> {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new("
+            SELECT reviews.id, reviews.card_id, reviews.difficulty, reviews.stage, reviews.reviewed_at, reviews.session_id, reviews.answer_time_ms,
+                reviews.previous_ease_factor, reviews.previous_interval, reviews.previous_step, reviews.previous_next_review,
+                reviews.previous_lapses, reviews.previous_suspended, reviews.cram
+            FROM reviews
+            JOIN flashcards ON flashcards.id = reviews.card_id
+            WHERE 1=1
+        ");
+
+        if let Some(deck_id) = filters.deck_id() {
+            query_builder.push(" AND flashcards.deck_id = ");
+            query_builder.push_bind(deck_id as i32);
+        }
+
+        if let Some(card_id) = filters.card_id() {
+            query_builder.push(" AND reviews.card_id = ");
+            query_builder.push_bind(card_id as i32);
+        }
+
+        if let Some(session_id) = filters.session_id() {
+            query_builder.push(" AND reviews.session_id = ");
+            query_builder.push_bind(session_id as i32);
+        }
+
+        if let Some(since) = filters.since() {
+            query_builder.push(" AND reviews.reviewed_at >= ");
+            query_builder.push_bind(since);
+        }
+
+        query_builder.push(" ORDER BY reviews.id");
+
+        let rows = query_builder
+            .build_query_as::<ReviewRow>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(CardReview::from).collect())
+    }
+
+    async fn get_decks(&self) -> StoreResult<Vec<Deck>> {
+        let rows = sqlx::query_as::<_, DeckRow>(&format!("SELECT {DECK_COLUMNS} FROM decks ORDER BY id"))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(Deck::from).collect())
+    }
+
+    async fn get_deck(&self, id: u32) -> StoreResult<Deck> {
+        let row = sqlx::query_as::<_, DeckRow>(&format!("SELECT {DECK_COLUMNS} FROM decks WHERE id = $1"))
+            .bind(id as i32)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.into())
+    }
+
+    async fn add_deck(&self, deck: &Deck) -> StoreResult<Deck> {
+        let row = sqlx::query_as::<_, DeckRow>(&format!("
+                INSERT INTO decks (name, new_cards_per_day, reviews_per_day)
+                VALUES ($1, $2, $3)
+                RETURNING {DECK_COLUMNS}
+            "))
+            .bind(&deck.name)
+            .bind(deck.new_cards_per_day as i32)
+            .bind(deck.reviews_per_day as i32)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.into())
+    }
+
+    async fn update_deck(&self, deck: &Deck) -> StoreResult<()> {
+        let result = sqlx::query(
+                "UPDATE decks SET
+                    name = $1,
+                    new_cards_per_day = $2,
+                    reviews_per_day = $3
+                WHERE id = $4"
+            )
+            .bind(&deck.name)
+            .bind(deck.new_cards_per_day as i32)
+            .bind(deck.reviews_per_day as i32)
+            .bind(deck.id as i32)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(StoreError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn add_session(&self, session: &StudySession) -> StoreResult<StudySession> {
+        let row = sqlx::query_as::<_, SessionRow>(&format!("
+                INSERT INTO study_sessions (deck_id, started_at, ended_at, kind)
+                VALUES ($1, $2, $3, $4)
+                RETURNING {SESSION_COLUMNS}
+            "))
+            .bind(session.deck_id as i32)
+            .bind(session.started_at)
+            .bind(session.ended_at)
+            .bind(session.kind.to_string())
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.into())
+    }
+
+    async fn get_session(&self, id: u32) -> StoreResult<StudySession> {
+        let row = sqlx::query_as::<_, SessionRow>(&format!("SELECT {SESSION_COLUMNS} FROM study_sessions WHERE id = $1"))
+            .bind(id as i32)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.into())
+    }
+
+    async fn finish_session(&self, id: u32, ended_at: DateTime<Utc>) -> StoreResult<StudySession> {
+        // Finishing twice keeps the first end time.
+        let row = sqlx::query_as::<_, SessionRow>(&format!("
+                UPDATE study_sessions SET
+                    ended_at = COALESCE(ended_at, $1)
+                WHERE id = $2
+                RETURNING {SESSION_COLUMNS}
+            "))
+            .bind(ended_at)
+            .bind(id as i32)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.into())
+    }
+
+    async fn migration_status(&self) -> StoreResult<Vec<MigrationStatus>> {
+        migrations::status(&MIGRATOR, &self.pool).await
+    }
+
+    async fn run_migrations(&self) -> StoreResult<()> {
+        migrations::run(&MIGRATOR, &self.pool).await
+    }
+
+    async fn revert_migrations(&self, target: Option<i64>) -> StoreResult<()> {
+        migrations::revert(&MIGRATOR, &self.pool, target).await
+    }
+}
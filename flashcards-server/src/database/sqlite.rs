@@ -0,0 +1,657 @@
+use flashcards_data::{Card, CardReview, CardRevision, Deck, StudySession, UpdateCardPayload};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{
+    migrate::{MigrateDatabase, Migrator},
+    sqlite::SqlitePoolOptions,
+    Sqlite,
+    SqliteConnection,
+    QueryBuilder,
+    Pool,
+};
+use crate::database::{migrations, CardChange, CardStore, GetCardFilters, GetReviewFilters, MigrationStatus, StoreError, StoreResult};
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+const REVIEW_COLUMNS: &str = "id, card_id, difficulty, stage, reviewed_at, session_id, answer_time_ms, previous_ease_factor, previous_interval, previous_step, previous_next_review, previous_lapses, previous_suspended, cram";
+
+const SESSION_COLUMNS: &str = "id, deck_id, started_at, ended_at, kind";
+
+const DECK_COLUMNS: &str = "id, name, new_cards_per_day, reviews_per_day";
+
+// Tags come along as a JSON array, sorted.
+const CARD_COLUMNS: &str = "id, deck_id, front_of_card as front, back_of_card as back, ease_factor, interval, stage, step, lapses, next_review, created_at, updated_at, suspended, buried_until, flag, deleted_at,
+    (SELECT json_group_array(tag) FROM (SELECT tag FROM card_tags WHERE card_tags.card_id = flashcards.id ORDER BY tag)) as tags";
+
+// Writes the scheduling fields of `card`, as done when a review is recorded or undone. A review
+// can suspend a leech, so `suspended` is part of them.
+async fn save_schedule(connection: &mut SqliteConnection, card: &Card) -> StoreResult<()> {
+    let result = sqlx::query(
+            "UPDATE flashcards SET
+                interval = ?,
+                ease_factor = ?,
+                stage = ?,
+                step = ?,
+                lapses = ?,
+                suspended = ?,
+                next_review = ?
+            WHERE id = ?"
+        )
+        .bind(card.interval())
+        .bind(card.ease_factor())
+        .bind(card.stage().as_str())
+        .bind(card.step())
+        .bind(card.lapses())
+        .bind(card.suspended())
+        .bind(card.next_review())
+        .bind(card.id())
+        .execute(connection)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(StoreError::NotFound);
+    }
+
+    Ok(())
+}
+
+// Writes every field of `card` except its trash state.
+async fn save_card(connection: &mut SqliteConnection, card: &Card) -> StoreResult<()> {
+    let result = sqlx::query(
+            "UPDATE flashcards SET
+                deck_id = ?,
+                front_of_card = ?,
+                back_of_card = ?,
+                interval = ?,
+                ease_factor = ?,
+                stage = ?,
+                step = ?,
+                lapses = ?,
+                next_review = ?,
+                updated_at = ?,
+                suspended = ?,
+                buried_until = ?,
+                flag = ?
+            WHERE id = ?"
+        )
+        .bind(card.deck_id())
+        .bind(card.front())
+        .bind(card.back())
+        .bind(card.interval())
+        .bind(card.ease_factor())
+        .bind(card.stage().as_str())
+        .bind(card.step())
+        .bind(card.lapses())
+        .bind(card.next_review())
+        .bind(card.updated_at())
+        .bind(card.suspended())
+        .bind(card.buried_until())
+        .bind(card.flag().as_str())
+        .bind(card.id())
+        .execute(connection)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(StoreError::NotFound);
+    }
+
+    Ok(())
+}
+
+async fn insert_card(connection: &mut SqliteConnection, card: &Card) -> StoreResult<Card> {
+    let card = sqlx::query_as::<_, Card>(&format!("INSERT INTO flashcards (
+            deck_id,
+            front_of_card,
+            back_of_card,
+            ease_factor,
+            interval,
+            stage,
+            step,
+            lapses,
+            next_review,
+            created_at,
+            updated_at,
+            suspended,
+            buried_until,
+            flag,
+            deleted_at
+        ) VALUES (
+            ?,
+            ?,
+            ?,
+            ?,
+            ?,
+            ?,
+            ?,
+            ?,
+            ?,
+            ?,
+            ?,
+            ?,
+            ?,
+            ?,
+            ?
+        )
+        RETURNING {CARD_COLUMNS}"))
+        .bind(card.deck_id())
+        .bind(card.front())
+        .bind(card.back())
+        .bind(card.ease_factor())
+        .bind(card.interval())
+        .bind(card.stage().as_str())
+        .bind(card.step())
+        .bind(card.lapses())
+        .bind(card.next_review())
+        .bind(card.created_at())
+        .bind(card.updated_at())
+        .bind(card.suspended())
+        .bind(card.buried_until())
+        .bind(card.flag().as_str())
+        .bind(card.deleted_at())
+        .fetch_one(connection)
+        .await?;
+
+    Ok(card)
+}
+
+async fn fetch_card(connection: &mut SqliteConnection, id: u32) -> StoreResult<Card> {
+    let card = sqlx::query_as::<_, Card>(&format!("SELECT {CARD_COLUMNS} FROM flashcards WHERE id = ? AND deleted_at IS NULL"))
+        .bind(id)
+        .fetch_one(connection)
+        .await?;
+
+    Ok(card)
+}
+
+// Replaces the content of a live card and keeps the old content as a revision.
+async fn update_content(connection: &mut SqliteConnection, id: u32, update: &UpdateCardPayload, editor: Option<&str>) -> StoreResult<Card> {
+    let card = fetch_card(&mut *connection, id).await?;
+
+    if !update.changes(&card) {
+        return Ok(card);
+    }
+
+    sqlx::query("INSERT INTO card_revisions (card_id, front_of_card, back_of_card, created_at, editor) VALUES (?, ?, ?, ?, ?)")
+        .bind(id)
+        .bind(card.front())
+        .bind(card.back())
+        .bind(Utc::now())
+        .bind(editor)
+        .execute(&mut *connection)
+        .await?;
+
+    let card = sqlx::query_as::<_, Card>(&format!("
+            UPDATE flashcards SET
+                front_of_card = ?,
+                back_of_card = ?,
+                updated_at = ?
+            WHERE id = ?
+            RETURNING {CARD_COLUMNS}
+        "))
+        .bind(update.front.as_deref().unwrap_or(card.front()))
+        .bind(update.back.as_deref().unwrap_or(card.back()))
+        .bind(Utc::now())
+        .bind(id)
+        .fetch_one(&mut *connection)
+        .await?;
+
+    Ok(card)
+}
+
+async fn trash_card(connection: &mut SqliteConnection, id: u32, deleted_at: DateTime<Utc>) -> StoreResult<Card> {
+    let card = sqlx::query_as::<_, Card>(&format!("UPDATE flashcards
+            SET deleted_at = ?
+            WHERE id = ? AND deleted_at IS NULL
+            RETURNING {CARD_COLUMNS}"))
+        .bind(deleted_at)
+        .bind(id)
+        .fetch_one(connection)
+        .await?;
+
+    Ok(card)
+}
+
+async fn tag_card(connection: &mut SqliteConnection, id: u32, add: &[String], remove: &[String]) -> StoreResult<Card> {
+    fetch_card(&mut *connection, id).await?;
+
+    for tag in add {
+        sqlx::query("INSERT INTO card_tags (card_id, tag) VALUES (?, ?) ON CONFLICT DO NOTHING")
+            .bind(id)
+            .bind(tag)
+            .execute(&mut *connection)
+            .await?;
+    }
+
+    for tag in remove {
+        sqlx::query("DELETE FROM card_tags WHERE card_id = ? AND tag = ?")
+            .bind(id)
+            .bind(tag)
+            .execute(&mut *connection)
+            .await?;
+    }
+
+    fetch_card(connection, id).await
+}
+
+// Checked up front so a missing deck is reported as such rather than as a foreign key error.
+async fn require_deck(connection: &mut SqliteConnection, deck_id: u32) -> StoreResult<()> {
+    sqlx::query("SELECT id FROM decks WHERE id = ?")
+        .bind(deck_id)
+        .fetch_optional(connection)
+        .await?
+        .ok_or_else(|| StoreError::Invalid(format!("deck {} does not exist", deck_id)))?;
+
+    Ok(())
+}
+
+async fn apply_change(connection: &mut SqliteConnection, change: &CardChange, editor: Option<&str>, now: DateTime<Utc>) -> StoreResult<Card> {
+    match change {
+        CardChange::Create(card) => {
+            require_deck(&mut *connection, *card.deck_id()).await?;
+            insert_card(connection, card).await
+        },
+        CardChange::UpdateContent(id, update) => update_content(connection, *id, update, editor).await,
+        CardChange::Trash(id) => trash_card(connection, *id, now).await,
+        CardChange::Edit(id, card_edit) => {
+            if let Some(deck_id) = card_edit.deck_id {
+                require_deck(&mut *connection, deck_id).await?;
+            }
+
+            let mut card = fetch_card(&mut *connection, *id).await?;
+            card_edit.apply(&mut card);
+            save_card(connection, &card).await?;
+
+            Ok(card)
+        },
+        CardChange::Tag(id, add, remove) => tag_card(connection, *id, add, remove).await,
+    }
+}
+
+#[derive(Debug)]
+pub struct SqliteStore {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteStore {
+
+    pub async fn connect(url: &str, max_connections: u32) -> StoreResult<Self> {
+        if !Sqlite::database_exists(url).await.unwrap_or(false) {
+            log::info!("Creating DB {}", url);
+            Sqlite::create_database(url).await?;
+            log::info!("Created DB");
+        } else {
+            log::info!("DB already exists");
+        }
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(max_connections)
+            .connect(url).await?;
+
+        Ok(Self {
+            pool
+        })
+    }
+}
+
+#[async_trait]
+impl CardStore for SqliteStore {
+
+    async fn remove_card(&self, card_id: u32, deleted_at: DateTime<Utc>) -> StoreResult<()> {
+        let mut connection = self.pool.acquire().await?;
+
+        trash_card(&mut connection, card_id, deleted_at).await?;
+
+        Ok(())
+    }
+
+    async fn restore_card(&self, card_id: u32) -> StoreResult<Card> {
+        let card = sqlx::query_as::<_, Card>(&format!("UPDATE flashcards
+                SET deleted_at = NULL
+                WHERE id = ? AND deleted_at IS NOT NULL
+                RETURNING {CARD_COLUMNS}"))
+            .bind(card_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(card)
+    }
+
+    // Revisions and reviews go with the cards through ON DELETE CASCADE.
+    async fn purge_trash(&self, deleted_before: DateTime<Utc>) -> StoreResult<u64> {
+        let result = sqlx::query("DELETE FROM flashcards WHERE deleted_at < ?")
+            .bind(deleted_before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn update_card(&self, card: &Card) -> StoreResult<()> {
+        let mut connection = self.pool.acquire().await?;
+
+        save_card(&mut connection, card).await
+    }
+
+    async fn add_card(&self, card: &Card) -> StoreResult<Card> {
+        let mut connection = self.pool.acquire().await?;
+
+        insert_card(&mut connection, card).await
+    }
+
+    async fn get_card(&self, id: u32) -> StoreResult<Card> {
+        let card = sqlx::query_as::<_, Card>(&format!("SELECT {CARD_COLUMNS} FROM flashcards WHERE id = ? AND deleted_at IS NULL"))
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(card)
+    }
+
+    async fn get_cards(&self, filters: GetCardFilters) -> StoreResult<Vec<Card>> {
+        let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(format!("
+            SELECT {CARD_COLUMNS}
+            FROM flashcards
+            WHERE 1=1
+        "));
+
+        if filters.trashed() {
+            query_builder.push(" AND deleted_at IS NOT NULL");
+        } else {
+            query_builder.push(" AND deleted_at IS NULL");
+        }
+
+        if let Some(from) = filters.from() {
+            query_builder.push(" AND next_review < ");
+            query_builder.push_bind(from);
+        }
+
+        if let Some(deck_id) = filters.deck_id() {
+            query_builder.push(" AND deck_id = ");
+            query_builder.push_bind(deck_id);
+        }
+
+        if let Some(suspended) = filters.suspended() {
+            query_builder.push(" AND suspended = ");
+            query_builder.push_bind(suspended);
+        }
+
+        // Cards that were never buried have no date and count as not buried.
+        if let Some((buried, at)) = filters.buried() {
+            query_builder.push(" AND coalesce(buried_until > ");
+            query_builder.push_bind(at);
+            query_builder.push(", FALSE) = ");
+            query_builder.push_bind(buried);
+        }
+
+        if let Some(flag) = filters.flag() {
+            query_builder.push(" AND flag = ");
+            query_builder.push_bind(flag.as_str());
+        }
+
+        if let Some((leech, threshold)) = filters.leech() {
+            query_builder.push(" AND (lapses >= ");
+            query_builder.push_bind(threshold);
+            query_builder.push(") = ");
+            query_builder.push_bind(leech);
+        }
+
+        query_builder.push(" ORDER BY id");
+
+        let cards = query_builder
+            .build_query_as::<Card>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(cards)
+    }
+
+    async fn update_card_content(&self, id: u32, update: &UpdateCardPayload, editor: Option<&str>) -> StoreResult<Card> {
+        let mut transaction = self.pool.begin().await?;
+
+        let card = update_content(&mut transaction, id, update, editor).await?;
+
+        transaction.commit().await?;
+
+        Ok(card)
+    }
+
+    async fn apply_changes(&self, changes: &[CardChange], editor: Option<&str>, now: DateTime<Utc>) -> StoreResult<Vec<StoreResult<Card>>> {
+        let mut transaction = self.pool.begin().await?;
+        let mut outcomes = Vec::with_capacity(changes.len());
+
+        for change in changes {
+            match apply_change(&mut transaction, change, editor, now).await {
+                Err(error) if !error.is_rejection() => return Err(error),
+                outcome => outcomes.push(outcome),
+            }
+        }
+
+        transaction.commit().await?;
+
+        Ok(outcomes)
+    }
+
+    async fn get_card_revisions(&self, card_id: u32) -> StoreResult<Vec<CardRevision>> {
+        let revisions = sqlx::query_as::<_, CardRevision>(
+                "SELECT id, card_id, front_of_card as front, back_of_card as back, created_at, editor
+                FROM card_revisions
+                WHERE card_id = ?
+                ORDER BY id"
+            )
+            .bind(card_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(revisions)
+    }
+
+    async fn record_review(&self, card: &Card, review: &CardReview) -> StoreResult<CardReview> {
+        let mut transaction = self.pool.begin().await?;
+
+        save_schedule(&mut transaction, card).await?;
+
+        let review = sqlx::query_as::<_, CardReview>(&format!("
+                INSERT INTO reviews (
+                    card_id, difficulty, stage, reviewed_at, session_id, answer_time_ms,
+                    previous_ease_factor, previous_interval, previous_step, previous_next_review,
+                    previous_lapses, previous_suspended, cram
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                RETURNING {REVIEW_COLUMNS}
+            "))
+            .bind(card.id())
+            .bind(review.difficulty.as_str())
+            .bind(review.stage.as_str())
+            .bind(review.reviewed_at)
+            .bind(review.session_id)
+            .bind(review.answer_time_ms)
+            .bind(review.previous_ease_factor)
+            .bind(review.previous_interval)
+            .bind(review.previous_step)
+            .bind(review.previous_next_review)
+            .bind(review.previous_lapses)
+            .bind(review.previous_suspended)
+            .bind(review.cram)
+            .fetch_one(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(review)
+    }
+
+    async fn get_review(&self, id: u32) -> StoreResult<CardReview> {
+        let review = sqlx::query_as::<_, CardReview>(&format!("SELECT {REVIEW_COLUMNS} FROM reviews WHERE id = ?"))
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(review)
+    }
+
+    async fn undo_review(&self, card: &Card, review_id: u32) -> StoreResult<()> {
+        let mut transaction = self.pool.begin().await?;
+
+        save_schedule(&mut transaction, card).await?;
+
+        let result = sqlx::query("DELETE FROM reviews WHERE id = ?")
+            .bind(review_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(StoreError::NotFound);
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_reviews(&self, filters: GetReviewFilters) -> StoreResult<Vec<CardReview>> {
+        let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new("
+            SELECT reviews.id, reviews.card_id, reviews.difficulty, reviews.stage, reviews.reviewed_at, reviews.session_id, reviews.answer_time_ms,
+                reviews.previous_ease_factor, reviews.previous_interval, reviews.previous_step, reviews.previous_next_review,
+                reviews.previous_lapses, reviews.previous_suspended, reviews.cram
+            FROM reviews
+            JOIN flashcards ON flashcards.id = reviews.card_id
+            WHERE 1=1
+        ");
+
+        if let Some(deck_id) = filters.deck_id() {
+            query_builder.push(" AND flashcards.deck_id = ");
+            query_builder.push_bind(deck_id);
+        }
+
+        if let Some(card_id) = filters.card_id() {
+            query_builder.push(" AND reviews.card_id = ");
+            query_builder.push_bind(card_id);
+        }
+
+        if let Some(session_id) = filters.session_id() {
+            query_builder.push(" AND reviews.session_id = ");
+            query_builder.push_bind(session_id);
+        }
+
+        if let Some(since) = filters.since() {
+            query_builder.push(" AND reviews.reviewed_at >= ");
+            query_builder.push_bind(since);
+        }
+
+        query_builder.push(" ORDER BY reviews.id");
+
+        let reviews = query_builder
+            .build_query_as::<CardReview>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(reviews)
+    }
+
+    async fn get_decks(&self) -> StoreResult<Vec<Deck>> {
+        let decks = sqlx::query_as::<_, Deck>(&format!("SELECT {DECK_COLUMNS} FROM decks ORDER BY id"))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(decks)
+    }
+
+    async fn get_deck(&self, id: u32) -> StoreResult<Deck> {
+        let deck = sqlx::query_as::<_, Deck>(&format!("SELECT {DECK_COLUMNS} FROM decks WHERE id = ?"))
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(deck)
+    }
+
+    async fn add_deck(&self, deck: &Deck) -> StoreResult<Deck> {
+        let deck = sqlx::query_as::<_, Deck>(&format!("
+                INSERT INTO decks (name, new_cards_per_day, reviews_per_day)
+                VALUES (?, ?, ?)
+                RETURNING {DECK_COLUMNS}
+            "))
+            .bind(&deck.name)
+            .bind(deck.new_cards_per_day)
+            .bind(deck.reviews_per_day)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(deck)
+    }
+
+    async fn update_deck(&self, deck: &Deck) -> StoreResult<()> {
+        let result = sqlx::query(
+                "UPDATE decks SET
+                    name = ?,
+                    new_cards_per_day = ?,
+                    reviews_per_day = ?
+                WHERE id = ?"
+            )
+            .bind(&deck.name)
+            .bind(deck.new_cards_per_day)
+            .bind(deck.reviews_per_day)
+            .bind(deck.id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(StoreError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn add_session(&self, session: &StudySession) -> StoreResult<StudySession> {
+        let session = sqlx::query_as::<_, StudySession>(&format!("
+                INSERT INTO study_sessions (deck_id, started_at, ended_at, kind)
+                VALUES (?, ?, ?, ?)
+                RETURNING {SESSION_COLUMNS}
+            "))
+            .bind(session.deck_id)
+            .bind(session.started_at)
+            .bind(session.ended_at)
+            .bind(session.kind.to_string())
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(session)
+    }
+
+    async fn get_session(&self, id: u32) -> StoreResult<StudySession> {
+        let session = sqlx::query_as::<_, StudySession>(&format!("SELECT {SESSION_COLUMNS} FROM study_sessions WHERE id = ?"))
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(session)
+    }
+
+    async fn finish_session(&self, id: u32, ended_at: DateTime<Utc>) -> StoreResult<StudySession> {
+        // Finishing twice keeps the first end time.
+        let session = sqlx::query_as::<_, StudySession>(&format!("
+                UPDATE study_sessions SET
+                    ended_at = COALESCE(ended_at, ?)
+                WHERE id = ?
+                RETURNING {SESSION_COLUMNS}
+            "))
+            .bind(ended_at)
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(session)
+    }
+
+    async fn migration_status(&self) -> StoreResult<Vec<MigrationStatus>> {
+        migrations::status(&MIGRATOR, &self.pool).await
+    }
+
+    async fn run_migrations(&self) -> StoreResult<()> {
+        migrations::run(&MIGRATOR, &self.pool).await
+    }
+
+    async fn revert_migrations(&self, target: Option<i64>) -> StoreResult<()> {
+        migrations::revert(&MIGRATOR, &self.pool, target).await
+    }
+}
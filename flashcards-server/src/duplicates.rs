@@ -0,0 +1,64 @@
+use flashcards_data::{Card, CardStage, DuplicateGroup};
+use std::collections::BTreeMap;
+use unicode_normalization::UnicodeNormalization;
+
+// What two fronts are compared by. Compatibility normalisation makes e.g. full width letters and
+// ligatures match their plain forms; after that case and punctuation are dropped and runs of
+// whitespace become a single space. Accents are kept, they tell words apart.
+pub fn normalize(text: &str) -> String {
+    let folded: String = text.nfkc()
+        .flat_map(char::to_lowercase)
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect();
+
+    folded.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+// The cards among `cards` that a new card with `front` would duplicate. Fronts made only of
+// punctuation have nothing to compare and never count as duplicates.
+pub fn duplicates_of(cards: &[Card], front: &str) -> Vec<Card> {
+    let key = normalize(front);
+
+    if key.is_empty() {
+        return Vec::new();
+    }
+
+    cards.iter()
+        .filter(|card| normalize(card.front()) == key)
+        .cloned()
+        .collect()
+}
+
+// Groups the cards sharing a normalised front within the same deck, leaving out the cards
+// without a duplicate.
+pub fn group(cards: Vec<Card>) -> Vec<DuplicateGroup> {
+    let mut groups: BTreeMap<(u32, String), Vec<Card>> = BTreeMap::new();
+
+    for card in cards {
+        let key = normalize(card.front());
+        if !key.is_empty() {
+            groups.entry((*card.deck_id(), key)).or_default().push(card);
+        }
+    }
+
+    groups.into_iter()
+        .filter(|(_, cards)| cards.len() > 1)
+        .map(|((deck_id, key), cards)| DuplicateGroup { deck_id, key, cards })
+        .collect()
+}
+
+// Which of several duplicates to keep, given each card with the number of reviews it has: the
+// most reviewed, then the furthest along, then the oldest.
+pub fn pick_survivor(candidates: &[(Card, usize)]) -> Option<&Card> {
+    let progress = |card: &Card| match card.stage() {
+        CardStage::New => 0,
+        CardStage::Learning | CardStage::Relearning => 1,
+        CardStage::Review => 2,
+    };
+
+    candidates.iter()
+        .max_by_key(|(card, reviews)| {
+            (*reviews, progress(card), *card.interval(), std::cmp::Reverse((*card.created_at(), *card.id())))
+        })
+        .map(|(card, _)| card)
+}
@@ -0,0 +1,83 @@
+use axum::{
+    http::StatusCode,
+    routing::{
+        get,
+        post,
+        delete,
+        patch,
+        put,
+    },
+    Router,
+    response::{IntoResponse, Json, Response},
+};
+use serde_json::json;
+use std::sync::Arc;
+
+pub mod config;
+pub mod database;
+pub mod duplicates;
+mod routes;
+pub mod study;
+
+use flashcards_data::{DuplicateCards, SchedulerSettings};
+use crate::config::StudyConfig;
+use crate::database::{CardStore, StoreError};
+
+pub struct AppState {
+    pub database: Arc<dyn CardStore>,
+    pub scheduler: SchedulerSettings,
+    pub study: StudyConfig,
+}
+
+impl IntoResponse for StoreError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            StoreError::NotFound => StatusCode::NOT_FOUND,
+            StoreError::Invalid(_) => StatusCode::BAD_REQUEST,
+            StoreError::Duplicate(_) => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let body = match &self {
+            StoreError::Duplicate(cards) => json!(DuplicateCards { error: self.to_string(), duplicates: cards.clone() }),
+            _ => json!({ "error": self.to_string() }),
+        };
+
+        (status, Json(body)).into_response()
+    }
+}
+
+// Every route the API serves. CORS and other deployment concerns are layered on by the binary.
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/health", get(routes::get_health))
+        .route("/cards", get(routes::get_cards))
+        .route("/cards/due", get(routes::get_cards_due))
+        .route("/cards", post(routes::add_card))
+        .route("/cards/batch", post(routes::batch_cards))
+        .route("/cards/duplicates", get(routes::get_duplicates))
+        .route("/cards/answer-times", get(routes::get_answer_times))
+        .route("/cards/merge", post(routes::merge_cards))
+        .route("/cards/{card_id}/review", post(routes::review_card))
+        .route("/cards/{card_id}/reset", post(routes::reset_card))
+        .route("/cards/{card_id}/status", patch(routes::patch_card_status))
+        .route("/reviews/{review_id}/undo", post(routes::undo_review))
+        .route("/cards/{card_id}", delete(routes::remove_card))
+        .route("/cards/{card_id}", put(routes::update_card))
+        .route("/cards/{card_id}", patch(routes::patch_card))
+        .route("/cards/{card_id}/revisions", get(routes::get_card_revisions))
+        .route("/cards/{card_id}/revisions/{revision_id}/revert", post(routes::revert_card))
+        .route("/trash", get(routes::get_trash))
+        .route("/trash/{card_id}/restore", post(routes::restore_card))
+        .route("/decks", get(routes::get_decks))
+        .route("/decks", post(routes::add_deck))
+        .route("/decks/{deck_id}", get(routes::get_deck))
+        .route("/decks/{deck_id}", patch(routes::patch_deck))
+        .route("/study/queue", get(routes::get_study_queue))
+        .route("/scheduler", get(routes::get_scheduler))
+        .route("/sessions", post(routes::start_session))
+        .route("/sessions/{session_id}", get(routes::get_session))
+        .route("/sessions/{session_id}/next", get(routes::get_session_next))
+        .route("/sessions/{session_id}/finish", post(routes::finish_session))
+        .with_state(state)
+}
@@ -1,207 +1,103 @@
-use flashcards_data::{CardDifficulty, ReviewCardPayload, CreateCardPayload, Card};
-
-use chrono::{Utc, Days};
+use clap::Parser;
 use tower_http::cors::{CorsLayer};
-use http::header::{HeaderValue};
 use http::Method;
-use axum::{
-    extract::{State, Path},
-    routing::{
-        get,
-        post,
-        delete,
-        put,
-    },
-    Router,
-    response::Json,
-};
-/* use axum_macros::{
-    debug_handler,
-};*/
-
-use serde_json::{Value, json};
-use std::sync::{Arc, Mutex};
-
-mod database;
-use crate::database::{Database, GetCardFilters};
-
-const DB_URL: &str = "sqlite://flashcards.db";
-
-struct AppState {
-    database: Mutex<Database>,
-}
-
+use std::sync::Arc;
+use std::time::Duration;
 
+use flashcards_server::AppState;
+use flashcards_server::config::{Cli, Command, Config, MigrateCommand, TrashConfig};
+use flashcards_server::database::{self, CardStore};
 
 #[tokio::main]
-async fn main() -> Result<(), sqlx::Error> {
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
-    let database = Database::new();
-        
-    let shared_state = Arc::new(AppState {
-        database: Mutex::new(database),
-    });
+    let cli = Cli::parse();
 
-    let cors = CorsLayer::new()
-        .allow_origin("http://localhost:8080".parse::<HeaderValue>().unwrap())
-        .allow_methods([Method::PUT, Method::DELETE, Method::GET, Method::POST, Method::OPTIONS])
-        .allow_headers(tower_http::cors::Any/*["priority"]*/); // I'd rather not do the ANY thing.
+    let config = match Config::load(&cli.args) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+    };
 
-    let app = Router::new()
-        .route("/health", get(get_health))
-        .route("/cards", get(get_cards))
-        .route("/cards/due", get(get_cards_due))
-        .route("/cards", post(add_card))
-        .route("/cards/{card_id}/review", post(review_card))
-        .route("/cards/{card_id}", delete(remove_card))
-        .route("/cards/{card_id}", put(update_card))
-        .with_state(shared_state)
-        .layer(cors);
+    env_logger::Builder::new()
+        .filter_level(config.log_level())
+        .init();
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    let database = database::connect(&config.database.url, config.database.max_connections).await?;
 
-    Ok(())
+    if let Some(Command::Migrate(command)) = cli.command {
+        return migrate(database.as_ref(), command).await;
+    }
 
-}
-
-async fn remove_card(State(state): State<Arc<AppState>>, Path(card_id): Path<u32>/*, Json(payload): Json<DeleteCardPayload>*/) -> Json<Value> {
+    // Refuses to start when the database was migrated by a newer server.
+    if let Err(error) = database.run_migrations().await {
+        log::error!("{}", error);
+        std::process::exit(1);
+    }
 
-    let database = state.database.lock().unwrap();
+    // Only a running server needs it, `migrate` leaves the disk alone.
+    std::fs::create_dir_all(&config.media_dir)?;
 
-    println!("CardId: {}", card_id);
+    tokio::spawn(purge_trash(database.clone(), config.trash.clone()));
 
-    database.remove_card(card_id);
+    let shared_state = Arc::new(AppState {
+        database,
+        scheduler: config.scheduler.clone(),
+        study: config.study.clone(),
+    });
 
-    Json(json!(
-        true
-    ))
+    let cors = CorsLayer::new()
+        .allow_origin(config.allowed_origins())
+        .allow_methods([Method::PUT, Method::PATCH, Method::DELETE, Method::GET, Method::POST, Method::OPTIONS])
+        .allow_headers(tower_http::cors::Any/*["priority"]*/); // I'd rather not do the ANY thing.
 
-}
+    let app = flashcards_server::router(shared_state)
+        .layer(cors);
 
-async fn update_card(State(state): State<Arc<AppState>>, Path(card_id): Path<u32>, Json(payload): Json<CreateCardPayload>) -> Json<Value> {
-    let database = state.database.lock().unwrap();
+    log::info!("Listening on {}", config.listen_address);
 
-    let updated_card = Card::new(
-        card_id,
-        payload.front.clone(),
-        payload.back.clone(),
-    );
+    let listener = tokio::net::TcpListener::bind(&config.listen_address).await?;
+    axum::serve(listener, app).await?;
 
-    database.update_card(&updated_card);
+    Ok(())
 
-    Json(json!(
-        updated_card
-    ))
 }
 
-async fn add_card(State(state): State<Arc<AppState>>, Json(payload): Json<CreateCardPayload>) -> Json<Value> {
-
-    let database = state.database.lock().unwrap();
-    let cards = &mut database.get_cards(GetCardFilters::default());
-    let cards_total = cards.len();
+// Empties the trash of cards deleted longer ago than the retention period, once at startup and
+// then every hour.
+async fn purge_trash(database: Arc<dyn CardStore>, trash: TrashConfig) {
+    let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
 
-    let new_card = Card::new(
-        cards_total as u32,
-        payload.front.clone(),
-        payload.back.clone(),
-    ); 
+    loop {
+        interval.tick().await;
 
-    database.add_card(&new_card);
-
-    cards.push(Card::new(
-        cards_total as u32,
-        payload.front.clone(),
-        payload.back.clone(),
-    ));
-
-    Json(json!(
-        new_card
-    ))
-
-}
-
-async fn review_card(
-        State(state): State<Arc<AppState>>, 
-        Path(card_id): Path<u32>,
-        Json(payload): Json<ReviewCardPayload>,
-    ) -> Json<Value> {
-
-    let database = state.database.lock().unwrap();
-    let card = &mut database.get_card(card_id);
-    let difficulty = payload.difficulty.clone();
-
-    if let Some(card) = card {
-        match difficulty {
-            CardDifficulty::Easy => {
-                let mut ease_factor = card.ease_factor().clone();
-                card.set_interval(card.interval() * card.ease_factor());
-
-                ease_factor = u8::min(5, ease_factor + 1);
-                card.set_ease_factor(ease_factor);
-            },
-            CardDifficulty::Medium => {
-                let mut ease_factor = card.ease_factor().clone();
-                ease_factor = u8::min(5, ease_factor + 1);
-                card.set_ease_factor(ease_factor);
-            },
-            CardDifficulty::Hard => {
-                card.set_interval(1);
-
-                let mut ease_factor = card.ease_factor().clone();
-                ease_factor = u8::max(1, ease_factor - 1);
-                card.set_ease_factor(ease_factor);
-            },
+        let deleted_before = chrono::Utc::now() - chrono::TimeDelta::days(trash.retention_days as i64);
+        match database.purge_trash(deleted_before).await {
+            Ok(0) => {},
+            Ok(purged) => log::info!("Purged {} cards from the trash", purged),
+            Err(error) => log::error!("Could not purge the trash: {}", error),
         }
-
-        let mut dt = Utc::now();
-        let days_to_add = Days::new(*card.interval() as u64);
-        dt = dt.checked_add_days(days_to_add).unwrap();
-
-        card.set_next_review(&format!("{dt}"));
-
-        database.update_card(&card);
-
-        return Json(json!(
-            card
-        ));
-
-    };
-
-    Json(json!(
-        None::<Card>
-    ))
-
+    }
 }
 
-// Function to serve route /cards/due 
-async fn get_cards_due(State(state): State<Arc<AppState>>) -> Json<Value> {
-    let database = state.database.lock().unwrap();
-
-    let dt = Utc::now();
-
-    let filters = GetCardFilters::default()
-        .add_from(dt);
-
-    println!("{}", dt.timestamp());
+async fn migrate(database: &dyn CardStore, command: MigrateCommand) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        MigrateCommand::Status => {},
+        MigrateCommand::Run => database.run_migrations().await?,
+        MigrateCommand::Revert { target } => database.revert_migrations(target).await?,
+    }
 
-    let cards = database.get_cards(filters);
+    for status in database.migration_status().await? {
+        let state = match (status.applied, status.known) {
+            (_, false) => "unknown",
+            (true, true) => "applied",
+            (false, true) => "pending",
+        };
 
-    Json(json!(
-        cards
-    ))
-}
-
-async fn get_cards(State(state): State<Arc<AppState>>) -> Json<Value> {
-    //let cards = state.cards.lock().unwrap();
-    let database = state.database.lock().unwrap();
-    let cards = database.get_cards(GetCardFilters::default());
+        println!("{:<16} {:<8} {}", status.version, state, status.description);
+    }
 
-    Json(json!(
-        cards
-    ))
-}
-
-async fn get_health() -> String {
-    String::from("200 OK")
+    Ok(())
 }
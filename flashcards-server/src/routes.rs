@@ -0,0 +1,727 @@
+use flashcards_data::{
+    BatchOperation,
+    BatchPayload,
+    BatchResult,
+    MergeCardsPayload,
+    MergedCards,
+    ReviewCardPayload,
+    CreateCardPayload,
+    UpdateCardPayload,
+    UpdateCardStatusPayload,
+    CreateDeckPayload,
+    UpdateDeckPayload,
+    CreateSessionPayload,
+    Card,
+    CardFlag,
+    CardReview,
+    CramSelection,
+    ReviewedCard,
+    Deck,
+    SessionKind,
+    StudyQueue,
+    StudySession,
+    DEFAULT_DECK_ID,
+    EDITOR_HEADER,
+    normalize_tag,
+};
+
+use chrono::{DateTime, Utc};
+use axum::{
+    extract::{State, Path, Query},
+    http::HeaderMap,
+    response::Json,
+};
+use serde::Deserialize;
+/* use axum_macros::{
+    debug_handler,
+};*/
+
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+use crate::{duplicates, study, AppState};
+use crate::database::{CardChange, CardEdit, GetCardFilters, GetReviewFilters, StoreError};
+
+pub async fn remove_card(State(state): State<Arc<AppState>>, Path(card_id): Path<u32>/*, Json(payload): Json<DeleteCardPayload>*/) -> Result<Json<Value>, StoreError> {
+
+    log::debug!("CardId: {}", card_id);
+
+    // Deleting only moves the card to the trash, see /trash.
+    state.database.remove_card(card_id, Utc::now()).await?;
+
+    Ok(Json(json!(
+        true
+    )))
+
+}
+
+// Deleted cards that haven't been purged yet, most recently deleted first.
+pub async fn get_trash(State(state): State<Arc<AppState>>) -> Result<Json<Value>, StoreError> {
+    let mut cards = state.database.get_cards(GetCardFilters::default().add_trashed()).await?;
+    cards.sort_by_key(|card| std::cmp::Reverse(*card.deleted_at()));
+
+    Ok(Json(json!(cards)))
+}
+
+pub async fn restore_card(State(state): State<Arc<AppState>>, Path(card_id): Path<u32>) -> Result<Json<Value>, StoreError> {
+    let card = state.database.restore_card(card_id).await?;
+
+    Ok(Json(json!(card)))
+}
+
+// Whoever the client says is editing, there are no accounts to check it against.
+fn editor(headers: &HeaderMap) -> Option<String> {
+    let editor = headers.get(EDITOR_HEADER)?.to_str().ok()?.trim();
+
+    match editor.is_empty() {
+        true => None,
+        false => Some(editor.chars().take(100).collect()),
+    }
+}
+
+// PUT replaces the whole content of a card. Like PATCH it leaves the scheduling state alone;
+// use /cards/{card_id}/reset to start a card over.
+pub async fn update_card(State(state): State<Arc<AppState>>, Path(card_id): Path<u32>, headers: HeaderMap, Json(payload): Json<CreateCardPayload>) -> Result<Json<Value>, StoreError> {
+
+    let update = UpdateCardPayload {
+        front: Some(payload.front),
+        back: Some(payload.back),
+    };
+
+    let updated_card = state.database.update_card_content(card_id, &update, editor(&headers).as_deref()).await?;
+
+    Ok(Json(json!(
+        updated_card
+    )))
+}
+
+pub async fn patch_card(State(state): State<Arc<AppState>>, Path(card_id): Path<u32>, headers: HeaderMap, Json(payload): Json<UpdateCardPayload>) -> Result<Json<Value>, StoreError> {
+
+    let updated_card = state.database.update_card_content(card_id, &payload, editor(&headers).as_deref()).await?;
+
+    Ok(Json(json!(
+        updated_card
+    )))
+}
+
+// Earlier content of a card, oldest first.
+pub async fn get_card_revisions(State(state): State<Arc<AppState>>, Path(card_id): Path<u32>) -> Result<Json<Value>, StoreError> {
+
+    state.database.get_card(card_id).await?;
+    let revisions = state.database.get_card_revisions(card_id).await?;
+
+    Ok(Json(json!(revisions)))
+}
+
+// Puts the content of a revision back. This is an edit like any other, so the content being
+// replaced becomes a revision itself and the revert can be reverted in turn.
+pub async fn revert_card(State(state): State<Arc<AppState>>, Path((card_id, revision_id)): Path<(u32, u32)>, headers: HeaderMap) -> Result<Json<Value>, StoreError> {
+
+    let revision = state.database.get_card_revisions(card_id).await?
+        .into_iter()
+        .find(|revision| revision.id == revision_id)
+        .ok_or(StoreError::NotFound)?;
+
+    let update = UpdateCardPayload {
+        front: Some(revision.front),
+        back: Some(revision.back),
+    };
+
+    let updated_card = state.database.update_card_content(card_id, &update, editor(&headers).as_deref()).await?;
+
+    Ok(Json(json!(
+        updated_card
+    )))
+}
+
+// Suspends, buries or flags a card, or undoes that.
+pub async fn patch_card_status(State(state): State<Arc<AppState>>, Path(card_id): Path<u32>, Json(payload): Json<UpdateCardStatusPayload>) -> Result<Json<Value>, StoreError> {
+
+    let mut card = state.database.get_card(card_id).await?;
+
+    if let Some(suspended) = payload.suspended {
+        card.set_suspended(suspended);
+    }
+    if let Some(buried) = payload.buried {
+        let buried_until = buried.then(|| {
+            study::next_day_start(Utc::now(), state.study.timezone(), state.study.rollover_hour)
+        });
+        card.set_buried_until(buried_until);
+    }
+    if let Some(flag) = payload.flag {
+        card.set_flag(flag);
+    }
+
+    state.database.update_card(&card).await?;
+
+    Ok(Json(json!(
+        card
+    )))
+}
+
+// Throws away the learning progress of a card, it is scheduled as if it was new.
+pub async fn reset_card(State(state): State<Arc<AppState>>, Path(card_id): Path<u32>) -> Result<Json<Value>, StoreError> {
+
+    let mut card = state.database.get_card(card_id).await?;
+
+    state.scheduler.reset(&mut card, Utc::now());
+
+    state.database.update_card(&card).await?;
+
+    Ok(Json(json!(
+        card
+    )))
+}
+
+// A card as it starts out, before the store assigns it an id.
+fn new_card(state: &AppState, payload: CreateCardPayload) -> Card {
+    let mut new_card = Card::new(
+        0,
+        payload.front,
+        payload.back,
+    );
+    new_card.set_deck_id(payload.deck_id.unwrap_or(DEFAULT_DECK_ID));
+    new_card.set_ease_factor(state.scheduler.starting_ease_factor);
+    new_card.set_interval(state.scheduler.starting_interval);
+    new_card
+}
+
+pub async fn add_card(State(state): State<Arc<AppState>>, Json(payload): Json<CreateCardPayload>) -> Result<Json<Value>, StoreError> {
+
+    let deck = state.database.get_deck(payload.deck_id.unwrap_or(DEFAULT_DECK_ID)).await?;
+
+    if !payload.allow_duplicate {
+        let deck_cards = state.database.get_cards(GetCardFilters::default().add_deck(deck.id)).await?;
+        let duplicates = duplicates::duplicates_of(&deck_cards, &payload.front);
+
+        if !duplicates.is_empty() {
+            return Err(StoreError::Duplicate(duplicates));
+        }
+    }
+
+    // The id is assigned by the store, so respond with the persisted card rather than
+    // the one we built.
+    let saved_card = state.database.add_card(&new_card(&state, payload)).await?;
+
+    Ok(Json(json!(
+        saved_card
+    )))
+
+}
+
+fn normalize_tags(tags: &[String]) -> Result<Vec<String>, StoreError> {
+    tags.iter()
+        .map(|tag| normalize_tag(tag).ok_or_else(|| StoreError::Invalid(String::from("tags can't be blank"))))
+        .collect()
+}
+
+// Large enough to reorganise a deck in one go, small enough to keep the transaction short.
+const MAXIMUM_BATCH_OPERATIONS: usize = 1000;
+
+// Carries out many operations in a single transaction and reports on each, in order.
+pub async fn batch_cards(State(state): State<Arc<AppState>>, headers: HeaderMap, Json(payload): Json<BatchPayload>) -> Result<Json<Value>, StoreError> {
+
+    if payload.operations.len() > MAXIMUM_BATCH_OPERATIONS {
+        return Err(StoreError::Invalid(format!("a batch holds at most {} operations", MAXIMUM_BATCH_OPERATIONS)));
+    }
+
+    // New cards are checked for duplicates against the cards already stored and the ones
+    // created earlier in the same batch.
+    let mut known_cards = state.database.get_cards(GetCardFilters::default()).await?;
+
+    let changes: Vec<Result<CardChange, StoreError>> = payload.operations.into_iter()
+        .map(|operation| match operation {
+            BatchOperation::Create(payload) => {
+                let card = new_card(&state, payload.clone());
+                let deck_cards: Vec<Card> = known_cards.iter()
+                    .filter(|known| known.deck_id() == card.deck_id())
+                    .cloned()
+                    .collect();
+                let duplicates = duplicates::duplicates_of(&deck_cards, card.front());
+
+                if !payload.allow_duplicate && !duplicates.is_empty() {
+                    return Err(StoreError::Duplicate(duplicates));
+                }
+
+                known_cards.push(card.clone());
+                Ok(CardChange::Create(card))
+            },
+            BatchOperation::Update { id, front, back } => Ok(CardChange::UpdateContent(id, UpdateCardPayload { front, back })),
+            BatchOperation::Delete { id } => Ok(CardChange::Trash(id)),
+            BatchOperation::Move { id, deck_id } => Ok(CardChange::Edit(id, CardEdit { deck_id: Some(deck_id), ..Default::default() })),
+            BatchOperation::Flag { id, flag } => Ok(CardChange::Edit(id, CardEdit { flag: Some(flag), ..Default::default() })),
+            BatchOperation::Suspend { id, suspended } => Ok(CardChange::Edit(id, CardEdit { suspended: Some(suspended), ..Default::default() })),
+            BatchOperation::Tag { id, add, remove } => Ok(CardChange::Tag(id, normalize_tags(&add)?, normalize_tags(&remove)?)),
+        })
+        .collect();
+
+    let accepted: Vec<CardChange> = changes.iter()
+        .filter_map(|change| change.as_ref().ok().cloned())
+        .collect();
+
+    let mut outcomes = state.database.apply_changes(&accepted, editor(&headers).as_deref(), Utc::now()).await?
+        .into_iter();
+
+    // Put the store's outcomes back in between the operations refused up front.
+    let results: Vec<BatchResult> = changes.into_iter()
+        .map(|change| match change.and_then(|_| outcomes.next().unwrap_or(Err(StoreError::NotFound))) {
+            Ok(card) => BatchResult::Done { card },
+            Err(error) => BatchResult::Failed { error: error.to_string() },
+        })
+        .collect();
+
+    Ok(Json(json!(results)))
+}
+
+// Cards that are likely the same card, grouped per deck.
+pub async fn get_duplicates(State(state): State<Arc<AppState>>, Query(params): Query<DuplicateParams>) -> Result<Json<Value>, StoreError> {
+
+    let filters = match params.deck_id {
+        Some(deck_id) => GetCardFilters::default().add_deck(deck_id),
+        None => GetCardFilters::default(),
+    };
+
+    let groups = duplicates::group(state.database.get_cards(filters).await?);
+
+    Ok(Json(json!(groups)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DuplicateParams {
+    deck_id: Option<u32>,
+}
+
+// Average and longest answer time of every card that has timed answers, optionally of one deck.
+pub async fn get_answer_times(State(state): State<Arc<AppState>>, Query(params): Query<AnswerTimeParams>) -> Result<Json<Value>, StoreError> {
+
+    let filters = match params.deck_id {
+        Some(deck_id) => GetReviewFilters::default().add_deck(deck_id),
+        None => GetReviewFilters::default(),
+    };
+
+    let reviews = state.database.get_reviews(filters).await?;
+
+    Ok(Json(json!(
+        study::answer_times(&reviews)
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnswerTimeParams {
+    deck_id: Option<u32>,
+}
+
+// Keeps the duplicate with the best scheduling history and moves the others to the trash,
+// from where they can still be restored. Reviews are not moved over to the kept card: each one
+// holds the state undo puts back, which only fits the card it was given to. The trashed cards
+// keep their reviews until the trash is purged, then that history is gone.
+pub async fn merge_cards(State(state): State<Arc<AppState>>, Json(payload): Json<MergeCardsPayload>) -> Result<Json<Value>, StoreError> {
+
+    let mut card_ids = payload.card_ids;
+    card_ids.sort_unstable();
+    card_ids.dedup();
+
+    if card_ids.len() < 2 {
+        return Err(StoreError::Invalid(String::from("a merge needs at least two cards")));
+    }
+
+    let mut candidates = Vec::new();
+    for card_id in card_ids {
+        let card = state.database.get_card(card_id).await?;
+        let reviews = state.database.get_reviews(GetReviewFilters::default().add_card(card_id)).await?;
+        candidates.push((card, reviews.len()));
+    }
+
+    // Only cards that `GET /cards/duplicates` would group together can be merged.
+    let (first, _) = &candidates[0];
+    let key = duplicates::normalize(first.front());
+
+    let same_group = !key.is_empty() && candidates.iter()
+        .all(|(card, _)| card.deck_id() == first.deck_id() && duplicates::normalize(card.front()) == key);
+
+    if !same_group {
+        return Err(StoreError::Invalid(String::from("only duplicates within one deck can be merged")));
+    }
+
+    let kept = duplicates::pick_survivor(&candidates)
+        .cloned()
+        .ok_or(StoreError::NotFound)?;
+
+    let changes: Vec<CardChange> = candidates.iter()
+        .filter(|(card, _)| card.id() != kept.id())
+        .map(|(card, _)| CardChange::Trash(*card.id()))
+        .collect();
+
+    let trashed = state.database.apply_changes(&changes, None, Utc::now()).await?
+        .into_iter()
+        .filter_map(|outcome| outcome.ok())
+        .map(|card| *card.id())
+        .collect();
+
+    Ok(Json(json!(MergedCards { kept, trashed })))
+}
+
+pub async fn review_card(
+        State(state): State<Arc<AppState>>,
+        Path(card_id): Path<u32>,
+        Json(payload): Json<ReviewCardPayload>,
+    ) -> Result<Json<Value>, StoreError> {
+
+    let now = Utc::now();
+    let mut card = state.database.get_card(card_id).await?;
+
+    let cram = match payload.session_id {
+        Some(session_id) => {
+            let session = state.database.get_session(session_id).await?;
+            if session.ended_at.is_some() {
+                return Err(StoreError::Invalid(format!("session {} has already finished", session_id)));
+            }
+            session.kind.is_cram()
+        },
+        None => false,
+    };
+
+    let answer_time_ms = payload.answer_time_ms.map(|answer_time_ms| state.study.cap_answer_time(answer_time_ms));
+
+    // The review is logged with the stage the card was answered in, that is what the
+    // daily limits count, and with the answer as given even when it is scheduled as a slower one.
+    let review = CardReview {
+        session_id: payload.session_id,
+        answer_time_ms,
+        cram,
+        ..CardReview::of(&card, payload.difficulty.clone(), now)
+    };
+
+    // Cramming is practice on the side, the card keeps the schedule it had.
+    if !cram {
+        state.scheduler.review(&mut card, &payload.difficulty, answer_time_ms, now);
+    }
+
+    let review = state.database.record_review(&card, &review).await?;
+
+    // As stored: Postgres keeps timestamps to the microsecond only.
+    let card = state.database.get_card(card_id).await?;
+
+    Ok(Json(json!(
+        ReviewedCard {
+            card,
+            review_id: review.id,
+        }
+    )))
+
+}
+
+// Takes back the latest answer given to a card. The card gets the scheduling state the review
+// log has from before that answer, and the review is removed.
+pub async fn undo_review(
+        State(state): State<Arc<AppState>>,
+        Path(review_id): Path<u32>,
+    ) -> Result<Json<Value>, StoreError> {
+
+    let review = state.database.get_review(review_id).await?;
+
+    let card_reviews = state.database.get_reviews(
+        GetReviewFilters::default()
+            .add_card(review.card_id)
+    ).await?;
+
+    if card_reviews.last().map(|latest| latest.id) != Some(review_id) {
+        return Err(StoreError::Invalid(format!("review {} is not the latest review of card {}", review_id, review.card_id)));
+    }
+
+    let mut card = state.database.get_card(review.card_id).await?;
+
+    if !review.restore(&mut card) {
+        return Err(StoreError::Invalid(format!("review {} was logged without the previous state of the card", review_id)));
+    }
+
+    state.database.undo_review(&card, review_id).await?;
+
+    Ok(Json(json!(
+        card
+    )))
+
+}
+
+// Function to serve route /cards/due 
+pub async fn get_cards_due(State(state): State<Arc<AppState>>) -> Result<Json<Value>, StoreError> {
+
+    let dt = Utc::now();
+
+    let filters = GetCardFilters::default()
+        .add_from(dt)
+        .add_studyable(dt);
+
+    log::debug!("{}", dt.timestamp());
+
+    let cards = state.database.get_cards(filters).await?;
+
+    Ok(Json(json!(
+        cards
+    )))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CardListParams {
+    suspended: Option<bool>,
+    buried: Option<bool>,
+    flag: Option<CardFlag>,
+    leech: Option<bool>,
+}
+
+pub async fn get_cards(State(state): State<Arc<AppState>>, Query(params): Query<CardListParams>) -> Result<Json<Value>, StoreError> {
+
+    let mut filters = GetCardFilters::default();
+
+    if let Some(suspended) = params.suspended {
+        filters = filters.add_suspended(suspended);
+    }
+    if let Some(buried) = params.buried {
+        filters = filters.add_buried(buried, Utc::now());
+    }
+    if let Some(flag) = params.flag {
+        filters = filters.add_flag(flag);
+    }
+    if let Some(leech) = params.leech {
+        // With leech detection turned off there are no leeches.
+        let threshold = match state.scheduler.leech_threshold {
+            0 => u32::MAX,
+            threshold => threshold,
+        };
+        filters = filters.add_leech(leech, threshold);
+    }
+
+    let cards = state.database.get_cards(filters).await?;
+
+    Ok(Json(json!(
+        cards
+    )))
+}
+
+pub async fn get_decks(State(state): State<Arc<AppState>>) -> Result<Json<Value>, StoreError> {
+    let decks = state.database.get_decks().await?;
+
+    Ok(Json(json!(
+        decks
+    )))
+}
+
+pub async fn get_deck(State(state): State<Arc<AppState>>, Path(deck_id): Path<u32>) -> Result<Json<Value>, StoreError> {
+    let deck = state.database.get_deck(deck_id).await?;
+
+    Ok(Json(json!(
+        deck
+    )))
+}
+
+pub async fn add_deck(State(state): State<Arc<AppState>>, Json(payload): Json<CreateDeckPayload>) -> Result<Json<Value>, StoreError> {
+
+    if payload.name.trim().is_empty() {
+        return Err(StoreError::Invalid(String::from("a deck needs a name")));
+    }
+
+    let mut deck = Deck::new(0, payload.name.trim().to_string());
+    if let Some(new_cards_per_day) = payload.new_cards_per_day {
+        deck.new_cards_per_day = new_cards_per_day;
+    }
+    if let Some(reviews_per_day) = payload.reviews_per_day {
+        deck.reviews_per_day = reviews_per_day;
+    }
+
+    let saved_deck = state.database.add_deck(&deck).await?;
+
+    Ok(Json(json!(
+        saved_deck
+    )))
+}
+
+pub async fn patch_deck(State(state): State<Arc<AppState>>, Path(deck_id): Path<u32>, Json(payload): Json<UpdateDeckPayload>) -> Result<Json<Value>, StoreError> {
+
+    let mut deck = state.database.get_deck(deck_id).await?;
+
+    if let Some(name) = payload.name {
+        if name.trim().is_empty() {
+            return Err(StoreError::Invalid(String::from("a deck needs a name")));
+        }
+        deck.name = name.trim().to_string();
+    }
+    if let Some(new_cards_per_day) = payload.new_cards_per_day {
+        deck.new_cards_per_day = new_cards_per_day;
+    }
+    if let Some(reviews_per_day) = payload.reviews_per_day {
+        deck.reviews_per_day = reviews_per_day;
+    }
+
+    state.database.update_deck(&deck).await?;
+
+    Ok(Json(json!(
+        deck
+    )))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StudyQueueParams {
+    deck_id: Option<u32>,
+}
+
+// Today's queue for a deck: everything that is due, cut down to what the deck's daily limits
+// still allow. Also returns when the earliest learning card that isn't due yet comes back.
+async fn todays_queue(state: &AppState, deck: &Deck, now: DateTime<Utc>) -> Result<(StudyQueue, Option<DateTime<Utc>>), StoreError> {
+
+    let day_start = study::day_start(now, state.study.timezone(), state.study.rollover_hour);
+
+    let reviews_today: Vec<CardReview> = state.database.get_reviews(
+        GetReviewFilters::default()
+            .add_deck(deck.id)
+            .add_since(day_start)
+    ).await?
+        .into_iter()
+        .filter(|review| !review.cram)
+        .collect();
+
+    let (due_cards, waiting_cards): (Vec<Card>, Vec<Card>) = state.database.get_cards(
+        GetCardFilters::default()
+            .add_deck(deck.id)
+            .add_studyable(now)
+    ).await?
+        .into_iter()
+        .partition(|card| card.is_due_at(now));
+
+    let upcoming_learning = waiting_cards.iter()
+        .filter(|card| card.stage().is_learning())
+        .map(|card| *card.next_review())
+        .min();
+
+    Ok((study::build_queue(deck, due_cards, &reviews_today), upcoming_learning))
+}
+
+// The scheduler settings the server runs with, so clients can preview what an answer does.
+pub async fn get_scheduler(State(state): State<Arc<AppState>>) -> Json<Value> {
+    Json(json!(
+        state.scheduler
+    ))
+}
+
+pub async fn get_study_queue(State(state): State<Arc<AppState>>, Query(params): Query<StudyQueueParams>) -> Result<Json<Value>, StoreError> {
+
+    let deck = state.database.get_deck(params.deck_id.unwrap_or(DEFAULT_DECK_ID)).await?;
+    let (queue, _) = todays_queue(&state, &deck, Utc::now()).await?;
+
+    Ok(Json(json!(
+        queue
+    )))
+}
+
+// Ten years, further ahead than any interval the scheduler hands out in practice.
+const MAXIMUM_CRAM_AHEAD_DAYS: u32 = 3650;
+
+pub async fn start_session(State(state): State<Arc<AppState>>, Json(mut payload): Json<CreateSessionPayload>) -> Result<Json<Value>, StoreError> {
+
+    let deck = state.database.get_deck(payload.deck_id.unwrap_or(DEFAULT_DECK_ID)).await?;
+
+    match &mut payload.kind {
+        SessionKind::Cram(CramSelection::Random { count: 0 }) => {
+            return Err(StoreError::Invalid(String::from("a random cram session needs at least one card")));
+        },
+        SessionKind::Cram(CramSelection::Ahead { days: 0 }) => {
+            return Err(StoreError::Invalid(String::from("cramming ahead needs at least one day")));
+        },
+        SessionKind::Cram(CramSelection::Ahead { days }) if *days > MAXIMUM_CRAM_AHEAD_DAYS => {
+            return Err(StoreError::Invalid(format!("cramming ahead covers at most {} days", MAXIMUM_CRAM_AHEAD_DAYS)));
+        },
+        SessionKind::Cram(CramSelection::Tag { tag }) => {
+            *tag = normalize_tag(tag).ok_or_else(|| StoreError::Invalid(String::from("tags can't be blank")))?;
+        },
+        _ => {},
+    }
+
+    let session = state.database.add_session(&StudySession {
+        deck_id: deck.id,
+        started_at: Utc::now(),
+        kind: payload.kind,
+        ..StudySession::default()
+    }).await?;
+
+    Ok(Json(json!(
+        session
+    )))
+}
+
+pub async fn get_session_next(State(state): State<Arc<AppState>>, Path(session_id): Path<u32>) -> Result<Json<Value>, StoreError> {
+
+    let now = Utc::now();
+    let session = state.database.get_session(session_id).await?;
+
+    if session.ended_at.is_some() {
+        return Err(StoreError::Invalid(format!("session {} has already finished", session_id)));
+    }
+
+    let deck = state.database.get_deck(session.deck_id).await?;
+
+    let session_reviews = state.database.get_reviews(
+        GetReviewFilters::default()
+            .add_session(session.id)
+    ).await?;
+
+    if let SessionKind::Cram(selection) = &session.kind {
+        let day_start = study::day_start(now, state.study.timezone(), state.study.rollover_hour);
+
+        let reviews_today = state.database.get_reviews(
+            GetReviewFilters::default()
+                .add_deck(deck.id)
+                .add_since(day_start)
+        ).await?;
+
+        // Suspended and buried cards stay out of cramming too, they were put aside on purpose.
+        let cards = state.database.get_cards(
+            GetCardFilters::default()
+                .add_deck(deck.id)
+                .add_studyable(now)
+        ).await?;
+
+        let cards = study::cram_cards(selection, session.id, cards, &reviews_today, now);
+
+        return Ok(Json(json!(
+            study::cram_next(&cards, &session_reviews)
+        )));
+    }
+
+    let (queue, upcoming_learning) = todays_queue(&state, &deck, now).await?;
+
+    Ok(Json(json!(
+        study::next_card(&queue, &session_reviews, upcoming_learning)
+    )))
+}
+
+pub async fn get_session(State(state): State<Arc<AppState>>, Path(session_id): Path<u32>) -> Result<Json<Value>, StoreError> {
+
+    let session = state.database.get_session(session_id).await?;
+
+    let session_reviews = state.database.get_reviews(
+        GetReviewFilters::default()
+            .add_session(session.id)
+    ).await?;
+
+    Ok(Json(json!(
+        study::summarize(session, &session_reviews, Utc::now())
+    )))
+}
+
+pub async fn finish_session(State(state): State<Arc<AppState>>, Path(session_id): Path<u32>) -> Result<Json<Value>, StoreError> {
+
+    let now = Utc::now();
+    let session = state.database.finish_session(session_id, now).await?;
+
+    let session_reviews = state.database.get_reviews(
+        GetReviewFilters::default()
+            .add_session(session.id)
+    ).await?;
+
+    Ok(Json(json!(
+        study::summarize(session, &session_reviews, now)
+    )))
+}
+
+pub async fn get_health() -> String {
+    String::from("200 OK")
+}
@@ -0,0 +1,240 @@
+use chrono::{DateTime, NaiveTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+use flashcards_data::{Card, CardAnswerTimes, CardDifficulty, CardReview, CardStage, CramSelection, Deck, SessionCard, SessionSummary, StudyQueue, StudySession};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// When the study day containing `now` began. Days start at `rollover_hour` local time rather
+// than at midnight, so reviews done at 1am still count towards the evening before.
+pub fn day_start(now: DateTime<Utc>, timezone: Tz, rollover_hour: u32) -> DateTime<Utc> {
+    let local = now.with_timezone(&timezone);
+    let date = (local - TimeDelta::hours(rollover_hour as i64)).date_naive();
+    let start = date.and_time(NaiveTime::from_hms_opt(rollover_hour, 0, 0).unwrap_or_default());
+
+    // The rollover hour can fall into a DST gap, in which case the day starts an hour later.
+    timezone.from_local_datetime(&start).earliest()
+        .or_else(|| timezone.from_local_datetime(&(start + TimeDelta::hours(1))).earliest())
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or(now)
+}
+
+// When the study day after the one containing `now` begins, which is when buried cards return.
+pub fn next_day_start(now: DateTime<Utc>, timezone: Tz, rollover_hour: u32) -> DateTime<Utc> {
+    day_start(now + TimeDelta::days(1), timezone, rollover_hour)
+}
+
+// Assembles what is left to study today from the deck's due cards and the reviews made since
+// the study day began. Learning cards are never held back, they were started already.
+pub fn build_queue(deck: &Deck, due_cards: Vec<Card>, reviews_today: &[CardReview]) -> StudyQueue {
+    let new_studied = reviews_today.iter()
+        .filter(|review| review.stage == CardStage::New)
+        .count() as u32;
+    let reviews_studied = reviews_today.iter()
+        .filter(|review| review.stage == CardStage::Review)
+        .count() as u32;
+
+    let new_remaining = deck.new_cards_per_day.saturating_sub(new_studied);
+    let reviews_remaining = deck.reviews_per_day.saturating_sub(reviews_studied);
+
+    let mut learning = Vec::new();
+    let mut reviews = Vec::new();
+    let mut new_cards = Vec::new();
+
+    for card in due_cards {
+        match card.stage() {
+            CardStage::Learning | CardStage::Relearning => learning.push(card),
+            CardStage::Review => reviews.push(card),
+            CardStage::New => new_cards.push(card),
+        }
+    }
+
+    learning.sort_by_key(|card| *card.next_review());
+    reviews.sort_by_key(|card| *card.next_review());
+    new_cards.sort_by_key(|card| (*card.created_at(), *card.id()));
+
+    let mut cards = learning;
+    cards.extend(reviews.into_iter().take(reviews_remaining as usize));
+    cards.extend(new_cards.into_iter().take(new_remaining as usize));
+
+    StudyQueue {
+        deck_id: deck.id,
+        cards,
+        new_studied,
+        reviews_studied,
+        new_remaining,
+        reviews_remaining,
+    }
+}
+
+// Picks the next card of a session from today's queue. Learning cards that are due go first,
+// after that new cards are spread out between the reviews: whichever kind is furthest behind,
+// relative to how many of it the session will see in total, is up next.
+pub fn next_card(queue: &StudyQueue, session_reviews: &[CardReview], upcoming_learning: Option<DateTime<Utc>>) -> SessionCard {
+    let remaining = queue.cards.len() as u32;
+
+    if let Some(card) = queue.cards.iter().find(|card| card.stage().is_learning()) {
+        return SessionCard {
+            card: Some(card.clone()),
+            next_due_at: None,
+            remaining,
+        };
+    }
+
+    let count_stage = |stage: CardStage| -> (u64, u64) {
+        let done = session_reviews.iter().filter(|review| review.stage == stage).count() as u64;
+        let left = queue.cards.iter().filter(|card| *card.stage() == stage).count() as u64;
+        (done, left)
+    };
+
+    let (reviews_done, reviews_left) = count_stage(CardStage::Review);
+    let (new_done, new_left) = count_stage(CardStage::New);
+
+    // new_done / (new_done + new_left) < reviews_done / (reviews_done + reviews_left)
+    let new_is_behind = new_done * (reviews_done + reviews_left) < reviews_done * (new_done + new_left);
+
+    let first_of = |stage: CardStage| queue.cards.iter().find(|card| *card.stage() == stage);
+
+    let card = match (first_of(CardStage::Review), first_of(CardStage::New)) {
+        (Some(_), Some(new_card)) if new_is_behind => Some(new_card),
+        (Some(review_card), _) => Some(review_card),
+        (None, new_card) => new_card,
+    };
+
+    SessionCard {
+        card: card.cloned(),
+        next_due_at: card.map_or(upcoming_learning, |_| None),
+        remaining,
+    }
+}
+
+// Shuffles cards the same way for every call in a session, so a random cram session keeps
+// drilling the cards it started with.
+fn shuffle_key(session_id: u32, card_id: u32) -> u64 {
+    // splitmix64
+    let mut key = ((session_id as u64) << 32 | card_id as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    key = (key ^ (key >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    key = (key ^ (key >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    key ^ (key >> 31)
+}
+
+// The cards a cram session drills, out of the studyable cards of its deck, in the order they
+// are shown. Due dates don't matter, except to `Ahead`. Failures only count from scheduled
+// study, cramming doesn't feed itself.
+pub fn cram_cards(selection: &CramSelection, session_id: u32, cards: Vec<Card>, reviews_today: &[CardReview], now: DateTime<Utc>) -> Vec<Card> {
+    let failed_today: HashSet<u32> = reviews_today.iter()
+        .filter(|review| !review.cram && review.difficulty == CardDifficulty::Hard)
+        .map(|review| review.card_id)
+        .collect();
+
+    let mut cards: Vec<Card> = cards.into_iter()
+        .filter(|card| match selection {
+            CramSelection::Deck | CramSelection::Random { .. } => true,
+            CramSelection::Flag { flag } => card.flag() == flag,
+            CramSelection::Tag { tag } => card.has_tag(tag),
+            CramSelection::FailedToday => failed_today.contains(card.id()),
+            CramSelection::Ahead { days } => {
+                let until = TimeDelta::try_days(*days as i64)
+                    .and_then(|ahead| now.checked_add_signed(ahead))
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC);
+
+                *card.stage() == CardStage::Review && *card.next_review() <= until
+            },
+        })
+        .collect();
+
+    match selection {
+        CramSelection::Random { count } => {
+            cards.sort_by_key(|card| shuffle_key(session_id, *card.id()));
+            cards.truncate(*count as usize);
+        },
+        _ => cards.sort_by_key(|card| (*card.next_review(), *card.id())),
+    }
+
+    cards
+}
+
+// Picks the next card of a cram session. Every card is shown once, and cards answered `Hard`
+// come back after the rest until they are answered right.
+pub fn cram_next(cards: &[Card], session_reviews: &[CardReview]) -> SessionCard {
+    let mut last_answers: HashMap<u32, &CardReview> = HashMap::new();
+
+    for review in session_reviews {
+        last_answers.insert(review.card_id, review);
+    }
+
+    let unanswered = cards.iter()
+        .filter(|card| !last_answers.contains_key(card.id()));
+
+    let mut failed: Vec<(&CardReview, &Card)> = cards.iter()
+        .filter_map(|card| last_answers.get(card.id()).map(|review| (*review, card)))
+        .filter(|(review, _)| review.difficulty == CardDifficulty::Hard)
+        .collect();
+    failed.sort_by_key(|(review, _)| (review.reviewed_at, review.id));
+
+    let left: Vec<&Card> = unanswered
+        .chain(failed.into_iter().map(|(_, card)| card))
+        .collect();
+
+    SessionCard {
+        card: left.first().map(|card| (*card).clone()),
+        next_due_at: None,
+        remaining: left.len() as u32,
+    }
+}
+
+pub fn summarize(session: StudySession, session_reviews: &[CardReview], now: DateTime<Utc>) -> SessionSummary {
+    let answers = session_reviews.len() as u32;
+    let correct = session_reviews.iter()
+        .filter(|review| review.difficulty != CardDifficulty::Hard)
+        .count() as u32;
+    let cards_studied = session_reviews.iter()
+        .map(|review| review.card_id)
+        .collect::<HashSet<u32>>()
+        .len() as u32;
+
+    let accuracy = match answers {
+        0 => 0.0,
+        answers => correct as f64 / answers as f64,
+    };
+
+    let answer_times: Vec<u64> = session_reviews.iter()
+        .filter_map(|review| review.answer_time_ms)
+        .map(u64::from)
+        .collect();
+    let average_answer_time_ms = match answer_times.len() {
+        0 => None,
+        timed => Some((answer_times.iter().sum::<u64>() / timed as u64) as u32),
+    };
+
+    let ended_at = session.ended_at.unwrap_or(now);
+    let time_spent_seconds = (ended_at - session.started_at).num_seconds().max(0);
+
+    SessionSummary {
+        session,
+        cards_studied,
+        answers,
+        correct,
+        accuracy,
+        time_spent_seconds,
+        average_answer_time_ms,
+    }
+}
+
+// Answer times per card, in card id order. Cards without a timed answer are left out.
+pub fn answer_times(reviews: &[CardReview]) -> Vec<CardAnswerTimes> {
+    let mut per_card: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+
+    for review in reviews {
+        if let Some(answer_time_ms) = review.answer_time_ms {
+            per_card.entry(review.card_id).or_default().push(answer_time_ms);
+        }
+    }
+
+    per_card.into_iter()
+        .map(|(card_id, times)| CardAnswerTimes {
+            card_id,
+            answers: times.len() as u32,
+            average_ms: (times.iter().map(|time| u64::from(*time)).sum::<u64>() / times.len() as u64) as u32,
+            maximum_ms: times.iter().copied().max().unwrap_or_default(),
+        })
+        .collect()
+}
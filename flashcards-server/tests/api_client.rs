@@ -0,0 +1,143 @@
+use axum::{body::Body, http::Request, Router};
+use flashcards_api::{ApiClient, ApiError, ApiRequest, ApiResponse, CardFilters, ReqwestTransport, Transport};
+use flashcards_data::{CardAnswerTimes, CardDifficulty, CardFlag, CreateCardPayload, CreateSessionPayload, ReviewCardPayload, UpdateCardPayload, UpdateCardStatusPayload};
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+mod common;
+
+use common::*;
+
+// Hands the client's requests straight to the router.
+struct RouterTransport(Router);
+
+impl Transport for RouterTransport {
+    async fn send(&self, request: ApiRequest) -> Result<ApiResponse, ApiError> {
+        let mut builder = Request::builder()
+            .method(request.method)
+            .uri(request.url);
+
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+
+        let request = builder.body(request.body.map(Body::from).unwrap_or_else(Body::empty)).unwrap();
+        let response = self.0.clone().oneshot(request).await.unwrap();
+        let status = response.status().as_u16();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+
+        Ok(ApiResponse { status, body: String::from_utf8(bytes.to_vec()).unwrap() })
+    }
+}
+
+async fn client(url: &str) -> ApiClient<RouterTransport> {
+    ApiClient::new("", RouterTransport(app(url).await))
+}
+
+fn payload(front: &str) -> CreateCardPayload {
+    CreateCardPayload { front: front.to_string(), back: "back".to_string(), ..Default::default() }
+}
+
+#[tokio::test]
+async fn client_manages_cards() {
+    for url in &backends() {
+        let client = client(url).await;
+
+        assert_eq!(client.get_health().await.unwrap(), "200 OK", "{url}");
+
+        let card = client.add_card(&payload("hola")).await.unwrap();
+
+        let Err(ApiError::Duplicate(duplicates)) = client.add_card(&payload("hola")).await else {
+            panic!("{url}: the duplicate was saved");
+        };
+        assert_eq!(duplicates, vec![card.clone()], "{url}");
+
+        let edit = UpdateCardPayload { back: Some("hello".to_string()), ..Default::default() };
+        let edited = client.patch_card(*card.id(), &edit, "ana").await.unwrap();
+        assert_eq!(edited.back(), "hello", "{url}");
+
+        let revisions = client.get_card_revisions(*card.id()).await.unwrap();
+        assert_eq!(revisions.len(), 1, "{url}");
+        assert_eq!(revisions[0].editor.as_deref(), Some("ana"), "{url}");
+
+        let flag = UpdateCardStatusPayload { flag: Some(CardFlag::Red), ..Default::default() };
+        client.patch_card_status(*card.id(), &flag).await.unwrap();
+        client.add_card(&payload("adiós")).await.unwrap();
+
+        let red = CardFilters { flag: Some(CardFlag::Red), ..Default::default() };
+        assert_eq!(client.get_cards(&red).await.unwrap().len(), 1, "{url}");
+        assert_eq!(client.get_cards(&CardFilters::default()).await.unwrap().len(), 2, "{url}");
+
+        assert!(client.remove_card(*card.id()).await.unwrap(), "{url}");
+        assert_eq!(client.get_trash().await.unwrap().len(), 1, "{url}");
+
+        let restored = client.restore_card(*card.id()).await.unwrap();
+        assert_eq!(restored.id(), card.id(), "{url}");
+        assert!(client.get_trash().await.unwrap().is_empty(), "{url}");
+    }
+}
+
+#[tokio::test]
+async fn client_runs_a_study_session() {
+    for url in &backends() {
+        let client = client(url).await;
+
+        let card = client.add_card(&payload("hola")).await.unwrap();
+
+        let session = client.start_session(&CreateSessionPayload::default()).await.unwrap();
+        let next = client.get_session_next(session.id).await.unwrap();
+        assert_eq!(next.card.as_ref().map(|card| *card.id()), Some(*card.id()), "{url}");
+
+        let answer = ReviewCardPayload { difficulty: CardDifficulty::Easy, session_id: Some(session.id), answer_time_ms: Some(2500) };
+        let reviewed = client.review_card(*card.id(), &answer).await.unwrap();
+
+        let times = client.get_answer_times(None).await.unwrap();
+        assert_eq!(times, vec![CardAnswerTimes { card_id: *card.id(), answers: 1, average_ms: 2500, maximum_ms: 2500 }], "{url}");
+
+        let summary = client.finish_session(session.id).await.unwrap();
+        assert_eq!(summary.answers, 1, "{url}");
+
+        let undone = client.undo_review(reviewed.review_id).await.unwrap();
+        assert_eq!(undone, card, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn client_reports_server_errors() {
+    for url in &backends() {
+        let client = client(url).await;
+
+        let error = client.reset_card(42).await.unwrap_err();
+
+        assert_eq!(error.status(), Some(404), "{url}");
+        assert!(matches!(error, ApiError::Status { ref message, .. } if !message.is_empty()), "{url}");
+    }
+}
+
+// The native transport, over a real socket.
+#[tokio::test]
+async fn reqwest_transport_talks_to_a_listening_server() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let app = app("memory:").await;
+
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let client = ApiClient::new(format!("http://{}/", address), ReqwestTransport::default());
+
+    assert_eq!(client.get_health().await.unwrap(), "200 OK");
+
+    let card = client.add_card(&payload("hola")).await.unwrap();
+    assert_eq!(client.get_cards(&CardFilters::default()).await.unwrap(), vec![card.clone()]);
+
+    let Err(ApiError::Duplicate(duplicates)) = client.add_card(&payload("hola")).await else {
+        panic!("the duplicate was saved");
+    };
+    assert_eq!(duplicates, vec![card]);
+
+    assert_eq!(client.reset_card(42).await.unwrap_err().status(), Some(404));
+
+    // Nothing listens on port 1.
+    let unreachable = ApiClient::new("http://127.0.0.1:1", ReqwestTransport::default());
+    assert!(matches!(unreachable.get_health().await, Err(ApiError::Transport(_))));
+}
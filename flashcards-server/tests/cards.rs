@@ -0,0 +1,437 @@
+use axum::http::{Method, StatusCode};
+use flashcards_data::{BatchResult, Card, CardFlag, CardRevision, CardStage, ReviewedCard, SchedulerSettings};
+use flashcards_server::database::GetReviewFilters;
+use serde_json::json;
+
+mod common;
+
+use common::*;
+
+#[tokio::test]
+async fn created_cards_match_the_stored_rows() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let first = create(&app, "one", "1").await;
+        let second = create(&app, "two", "2").await;
+
+        assert_ne!(first.id(), second.id(), "{url}");
+        assert_eq!(list(&app).await, vec![first, second], "{url}");
+    }
+}
+
+#[tokio::test]
+async fn ids_are_not_reused_after_a_delete() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let first = create(&app, "one", "1").await;
+        let second = create(&app, "two", "2").await;
+
+        let (status, _) = send(&app, Method::DELETE, &format!("/cards/{}", first.id()), None).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let third = create(&app, "three", "3").await;
+
+        assert_ne!(third.id(), first.id(), "{url}");
+        assert_ne!(third.id(), second.id(), "{url}");
+        assert_eq!(list(&app).await, vec![second, third], "{url}");
+    }
+}
+
+#[tokio::test]
+async fn update_changes_only_the_addressed_card() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let deleted = create(&app, "deleted", "-").await;
+        let kept = create(&app, "kept", "k").await;
+        let edited = create(&app, "typo", "t").await;
+
+        send(&app, Method::DELETE, &format!("/cards/{}", deleted.id()), None).await;
+
+        let uri = format!("/cards/{}", edited.id());
+        let (status, body) = send(&app, Method::PUT, &uri, Some(json!({ "front": "fixed", "back": "t" }))).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let updated: Card = serde_json::from_value(body).unwrap();
+        assert_eq!(updated.id(), edited.id(), "{url}");
+        assert_eq!(updated.front(), "fixed", "{url}");
+
+        let cards = list(&app).await;
+        assert_eq!(cards.len(), 2, "{url}");
+        assert_eq!(cards[0], kept, "{url}");
+        assert_eq!(cards[1].front(), "fixed", "{url}");
+    }
+}
+
+#[tokio::test]
+async fn missing_cards_are_reported_as_not_found() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let card = create(&app, "one", "1").await;
+        let uri = format!("/cards/{}", card.id());
+
+        let (status, _) = send(&app, Method::DELETE, &uri, None).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let (status, _) = send(&app, Method::DELETE, &uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{url}");
+
+        let (status, _) = send(&app, Method::PUT, &uri, Some(json!({ "front": "a", "back": "b" }))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{url}");
+
+        let review = json!({ "difficulty": "Easy" });
+        let (status, _) = send(&app, Method::POST, &format!("{uri}/review"), Some(review)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn editing_content_keeps_the_scheduling_state() {
+    for url in &backends() {
+        let (app, store) = app_with_store(url).await;
+
+        let card = create(&app, "teh", "the").await;
+        let reviewed = review(&app, &card, "Easy").await;
+
+        let uri = format!("/cards/{}", card.id());
+        let (status, body) = send(&app, Method::PATCH, &uri, Some(json!({ "front": "the" }))).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let patched: Card = serde_json::from_value(body).unwrap();
+        assert_eq!(patched.front(), "the", "{url}");
+        assert_eq!(patched.back(), "the", "{url}");
+        assert_eq!(patched.ease_factor(), reviewed.ease_factor(), "{url}");
+        assert_eq!(patched.interval(), reviewed.interval(), "{url}");
+        assert_eq!(patched.next_review(), reviewed.next_review(), "{url}");
+
+        let (status, body) = send(&app, Method::PUT, &uri, Some(json!({ "front": "the", "back": "THE" }))).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let replaced: Card = serde_json::from_value(body).unwrap();
+        assert_eq!(replaced.ease_factor(), reviewed.ease_factor(), "{url}");
+        assert_eq!(replaced.next_review(), reviewed.next_review(), "{url}");
+
+        let revisions = store.get_card_revisions(*card.id()).await.unwrap();
+        let fronts: Vec<(&str, &str)> = revisions.iter()
+            .map(|revision| (revision.front.as_str(), revision.back.as_str()))
+            .collect();
+        assert_eq!(fronts, vec![("teh", "the"), ("the", "the")], "{url}");
+    }
+}
+
+#[tokio::test]
+async fn revisions_record_the_editor_and_can_be_reverted_to() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let card = create(&app, "teh", "the").await;
+        let uri = format!("/cards/{}", card.id());
+
+        send_as(&app, "ada", Method::PATCH, &uri, Some(json!({ "front": "the" }))).await;
+        send_as(&app, "bob", Method::PATCH, &uri, Some(json!({ "back": "wrong" }))).await;
+
+        let (status, body) = send(&app, Method::GET, &format!("{uri}/revisions"), None).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+        let revisions: Vec<CardRevision> = serde_json::from_value(body).unwrap();
+        let history: Vec<(&str, &str, Option<&str>)> = revisions.iter()
+            .map(|revision| (revision.front.as_str(), revision.back.as_str(), revision.editor.as_deref()))
+            .collect();
+        assert_eq!(history, vec![("teh", "the", Some("ada")), ("the", "the", Some("bob"))], "{url}");
+
+        // Reverting is an edit too, so the bad content is kept as a revision.
+        let revert = format!("{uri}/revisions/{}/revert", revisions[1].id);
+        let (status, body) = send(&app, Method::POST, &revert, None).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+        let reverted: Card = serde_json::from_value(body).unwrap();
+        assert_eq!((reverted.front(), reverted.back()), ("the", "the"), "{url}");
+
+        let (_, body) = send(&app, Method::GET, &format!("{uri}/revisions"), None).await;
+        let revisions: Vec<CardRevision> = serde_json::from_value(body).unwrap();
+        assert_eq!(revisions.len(), 3, "{url}");
+        assert_eq!((revisions[2].back.as_str(), revisions[2].editor.as_deref()), ("wrong", None), "{url}");
+
+        // Revisions belong to their card.
+        let other = create(&app, "other", "o").await;
+        let (status, _) = send(&app, Method::POST, &format!("/cards/{}/revisions/{}/revert", other.id(), revisions[0].id), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{url}");
+
+        let (status, _) = send(&app, Method::GET, "/cards/999/revisions", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn reset_starts_a_card_over() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let card = create(&app, "one", "1").await;
+        review(&app, &card, "Easy").await;
+
+        let uri = format!("/cards/{}/reset", card.id());
+        let (status, body) = send(&app, Method::POST, &uri, None).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let reset: Card = serde_json::from_value(body).unwrap();
+        assert_eq!(reset.front(), "one", "{url}");
+        assert_eq!(*reset.ease_factor(), 3.0, "{url}");
+        assert_eq!(*reset.interval(), 1, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn due_lists_only_cards_past_their_review_date() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let due = create(&app, "due", "1").await;
+        let later = create(&app, "later", "2").await;
+        review(&app, &later, "Easy").await;
+
+        let (status, body) = send(&app, Method::GET, "/cards/due", None).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let cards: Vec<Card> = serde_json::from_value(body).unwrap();
+        assert_eq!(cards, vec![due], "{url}");
+    }
+}
+
+#[tokio::test]
+async fn intervals_grow_past_a_byte_and_stop_at_the_maximum() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let mut card = create(&app, "one", "1").await;
+        let mut intervals = Vec::new();
+        for _ in 0..12 {
+            card = review(&app, &card, "Easy").await;
+            intervals.push(*card.interval());
+        }
+
+        assert!(intervals.iter().any(|interval| *interval > 255), "{url}: {intervals:?}");
+        assert_eq!(*card.interval(), SchedulerSettings::default().maximum_interval, "{url}");
+        assert_eq!(*card.ease_factor(), 5.0, "{url}");
+
+        // What went over the wire is what was stored.
+        let stored = list(&app).await;
+        assert_eq!(stored, vec![card], "{url}");
+    }
+}
+
+#[tokio::test]
+async fn learning_cards_come_back_within_minutes() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let card = create(&app, "one", "1").await;
+        assert_eq!(*card.stage(), CardStage::New, "{url}");
+
+        let learning = review(&app, &card, "Hard").await;
+        assert_eq!(*learning.stage(), CardStage::Learning, "{url}");
+        assert!(*learning.next_review() < chrono::Utc::now() + chrono::TimeDelta::minutes(2), "{url}");
+
+        let stored = list(&app).await;
+        assert_eq!(stored, vec![learning], "{url}");
+    }
+}
+
+#[tokio::test]
+async fn undo_puts_back_the_state_before_the_latest_review() {
+    for url in &backends() {
+        let (app, store) = app_with_store(url).await;
+
+        let card = create(&app, "one", "1").await;
+
+        let uri = format!("/cards/{}/review", card.id());
+        let (_, body) = send(&app, Method::POST, &uri, Some(json!({ "difficulty": "Hard" }))).await;
+        let first: ReviewedCard = serde_json::from_value(body).unwrap();
+        let (_, body) = send(&app, Method::POST, &uri, Some(json!({ "difficulty": "Easy" }))).await;
+        let second: ReviewedCard = serde_json::from_value(body).unwrap();
+
+        // Only the latest answer can be taken back.
+        let (status, _) = send(&app, Method::POST, &format!("/reviews/{}/undo", first.review_id), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{url}");
+
+        let (status, body) = send(&app, Method::POST, &format!("/reviews/{}/undo", second.review_id), None).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+        let restored: Card = serde_json::from_value(body).unwrap();
+        assert_eq!(restored, first.card, "{url}");
+
+        let (status, body) = send(&app, Method::POST, &format!("/reviews/{}/undo", first.review_id), None).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+        let restored: Card = serde_json::from_value(body).unwrap();
+        assert_eq!(restored, card, "{url}");
+        assert_eq!(list(&app).await, vec![card], "{url}");
+
+        assert!(store.get_reviews(Default::default()).await.unwrap().is_empty(), "{url}");
+
+        let (status, _) = send(&app, Method::POST, &format!("/reviews/{}/undo", first.review_id), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn leeches_are_listed_and_undo_takes_a_lapse_back() {
+    for url in &backends() {
+        let (app, store) = app_with_store(url).await;
+
+        let mut card = create(&app, "leech", "1").await;
+        create(&app, "fine", "2").await;
+
+        // A graduated card, forgotten as often as the default threshold allows.
+        card.set_stage(CardStage::Review);
+        card.set_lapses(SchedulerSettings::default().leech_threshold - 1);
+        store.update_card(&card).await.unwrap();
+
+        let uri = format!("/cards/{}/review", card.id());
+        let (_, body) = send(&app, Method::POST, &uri, Some(json!({ "difficulty": "Hard" }))).await;
+        let lapsed: ReviewedCard = serde_json::from_value(body).unwrap();
+        assert_eq!(*lapsed.card.lapses(), SchedulerSettings::default().leech_threshold, "{url}");
+
+        let (_, body) = send(&app, Method::GET, "/cards?leech=true", None).await;
+        let leeches: Vec<Card> = serde_json::from_value(body).unwrap();
+        assert_eq!(leeches, vec![lapsed.card.clone()], "{url}");
+
+        send(&app, Method::POST, &format!("/reviews/{}/undo", lapsed.review_id), None).await;
+
+        let (_, body) = send(&app, Method::GET, "/cards?leech=true", None).await;
+        let leeches: Vec<Card> = serde_json::from_value(body).unwrap();
+        assert!(leeches.is_empty(), "{url}");
+    }
+}
+
+#[tokio::test]
+async fn deleted_cards_wait_in_the_trash_until_purged() {
+    for url in &backends() {
+        let (app, store) = app_with_store(url).await;
+
+        let card = create(&app, "one", "1").await;
+        let kept = create(&app, "two", "2").await;
+        review(&app, &card, "Easy").await;
+
+        send(&app, Method::DELETE, &format!("/cards/{}", card.id()), None).await;
+        assert_eq!(list(&app).await, vec![kept.clone()], "{url}");
+
+        let (status, body) = send(&app, Method::GET, "/trash", None).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+        let trash: Vec<Card> = serde_json::from_value(body).unwrap();
+        assert_eq!(trash.len(), 1, "{url}");
+        assert_eq!(trash[0].id(), card.id(), "{url}");
+        assert!(trash[0].deleted_at().is_some(), "{url}");
+
+        // Only cards in the trash can be restored, and they come back with their history.
+        let (status, _) = send(&app, Method::POST, &format!("/trash/{}/restore", kept.id()), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{url}");
+
+        let (status, body) = send(&app, Method::POST, &format!("/trash/{}/restore", card.id()), None).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+        let restored: Card = serde_json::from_value(body).unwrap();
+        assert_eq!(restored.deleted_at(), &None, "{url}");
+        assert_eq!(list(&app).await, vec![restored, kept.clone()], "{url}");
+
+        let history = GetReviewFilters::default().add_card(*card.id());
+        assert_eq!(store.get_reviews(history.clone()).await.unwrap().len(), 1, "{url}");
+
+        // The purge only takes cards deleted before the cutoff.
+        send(&app, Method::DELETE, &format!("/cards/{}", card.id()), None).await;
+        assert_eq!(store.purge_trash(chrono::Utc::now() - chrono::TimeDelta::days(1)).await.unwrap(), 0, "{url}");
+        assert_eq!(store.purge_trash(chrono::Utc::now() + chrono::TimeDelta::seconds(1)).await.unwrap(), 1, "{url}");
+
+        let (_, body) = send(&app, Method::GET, "/trash", None).await;
+        assert_eq!(body, json!([]), "{url}");
+        assert!(store.get_reviews(history).await.unwrap().is_empty(), "{url}");
+
+        let (status, _) = send(&app, Method::POST, &format!("/trash/{}/restore", card.id()), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn batches_report_on_every_operation() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let first = create(&app, "one", "1").await;
+        let second = create(&app, "two", "2").await;
+
+        let (_, body) = send(&app, Method::POST, "/decks", Some(json!({ "name": "Verbs" }))).await;
+        let deck_id = body["id"].as_u64().unwrap();
+
+        let operations = json!({ "operations": [
+            { "op": "create", "front": "three", "back": "3", "deck_id": deck_id },
+            { "op": "update", "id": first.id(), "back": "uno" },
+            { "op": "move", "id": first.id(), "deck_id": deck_id },
+            { "op": "flag", "id": first.id(), "flag": "Red" },
+            { "op": "suspend", "id": second.id(), "suspended": true },
+            { "op": "move", "id": second.id(), "deck_id": 999 },
+            { "op": "delete", "id": 999 },
+            { "op": "delete", "id": second.id() },
+        ] });
+        let (status, body) = send_as(&app, "ada", Method::POST, "/cards/batch", Some(operations)).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let results: Vec<BatchResult> = serde_json::from_value(body).unwrap();
+        let statuses: Vec<bool> = results.iter()
+            .map(|result| matches!(result, BatchResult::Done { .. }))
+            .collect();
+        assert_eq!(statuses, vec![true, true, true, true, true, false, false, true], "{url}");
+
+        // Operations on the same card build on each other.
+        let BatchResult::Done { card } = &results[3] else { unreachable!() };
+        assert_eq!((card.back(), *card.deck_id(), *card.flag()), ("uno", deck_id as u32, CardFlag::Red), "{url}");
+
+        let cards = list(&app).await;
+        let fronts: Vec<&str> = cards.iter().map(|card| card.front()).collect();
+        assert_eq!(fronts, vec!["one", "three"], "{url}");
+        assert_eq!(*cards[1].deck_id(), deck_id as u32, "{url}");
+
+        let (_, body) = send(&app, Method::GET, "/trash", None).await;
+        let trash: Vec<Card> = serde_json::from_value(body).unwrap();
+        assert_eq!(trash.len(), 1, "{url}");
+        assert!(*trash[0].suspended(), "{url}");
+
+        let (_, body) = send(&app, Method::GET, &format!("/cards/{}/revisions", first.id()), None).await;
+        let revisions: Vec<CardRevision> = serde_json::from_value(body).unwrap();
+        assert_eq!(revisions[0].editor.as_deref(), Some("ada"), "{url}");
+    }
+}
+
+#[tokio::test]
+async fn batches_add_and_remove_tags() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let first = create(&app, "one", "1").await;
+        let second = create(&app, "two", "2").await;
+        assert!(first.tags().is_empty(), "{url}");
+
+        let operations = json!({ "operations": [
+            { "op": "tag", "id": first.id(), "add": ["Irregular  Verbs", "food", "food"] },
+            { "op": "tag", "id": second.id(), "add": ["food"] },
+            { "op": "tag", "id": first.id(), "remove": ["food"] },
+            { "op": "tag", "id": second.id(), "add": [" "] },
+            { "op": "tag", "id": 999, "add": ["food"] },
+        ] });
+        let (status, body) = send(&app, Method::POST, "/cards/batch", Some(operations)).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let results: Vec<BatchResult> = serde_json::from_value(body).unwrap();
+        let statuses: Vec<bool> = results.iter()
+            .map(|result| matches!(result, BatchResult::Done { .. }))
+            .collect();
+        assert_eq!(statuses, vec![true, true, true, false, false], "{url}");
+
+        let cards = list(&app).await;
+        assert_eq!(cards[0].tags(), ["irregular-verbs"], "{url}");
+        assert_eq!(cards[1].tags(), ["food"], "{url}");
+
+        // A trashed card keeps its tags, for when it is restored.
+        send(&app, Method::DELETE, &format!("/cards/{}", second.id()), None).await;
+        let (_, body) = send(&app, Method::GET, "/trash", None).await;
+        let trash: Vec<Card> = serde_json::from_value(body).unwrap();
+        assert_eq!(trash[0].tags(), ["food"], "{url}");
+    }
+}
@@ -0,0 +1,121 @@
+// Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    Router,
+};
+use flashcards_data::{Card, SchedulerSettings, EDITOR_HEADER};
+use flashcards_server::{config::StudyConfig, database::{self, CardStore}, AppState};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use sqlx::migrate::MigrateDatabase;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+// Every test runs against each backend; `sqlite::memory:` exercises the real SQL while
+// keeping the database private to the test.
+const LOCAL_BACKENDS: [&str; 2] = ["memory:", "sqlite::memory:"];
+
+// A Postgres server to run the tests against as well, e.g.
+// `postgres://postgres@localhost/flashcards_test`. Each test gets a database of its own there.
+pub const POSTGRES_URL_VAR: &str = "FLASHCARDS_TEST_POSTGRES_URL";
+
+pub fn backends() -> Vec<String> {
+    let mut backends: Vec<String> = LOCAL_BACKENDS.iter().map(|url| url.to_string()).collect();
+
+    if let Ok(url) = std::env::var(POSTGRES_URL_VAR) {
+        backends.push(url);
+    }
+
+    backends
+}
+
+// Tests run in parallel, so on Postgres each gets a fresh database named after it. Whatever an
+// earlier run left there is dropped first.
+pub async fn private_database(url: &str) -> String {
+    if !url.starts_with("postgres") {
+        return url.to_string();
+    }
+
+    let test = std::thread::current().name().unwrap_or("test").replace("::", "_");
+    // Longer names would be cut short by Postgres.
+    let name: String = format!("{}_{}", env!("CARGO_CRATE_NAME"), test).chars().take(63).collect();
+    let base = url.rsplit_once('/').map_or(url, |(base, _)| base);
+    let private = format!("{}/{}", base, name);
+
+    let _ = sqlx::Postgres::force_drop_database(&private).await;
+
+    private
+}
+
+pub async fn app_with_store(url: &str) -> (Router, Arc<dyn CardStore>) {
+    let url = private_database(url).await;
+
+    // A single connection, otherwise every pooled SQLite connection gets its own database.
+    let database = database::connect(&url, 1).await.unwrap();
+    database.run_migrations().await.unwrap();
+
+    let router = flashcards_server::router(Arc::new(AppState {
+        database: database.clone(),
+        scheduler: SchedulerSettings::default(),
+        study: StudyConfig::default(),
+    }));
+
+    (router, database)
+}
+
+pub async fn app(url: &str) -> Router {
+    app_with_store(url).await.0
+}
+
+pub async fn send(app: &Router, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    send_request(app, Request::builder(), method, uri, body).await
+}
+
+// Like `send`, naming `editor` as the one making the request.
+pub async fn send_as(app: &Router, editor: &str, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    send_request(app, Request::builder().header(EDITOR_HEADER, editor), method, uri, body).await
+}
+
+async fn send_request(app: &Router, request: axum::http::request::Builder, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    let request = request
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+
+    let request = match body {
+        Some(body) => request.body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }.unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let value = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+
+    (status, value)
+}
+
+pub async fn create(app: &Router, front: &str, back: &str) -> Card {
+    let (status, body) = send(app, Method::POST, "/cards", Some(json!({ "front": front, "back": back }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    serde_json::from_value(body).unwrap()
+}
+
+pub async fn review(app: &Router, card: &Card, difficulty: &str) -> Card {
+    let uri = format!("/cards/{}/review", card.id());
+    let (status, body) = send(app, Method::POST, &uri, Some(json!({ "difficulty": difficulty }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    serde_json::from_value(body).unwrap()
+}
+
+pub async fn list(app: &Router) -> Vec<Card> {
+    let (status, body) = send(app, Method::GET, "/cards", None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    serde_json::from_value(body).unwrap()
+}
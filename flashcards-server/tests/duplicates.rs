@@ -0,0 +1,135 @@
+use axum::http::{Method, StatusCode};
+use chrono::{TimeDelta, Utc};
+use flashcards_data::{BatchResult, Card, DuplicateGroup, MergedCards};
+use flashcards_server::{database::GetReviewFilters, duplicates::normalize};
+use serde_json::json;
+
+mod common;
+
+use common::*;
+
+#[test]
+fn fronts_are_compared_without_case_spacing_punctuation_or_unicode_forms() {
+    assert_eq!(normalize("  Hello,   World! "), "hello world");
+    assert_eq!(normalize("ＨＥＬＬＯ"), "hello");
+    assert_eq!(normalize("ﬁsh"), "fish");
+    assert_eq!(normalize("cafe\u{301}"), normalize("café"));
+    assert_ne!(normalize("café"), normalize("cafe"));
+    assert_eq!(normalize("?!"), "");
+}
+
+#[tokio::test]
+async fn creating_a_duplicate_is_refused_unless_allowed() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let original = create(&app, "Der Hund", "the dog").await;
+
+        let (status, body) = send(&app, Method::POST, "/cards", Some(json!({ "front": "der  hund!", "back": "dog" }))).await;
+        assert_eq!(status, StatusCode::CONFLICT, "{url}");
+        let duplicates: Vec<Card> = serde_json::from_value(body["duplicates"].clone()).unwrap();
+        assert_eq!(duplicates, vec![original.clone()], "{url}");
+
+        // Other decks have cards of their own.
+        let (_, body) = send(&app, Method::POST, "/decks", Some(json!({ "name": "Other" }))).await;
+        let (status, _) = send(&app, Method::POST, "/cards", Some(json!({ "front": "Der Hund", "back": "dog", "deck_id": body["id"] }))).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let (status, _) = send(&app, Method::POST, "/cards", Some(json!({ "front": "der hund", "back": "dog", "allow_duplicate": true }))).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        // A batch checks its own new cards too.
+        let operations = json!({ "operations": [
+            { "op": "create", "front": "die Katze", "back": "the cat" },
+            { "op": "create", "front": "Die Katze.", "back": "cat" },
+            { "op": "create", "front": "DER HUND", "back": "dog" },
+        ] });
+        let (_, body) = send(&app, Method::POST, "/cards/batch", Some(operations)).await;
+        let results: Vec<BatchResult> = serde_json::from_value(body).unwrap();
+        let created: Vec<bool> = results.iter()
+            .map(|result| matches!(result, BatchResult::Done { .. }))
+            .collect();
+        assert_eq!(created, vec![true, false, false], "{url}");
+    }
+}
+
+#[tokio::test]
+async fn merging_keeps_the_card_with_the_most_history() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let fresh = create(&app, "one", "1").await;
+        let (_, body) = send(&app, Method::POST, "/cards", Some(json!({ "front": "One.", "back": "1", "allow_duplicate": true }))).await;
+        let studied: Card = serde_json::from_value(body).unwrap();
+        create(&app, "two", "2").await;
+        let studied = review(&app, &studied, "Easy").await;
+
+        let (status, body) = send(&app, Method::GET, "/cards/duplicates", None).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+        let groups: Vec<DuplicateGroup> = serde_json::from_value(body).unwrap();
+        assert_eq!(groups.len(), 1, "{url}");
+        assert_eq!(groups[0].key, "one", "{url}");
+        assert_eq!(groups[0].cards, vec![fresh.clone(), studied.clone()], "{url}");
+
+        let (status, body) = send(&app, Method::POST, "/cards/merge", Some(json!({ "card_ids": [fresh.id(), studied.id()] }))).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+        let merged: MergedCards = serde_json::from_value(body).unwrap();
+        assert_eq!(merged.kept, studied, "{url}");
+        assert_eq!(merged.trashed, vec![*fresh.id()], "{url}");
+
+        let (_, body) = send(&app, Method::GET, "/cards/duplicates", None).await;
+        assert_eq!(body, json!([]), "{url}");
+
+        let (status, _) = send(&app, Method::POST, "/cards/merge", Some(json!({ "card_ids": [studied.id()] }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn merging_refuses_cards_that_are_not_duplicates() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let one = create(&app, "one", "1").await;
+        let two = create(&app, "two", "2").await;
+
+        let (_, body) = send(&app, Method::POST, "/decks", Some(json!({ "name": "Other" }))).await;
+        let (_, body) = send(&app, Method::POST, "/cards", Some(json!({ "front": "one", "back": "1", "deck_id": body["id"] }))).await;
+        let elsewhere: Card = serde_json::from_value(body).unwrap();
+
+        for card_ids in [[one.id(), two.id()], [one.id(), elsewhere.id()]] {
+            let (status, _) = send(&app, Method::POST, "/cards/merge", Some(json!({ "card_ids": card_ids }))).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{url}");
+        }
+
+        assert_eq!(list(&app).await.len(), 3, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn merged_away_cards_keep_their_reviews_until_the_trash_is_purged() {
+    for url in &backends() {
+        let (app, store) = app_with_store(url).await;
+
+        let kept = create(&app, "one", "1").await;
+        let (_, body) = send(&app, Method::POST, "/cards", Some(json!({ "front": "One", "back": "1", "allow_duplicate": true }))).await;
+        let trashed: Card = serde_json::from_value(body).unwrap();
+
+        let kept = review(&app, &kept, "Easy").await;
+        review(&app, &kept, "Easy").await;
+        review(&app, &trashed, "Hard").await;
+
+        let (status, body) = send(&app, Method::POST, "/cards/merge", Some(json!({ "card_ids": [kept.id(), trashed.id()] }))).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+        let merged: MergedCards = serde_json::from_value(body).unwrap();
+        assert_eq!(merged.trashed, vec![*trashed.id()], "{url}");
+
+        let reviews_of = |card: &Card| GetReviewFilters::default().add_card(*card.id());
+        assert_eq!(store.get_reviews(reviews_of(&kept)).await.unwrap().len(), 2, "{url}");
+        assert_eq!(store.get_reviews(reviews_of(&trashed)).await.unwrap().len(), 1, "{url}");
+
+        store.purge_trash(Utc::now() + TimeDelta::minutes(1)).await.unwrap();
+        assert_eq!(store.get_reviews(reviews_of(&kept)).await.unwrap().len(), 2, "{url}");
+        assert!(store.get_reviews(reviews_of(&trashed)).await.unwrap().is_empty(), "{url}");
+    }
+}
@@ -0,0 +1,102 @@
+use chrono::{TimeZone, Utc};
+use flashcards_server::database::{self, GetCardFilters, StoreError};
+use sqlx::sqlite::SqlitePoolOptions;
+use std::path::PathBuf;
+
+mod common;
+
+use common::{private_database, POSTGRES_URL_VAR};
+
+// Migrations are tested against a real file so a second pool can poke at the raw tables.
+struct TempDatabase {
+    path: PathBuf,
+}
+
+impl TempDatabase {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("flashcards-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        Self { path }
+    }
+
+    fn url(&self) -> String {
+        format!("sqlite://{}", self.path.display())
+    }
+}
+
+impl Drop for TempDatabase {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[tokio::test]
+async fn legacy_timestamps_are_normalised() {
+    let temp = TempDatabase::new("legacy-timestamps");
+    let store = database::connect(&temp.url(), 1).await.unwrap();
+
+    // Roll back to the schema that still stored next_review as free-form text.
+    store.run_migrations().await.unwrap();
+    store.revert_migrations(Some(20260301120000)).await.unwrap();
+
+    let pool = SqlitePoolOptions::new().connect(&temp.url()).await.unwrap();
+    for next_review in ["2026-02-16 18:57:36", "2026-02-16 18:57:36.123456789 UTC", "garbage"] {
+        sqlx::query("INSERT INTO flashcards (front_of_card, back_of_card, next_review) VALUES ('f', 'b', ?)")
+            .bind(next_review)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    pool.close().await;
+
+    store.run_migrations().await.unwrap();
+
+    let cards = store.get_cards(GetCardFilters::default()).await.unwrap();
+    let expected = Utc.with_ymd_and_hms(2026, 2, 16, 18, 57, 36).unwrap();
+
+    assert_eq!(*cards[0].next_review(), expected);
+    assert_eq!(cards[1].next_review().timestamp(), expected.timestamp());
+    // Unparseable values become due right away instead of never.
+    assert!(*cards[2].next_review() > expected);
+    assert!(cards.iter().all(|card| card.created_at() == card.updated_at()));
+}
+
+#[tokio::test]
+async fn refuses_a_database_from_a_newer_server() {
+    let temp = TempDatabase::new("schema-ahead");
+    let store = database::connect(&temp.url(), 1).await.unwrap();
+    store.run_migrations().await.unwrap();
+
+    let pool = SqlitePoolOptions::new().connect(&temp.url()).await.unwrap();
+    sqlx::query("INSERT INTO _sqlx_migrations VALUES (29990101000000, 'future', CURRENT_TIMESTAMP, 1, X'00', 0)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool.close().await;
+
+    let result = store.run_migrations().await;
+
+    assert!(matches!(result, Err(StoreError::SchemaAhead { database: 29990101000000, .. })));
+}
+
+// Every migration goes down and back up again cleanly. Only run with a Postgres server, SQLite
+// is covered by the tests above.
+#[tokio::test]
+async fn postgres_migrations_revert_and_rerun() {
+    let Ok(url) = std::env::var(POSTGRES_URL_VAR) else {
+        return;
+    };
+
+    let store = database::connect(&private_database(&url).await, 1).await.unwrap();
+
+    store.run_migrations().await.unwrap();
+    assert!(store.migration_status().await.unwrap().iter().all(|status| status.applied));
+
+    store.revert_migrations(Some(0)).await.unwrap();
+    assert!(store.migration_status().await.unwrap().iter().all(|status| !status.applied));
+
+    store.run_migrations().await.unwrap();
+    assert!(store.migration_status().await.unwrap().iter().all(|status| status.applied));
+    assert!(store.get_cards(GetCardFilters::default()).await.unwrap().is_empty());
+}
@@ -0,0 +1,335 @@
+use axum::{http::{Method, StatusCode}, Router};
+use chrono::{TimeDelta, Utc};
+use flashcards_data::{Card, CardStage, CramSelection, SessionCard, SessionKind, SessionSummary, StudySession};
+use flashcards_server::study;
+use serde_json::json;
+
+mod common;
+
+use common::*;
+
+async fn start(app: &Router) -> StudySession {
+    let (status, body) = send(app, Method::POST, "/sessions", Some(json!({}))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    serde_json::from_value(body).unwrap()
+}
+
+async fn next(app: &Router, session: &StudySession) -> SessionCard {
+    let (status, body) = send(app, Method::GET, &format!("/sessions/{}/next", session.id), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    serde_json::from_value(body).unwrap()
+}
+
+async fn answer(app: &Router, session: &StudySession, card: &Card, difficulty: &str) -> Card {
+    let uri = format!("/cards/{}/review", card.id());
+    let body = json!({ "difficulty": difficulty, "session_id": session.id });
+    let (status, body) = send(app, Method::POST, &uri, Some(body)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    serde_json::from_value(body).unwrap()
+}
+
+#[tokio::test]
+async fn sessions_hand_out_every_due_card_once_answered() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        for front in ["one", "two", "three"] {
+            create(&app, front, "back").await;
+        }
+
+        let session = start(&app).await;
+        let mut seen = Vec::new();
+
+        loop {
+            let next = next(&app, &session).await;
+            let Some(card) = next.card else {
+                // Hard answers put a card back into learning, it returns within minutes.
+                assert!(next.next_due_at.is_some(), "{url}");
+                break;
+            };
+
+            assert!(!seen.contains(card.id()), "{url}: card {} was handed out twice", card.id());
+            seen.push(*card.id());
+
+            let difficulty = if seen.len() == 1 { "Hard" } else { "Easy" };
+            answer(&app, &session, &card, difficulty).await;
+        }
+
+        assert_eq!(seen.len(), 3, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn new_cards_are_spread_between_reviews() {
+    for url in &backends() {
+        let (app, store) = app_with_store(url).await;
+
+        // Four review cards that are due, and two new ones.
+        for index in 0..4 {
+            let mut card = create(&app, &format!("review {index}"), "back").await;
+            card.set_stage(CardStage::Review);
+            store.update_card(&card).await.unwrap();
+        }
+        create(&app, "new 0", "back").await;
+        create(&app, "new 1", "back").await;
+
+        let session = start(&app).await;
+        let mut stages = Vec::new();
+
+        while let Some(card) = next(&app, &session).await.card {
+            stages.push(*card.stage());
+            answer(&app, &session, &card, "Easy").await;
+        }
+
+        use CardStage::{New, Review};
+        assert_eq!(stages, vec![Review, New, Review, Review, New, Review], "{url}");
+    }
+}
+
+#[tokio::test]
+async fn finishing_a_session_summarises_it() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let first = create(&app, "one", "1").await;
+        let second = create(&app, "two", "2").await;
+
+        let session = start(&app).await;
+        let first = answer(&app, &session, &first, "Hard").await;
+        answer(&app, &session, &second, "Easy").await;
+        answer(&app, &session, &first, "Medium").await;
+
+        // Reviews outside the session don't count.
+        let third = create(&app, "three", "3").await;
+        review(&app, &third, "Easy").await;
+
+        let uri = format!("/sessions/{}/finish", session.id);
+        let (status, body) = send(&app, Method::POST, &uri, None).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let summary: SessionSummary = serde_json::from_value(body).unwrap();
+        assert_eq!(summary.cards_studied, 2, "{url}");
+        assert_eq!(summary.answers, 3, "{url}");
+        assert_eq!(summary.correct, 2, "{url}");
+        assert!((summary.accuracy - 2.0 / 3.0).abs() < 1e-9, "{url}");
+        assert!(summary.session.ended_at.is_some(), "{url}");
+
+        // A finished session takes no more answers.
+        let (status, _) = send(&app, Method::GET, &format!("/sessions/{}/next", session.id), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{url}");
+
+        let uri = format!("/cards/{}/review", third.id());
+        let (status, _) = send(&app, Method::POST, &uri, Some(json!({ "difficulty": "Easy", "session_id": session.id }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{url}");
+
+        let (status, _) = send(&app, Method::GET, "/sessions/99", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn answer_times_are_capped_and_averaged() {
+    for url in &backends() {
+        let (app, store) = app_with_store(url).await;
+
+        let first = create(&app, "one", "1").await;
+        let second = create(&app, "two", "2").await;
+        let third = create(&app, "three", "3").await;
+
+        let session = start(&app).await;
+        let uri = format!("/cards/{}/review", first.id());
+        send(&app, Method::POST, &uri, Some(json!({ "difficulty": "Easy", "session_id": session.id, "answer_time_ms": 4000 }))).await;
+
+        // Left on screen over a break, stored as the 60 second default maximum.
+        let uri = format!("/cards/{}/review", second.id());
+        send(&app, Method::POST, &uri, Some(json!({ "difficulty": "Easy", "session_id": session.id, "answer_time_ms": 600_000 }))).await;
+
+        // Untimed answers are left out of the average.
+        answer(&app, &session, &third, "Easy").await;
+
+        let reviews = store.get_reviews(Default::default()).await.unwrap();
+        let times: Vec<Option<u32>> = reviews.iter().map(|review| review.answer_time_ms).collect();
+        assert_eq!(times, vec![Some(4000), Some(60_000), None], "{url}");
+
+        let (_, body) = send(&app, Method::POST, &format!("/sessions/{}/finish", session.id), None).await;
+        let summary: SessionSummary = serde_json::from_value(body).unwrap();
+        assert_eq!(summary.average_answer_time_ms, Some(32_000), "{url}");
+    }
+}
+
+#[tokio::test]
+async fn cram_sessions_leave_schedules_and_limits_alone() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let cards = [
+            create(&app, "one", "back").await,
+            create(&app, "two", "back").await,
+            create(&app, "three", "back").await,
+        ];
+
+        let (status, body) = send(&app, Method::POST, "/sessions", Some(json!({ "kind": { "kind": "cram", "select": "deck" } }))).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+        let session: StudySession = serde_json::from_value(body).unwrap();
+        assert_eq!(session.kind, SessionKind::Cram(CramSelection::Deck), "{url}");
+
+        let mut shown = Vec::new();
+
+        while let Some(card) = next(&app, &session).await.card {
+            // The first card is failed once and comes back after the others.
+            let difficulty = if shown.is_empty() { "Hard" } else { "Easy" };
+            shown.push(*card.id());
+
+            let answered = answer(&app, &session, &card, difficulty).await;
+            assert_eq!(answered.next_review(), card.next_review(), "{url}");
+            assert_eq!(answered.stage(), &CardStage::New, "{url}");
+        }
+
+        assert_eq!(shown.len(), 4, "{url}");
+        assert_eq!(shown.first(), shown.last(), "{url}");
+
+        // Nothing was used up from the daily limits.
+        let scheduled = start(&app).await;
+        assert_eq!(next(&app, &scheduled).await.remaining, 3, "{url}");
+
+        // Only failures from scheduled study count.
+        review(&app, &cards[1], "Hard").await;
+
+        let (_, body) = send(&app, Method::POST, "/sessions", Some(json!({ "kind": { "kind": "cram", "select": "failed_today" } }))).await;
+        let failed: StudySession = serde_json::from_value(body).unwrap();
+
+        let next_failed = next(&app, &failed).await;
+        assert_eq!(next_failed.card.as_ref().map(Card::id), Some(cards[1].id()), "{url}");
+        assert_eq!(next_failed.remaining, 1, "{url}");
+
+        let (status, _) = send(&app, Method::POST, "/sessions", Some(json!({ "kind": { "kind": "cram", "select": "random", "count": 0 } }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn cramming_far_ahead_neither_panics_nor_starts() {
+    for url in &backends() {
+        let (app, store) = app_with_store(url).await;
+
+        let mut card = create(&app, "one", "back").await;
+        card.set_stage(CardStage::Review);
+        store.update_card(&card).await.unwrap();
+
+        let body = json!({ "kind": { "kind": "cram", "select": "ahead", "days": 1_000_000_000 } });
+        let (status, _) = send(&app, Method::POST, "/sessions", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{url}");
+
+        // Past the end of time every review card counts as due.
+        let selection = CramSelection::Ahead { days: u32::MAX };
+        let crammed = study::cram_cards(&selection, 1, vec![card.clone()], &[], Utc::now());
+        assert_eq!(crammed, vec![card], "{url}");
+    }
+}
+
+async fn cram(app: &Router, select: serde_json::Value) -> StudySession {
+    let (status, body) = send(app, Method::POST, "/sessions", Some(json!({ "kind": select }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    serde_json::from_value(body).unwrap()
+}
+
+// Every card a cram session shows when each is answered right the first time.
+async fn crammed(app: &Router, session: &StudySession) -> Vec<u32> {
+    let mut shown = Vec::new();
+
+    while let Some(card) = next(app, session).await.card {
+        shown.push(*card.id());
+        answer(app, session, &card, "Easy").await;
+    }
+
+    shown
+}
+
+#[tokio::test]
+async fn cram_sessions_pick_cards_by_flag_due_date_and_chance() {
+    for url in &backends() {
+        let (app, store) = app_with_store(url).await;
+
+        let cards = [
+            create(&app, "one", "back").await,
+            create(&app, "two", "back").await,
+            create(&app, "three", "back").await,
+        ];
+
+        let uri = format!("/cards/{}/status", cards[2].id());
+        let (status, _) = send(&app, Method::PATCH, &uri, Some(json!({ "flag": "Red" }))).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let session = cram(&app, json!({ "kind": "cram", "select": "flag", "flag": "Red" })).await;
+        assert_eq!(crammed(&app, &session).await, vec![*cards[2].id()], "{url}");
+
+        // Review cards due within the days ahead, soonest first. New cards are left out.
+        let now = Utc::now();
+        for (card, days) in [(&cards[0], 5), (&cards[1], 2)] {
+            let mut card = card.clone();
+            card.set_stage(CardStage::Review);
+            card.set_next_review(now + TimeDelta::days(days));
+            store.update_card(&card).await.unwrap();
+        }
+
+        let session = cram(&app, json!({ "kind": "cram", "select": "ahead", "days": 3 })).await;
+        assert_eq!(crammed(&app, &session).await, vec![*cards[1].id()], "{url}");
+
+        let session = cram(&app, json!({ "kind": "cram", "select": "ahead", "days": 7 })).await;
+        assert_eq!(crammed(&app, &session).await, vec![*cards[1].id(), *cards[0].id()], "{url}");
+
+        // A random pick keeps to the same cards for the whole session.
+        let session = cram(&app, json!({ "kind": "cram", "select": "random", "count": 2 })).await;
+        assert_eq!(next(&app, &session).await.remaining, 2, "{url}");
+
+        let shown = crammed(&app, &session).await;
+        assert_eq!(shown.len(), 2, "{url}");
+        assert_ne!(shown[0], shown[1], "{url}");
+        assert!(shown.iter().all(|id| cards.iter().any(|card| card.id() == id)), "{url}");
+    }
+}
+
+#[tokio::test]
+async fn cram_sessions_leave_out_suspended_and_buried_cards() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let kept = create(&app, "one", "back").await;
+        let buried = create(&app, "two", "back").await;
+        let suspended = create(&app, "three", "back").await;
+
+        let (status, _) = send(&app, Method::PATCH, &format!("/cards/{}/status", buried.id()), Some(json!({ "buried": true }))).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+        let (status, _) = send(&app, Method::PATCH, &format!("/cards/{}/status", suspended.id()), Some(json!({ "suspended": true }))).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let session = cram(&app, json!({ "kind": "cram", "select": "deck" })).await;
+        assert_eq!(crammed(&app, &session).await, vec![*kept.id()], "{url}");
+    }
+}
+
+#[tokio::test]
+async fn cram_sessions_pick_cards_by_tag() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let tagged = create(&app, "one", "back").await;
+        create(&app, "two", "back").await;
+
+        let operations = json!({ "operations": [{ "op": "tag", "id": tagged.id(), "add": ["irregular-verbs"] }] });
+        let (status, _) = send(&app, Method::POST, "/cards/batch", Some(operations)).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        // The tag is normalized like the card's, and kept that way with the session.
+        let session = cram(&app, json!({ "kind": "cram", "select": "tag", "tag": "Irregular Verbs" })).await;
+        assert_eq!(session.kind, SessionKind::Cram(CramSelection::Tag { tag: String::from("irregular-verbs") }), "{url}");
+        assert_eq!(crammed(&app, &session).await, vec![*tagged.id()], "{url}");
+
+        let (status, _) = send(&app, Method::POST, "/sessions", Some(json!({ "kind": { "kind": "cram", "select": "tag", "tag": " " } }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{url}");
+    }
+}
@@ -0,0 +1,209 @@
+use axum::http::{Method, StatusCode};
+use chrono::{TimeZone, Utc};
+use chrono_tz::Tz;
+use flashcards_data::{Card, CardAnswerTimes, CardFlag, Deck, SchedulerSettings, StudyQueue};
+use flashcards_server::study;
+use serde_json::json;
+
+mod common;
+
+use common::*;
+
+async fn queue(app: &axum::Router, deck_id: u32) -> StudyQueue {
+    let (status, body) = send(app, Method::GET, &format!("/study/queue?deck_id={deck_id}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    serde_json::from_value(body).unwrap()
+}
+
+async fn create_deck(app: &axum::Router, name: &str, new_cards_per_day: u32, reviews_per_day: u32) -> Deck {
+    let body = json!({ "name": name, "new_cards_per_day": new_cards_per_day, "reviews_per_day": reviews_per_day });
+    let (status, body) = send(app, Method::POST, "/decks", Some(body)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    serde_json::from_value(body).unwrap()
+}
+
+async fn create_in(app: &axum::Router, deck: &Deck, front: &str) -> Card {
+    let body = json!({ "front": front, "back": "back", "deck_id": deck.id });
+    let (status, body) = send(app, Method::POST, "/cards", Some(body)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    serde_json::from_value(body).unwrap()
+}
+
+#[tokio::test]
+async fn new_cards_are_limited_per_day() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let deck = create_deck(&app, "Imported", 3, 100).await;
+        for index in 0..10 {
+            create_in(&app, &deck, &format!("card {index}")).await;
+        }
+
+        let today = queue(&app, deck.id).await;
+        assert_eq!(today.cards.len(), 3, "{url}");
+        assert_eq!(today.new_remaining, 3, "{url}");
+
+        // Studying a new card uses up the allowance, and the card stays in the queue while
+        // it is being learned.
+        review(&app, &today.cards[0], "Hard").await;
+        review(&app, &today.cards[1], "Easy").await;
+
+        let today = queue(&app, deck.id).await;
+        assert_eq!(today.new_studied, 2, "{url}");
+        assert_eq!(today.new_remaining, 1, "{url}");
+        assert_eq!(today.cards.len(), 1, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn queues_only_hold_cards_of_their_deck() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let deck = create_deck(&app, "Spanish", 20, 200).await;
+        create_in(&app, &deck, "hola").await;
+        create(&app, "default", "deck").await;
+
+        let spanish = queue(&app, deck.id).await;
+        assert_eq!(spanish.cards.len(), 1, "{url}");
+        assert_eq!(spanish.cards[0].front(), "hola", "{url}");
+
+        let (status, _) = send(&app, Method::GET, "/study/queue", None).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let (status, _) = send(&app, Method::GET, "/study/queue?deck_id=99", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn deck_limits_can_be_changed() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let (status, body) = send(&app, Method::PATCH, "/decks/1", Some(json!({ "new_cards_per_day": 5 }))).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        let deck: Deck = serde_json::from_value(body).unwrap();
+        assert_eq!(deck.name, "Default", "{url}");
+        assert_eq!(deck.new_cards_per_day, 5, "{url}");
+
+        let (status, _) = send(&app, Method::POST, "/decks", Some(json!({ "name": " " }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{url}");
+
+        let (status, _) = send(&app, Method::POST, "/cards", Some(json!({ "front": "a", "back": "b", "deck_id": 42 }))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{url}");
+    }
+}
+
+#[test]
+fn study_days_roll_over_at_the_configured_hour() {
+    let berlin: Tz = "Europe/Berlin".parse().unwrap();
+
+    // 02:30 in Berlin (winter, UTC+1) still belongs to the previous day.
+    let now = Utc.with_ymd_and_hms(2026, 1, 15, 1, 30, 0).unwrap();
+    assert_eq!(study::day_start(now, berlin, 4), Utc.with_ymd_and_hms(2026, 1, 14, 3, 0, 0).unwrap());
+
+    // 05:00 in Berlin is past the rollover.
+    let now = Utc.with_ymd_and_hms(2026, 1, 15, 4, 0, 0).unwrap();
+    assert_eq!(study::day_start(now, berlin, 4), Utc.with_ymd_and_hms(2026, 1, 15, 3, 0, 0).unwrap());
+
+    // A rollover at midnight is a plain calendar day.
+    assert_eq!(study::day_start(now, Tz::UTC, 0), Utc.with_ymd_and_hms(2026, 1, 15, 0, 0, 0).unwrap());
+}
+
+#[test]
+fn a_rollover_inside_a_dst_gap_moves_an_hour_later() {
+    let berlin: Tz = "Europe/Berlin".parse().unwrap();
+
+    // Clocks jump from 02:00 to 03:00 on 2026-03-29, so 02:00 local doesn't exist.
+    let now = Utc.with_ymd_and_hms(2026, 3, 29, 12, 0, 0).unwrap();
+    assert_eq!(study::day_start(now, berlin, 2), Utc.with_ymd_and_hms(2026, 3, 29, 1, 0, 0).unwrap());
+}
+
+#[tokio::test]
+async fn suspended_and_buried_cards_stay_out_of_the_queue() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let suspended = create(&app, "suspended", "1").await;
+        let buried = create(&app, "buried", "2").await;
+        let flagged = create(&app, "flagged", "3").await;
+
+        let set_status = |card: &Card, status: serde_json::Value| {
+            let uri = format!("/cards/{}/status", card.id());
+            let app = app.clone();
+            async move {
+                let (status_code, body) = send(&app, Method::PATCH, &uri, Some(status)).await;
+                assert_eq!(status_code, StatusCode::OK);
+                serde_json::from_value::<Card>(body).unwrap()
+            }
+        };
+
+        assert!(*set_status(&suspended, json!({ "suspended": true })).await.suspended(), "{url}");
+        let buried = set_status(&buried, json!({ "buried": true })).await;
+        assert!(buried.buried_until().is_some_and(|until| until > Utc::now()), "{url}");
+        let flagged = set_status(&flagged, json!({ "flag": "Red" })).await;
+        assert_eq!(*flagged.flag(), CardFlag::Red, "{url}");
+
+        // Flags don't take a card out of rotation.
+        assert_eq!(queue(&app, 1).await.cards, vec![flagged.clone()], "{url}");
+
+        let (_, body) = send(&app, Method::GET, "/cards?suspended=true", None).await;
+        let cards: Vec<Card> = serde_json::from_value(body).unwrap();
+        assert_eq!(cards.len(), 1, "{url}");
+        assert_eq!(cards[0].front(), "suspended", "{url}");
+
+        let (_, body) = send(&app, Method::GET, "/cards?buried=false&flag=Red", None).await;
+        let cards: Vec<Card> = serde_json::from_value(body).unwrap();
+        assert_eq!(cards, vec![flagged.clone()], "{url}");
+
+        set_status(&suspended, json!({ "suspended": false })).await;
+        set_status(&buried, json!({ "buried": false })).await;
+        assert_eq!(queue(&app, 1).await.cards.len(), 3, "{url}");
+    }
+}
+
+#[tokio::test]
+async fn the_scheduler_settings_are_published() {
+    let app = app("memory:").await;
+
+    let (status, body) = send(&app, Method::GET, "/scheduler", None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let settings: SchedulerSettings = serde_json::from_value(body).unwrap();
+    assert_eq!(settings, SchedulerSettings::default());
+}
+
+#[tokio::test]
+async fn answer_times_are_kept_per_card() {
+    for url in &backends() {
+        let app = app(url).await;
+
+        let slow = create(&app, "one", "back").await;
+        let quick = create(&app, "two", "back").await;
+        create(&app, "three", "back").await;
+
+        for (card, answer_time_ms) in [(&slow, Some(9000)), (&slow, Some(3000)), (&slow, None), (&quick, Some(1500))] {
+            let body = json!({ "difficulty": "Medium", "answer_time_ms": answer_time_ms });
+            let (status, _) = send(&app, Method::POST, &format!("/cards/{}/review", card.id()), Some(body)).await;
+            assert_eq!(status, StatusCode::OK, "{url}");
+        }
+
+        let (status, body) = send(&app, Method::GET, "/cards/answer-times", None).await;
+        assert_eq!(status, StatusCode::OK, "{url}");
+
+        // Untimed answers don't count, cards never timed aren't listed.
+        let times: Vec<CardAnswerTimes> = serde_json::from_value(body).unwrap();
+        assert_eq!(times, vec![
+            CardAnswerTimes { card_id: *slow.id(), answers: 2, average_ms: 6000, maximum_ms: 9000 },
+            CardAnswerTimes { card_id: *quick.id(), answers: 1, average_ms: 1500, maximum_ms: 1500 },
+        ], "{url}");
+
+        let (_, body) = send(&app, Method::GET, "/cards/answer-times?deck_id=999", None).await;
+        assert_eq!(body, json!([]), "{url}");
+    }
+}
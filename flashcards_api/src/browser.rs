@@ -0,0 +1,33 @@
+// Sends requests with the browser's fetch, through gloo-net.
+use gloo_net::http::RequestBuilder;
+use crate::{ApiError, ApiRequest, ApiResponse, Transport};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GlooTransport;
+
+impl Transport for GlooTransport {
+    async fn send(&self, request: ApiRequest) -> Result<ApiResponse, ApiError> {
+
+        let mut builder = RequestBuilder::new(&request.url).method(request.method);
+
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+
+        let built = match request.body {
+            Some(body) => builder.body(body),
+            None => builder.build(),
+        }.map_err(|error| ApiError::Transport(error.to_string()))?;
+
+        let response = built.send()
+            .await
+            .map_err(|error| ApiError::Transport(error.to_string()))?;
+
+        let status = response.status();
+        let body = response.text()
+            .await
+            .map_err(|error| ApiError::Transport(error.to_string()))?;
+
+        Ok(ApiResponse { status, body })
+    }
+}
@@ -0,0 +1,310 @@
+// Typed client for the flashcards server: one async method per route, taking and returning the
+// shapes from `flashcards_data`.
+//
+// How requests travel is up to a `Transport`, so the same client runs in the browser
+// (`GlooTransport`, feature `gloo`), in native programs (`ReqwestTransport`, feature `reqwest`)
+// or straight against the router in tests.
+use std::fmt;
+use std::future::Future;
+use flashcards_data::{BatchPayload, BatchResult, Card, CardAnswerTimes, CardFlag, CardRevision, CreateCardPayload, CreateDeckPayload, CreateSessionPayload, Deck, DuplicateCards, DuplicateGroup, MergeCardsPayload, MergedCards, ReviewCardPayload, ReviewedCard, SchedulerSettings, SessionCard, SessionSummary, StudyQueue, StudySession, UpdateCardPayload, UpdateCardStatusPayload, UpdateDeckPayload, EDITOR_HEADER};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+pub use http::Method;
+
+#[cfg(feature = "gloo")]
+mod browser;
+#[cfg(feature = "reqwest")]
+mod native;
+
+#[cfg(feature = "gloo")]
+pub use browser::GlooTransport;
+#[cfg(feature = "reqwest")]
+pub use native::ReqwestTransport;
+
+// A request as the client built it, with the full URL and the body already encoded as JSON.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+// Carries a request to the server and brings back whatever it answered, whatever the status.
+// Only failing to get an answer at all is an error, reported as `ApiError::Transport`.
+pub trait Transport {
+    fn send(&self, request: ApiRequest) -> impl Future<Output = Result<ApiResponse, ApiError>>;
+}
+
+// Why a call to the server failed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ApiError {
+    // No answer: the server could not be reached, or the request could not be sent.
+    Transport(String),
+    // The deck already has cards with that front.
+    Duplicate(Vec<Card>),
+    // Any other answer outside 2xx, with the error the server gave.
+    Status { status: u16, message: String },
+    // The answer wasn't what the route should return.
+    Decode(String),
+}
+
+impl ApiError {
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ApiError::Duplicate(_) => Some(409),
+            ApiError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Transport(message) => write!(f, "could not reach the server: {}", message),
+            ApiError::Duplicate(cards) => write!(f, "{} card(s) with the same front already exist", cards.len()),
+            ApiError::Status { status, message } => write!(f, "the server answered {}: {}", status, message),
+            ApiError::Decode(message) => write!(f, "unexpected answer from the server: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: String,
+}
+
+// Narrows down `GET /cards`. Unset fields don't filter.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CardFilters {
+    pub suspended: Option<bool>,
+    pub buried: Option<bool>,
+    pub flag: Option<CardFlag>,
+    pub leech: Option<bool>,
+}
+
+impl CardFilters {
+    fn query(&self) -> String {
+        let params: Vec<String> = [
+            self.suspended.map(|suspended| format!("suspended={}", suspended)),
+            self.buried.map(|buried| format!("buried={}", buried)),
+            self.flag.map(|flag| format!("flag={}", flag.as_str())),
+            self.leech.map(|leech| format!("leech={}", leech)),
+        ].into_iter().flatten().collect();
+
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("?{}", params.join("&"))
+        }
+    }
+}
+
+fn deck_query(deck_id: Option<u32>) -> String {
+    deck_id.map(|deck_id| format!("?deck_id={}", deck_id)).unwrap_or_default()
+}
+
+fn json<B: Serialize>(body: &B) -> Result<Option<String>, ApiError> {
+    serde_json::to_string(body)
+        .map(Some)
+        .map_err(|error| ApiError::Decode(error.to_string()))
+}
+
+#[derive(Clone, Debug)]
+pub struct ApiClient<T> {
+    base_url: String,
+    transport: T,
+}
+
+impl<T: Transport> ApiClient<T> {
+
+    // `base_url` is where the routes hang off, e.g. `http://localhost:3000` or `/api`.
+    pub fn new(base_url: impl Into<String>, transport: T) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+
+        ApiClient { base_url, transport }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    // Sends the request and turns answers outside 2xx into errors. `editor` is recorded by the
+    // routes that keep a revision history.
+    async fn send(&self, method: Method, path: &str, body: Option<String>, editor: Option<&str>) -> Result<ApiResponse, ApiError> {
+
+        let mut headers = Vec::new();
+
+        if body.is_some() {
+            headers.push(("content-type".to_string(), "application/json".to_string()));
+        }
+
+        if let Some(editor) = editor {
+            headers.push((EDITOR_HEADER.to_string(), editor.to_string()));
+        }
+
+        let request = ApiRequest {
+            method,
+            url: format!("{}{}", self.base_url, path),
+            headers,
+            body,
+        };
+
+        let response = self.transport.send(request).await?;
+
+        match response.status {
+            200..=299 => Ok(response),
+            409 => match serde_json::from_str::<DuplicateCards>(&response.body) {
+                Ok(duplicate) => Err(ApiError::Duplicate(duplicate.duplicates)),
+                Err(_) => Err(error_status(response)),
+            },
+            _ => Err(error_status(response)),
+        }
+    }
+
+    async fn call<R: DeserializeOwned>(&self, method: Method, path: &str, body: Option<String>, editor: Option<&str>) -> Result<R, ApiError> {
+        let response = self.send(method, path, body, editor).await?;
+
+        serde_json::from_str(&response.body).map_err(|error| ApiError::Decode(error.to_string()))
+    }
+
+    pub async fn get_health(&self) -> Result<String, ApiError> {
+        Ok(self.send(Method::GET, "/health", None, None).await?.body)
+    }
+
+    pub async fn get_cards(&self, filters: &CardFilters) -> Result<Vec<Card>, ApiError> {
+        self.call(Method::GET, &format!("/cards{}", filters.query()), None, None).await
+    }
+
+    pub async fn get_cards_due(&self) -> Result<Vec<Card>, ApiError> {
+        self.call(Method::GET, "/cards/due", None, None).await
+    }
+
+    // Fails with `ApiError::Duplicate` when the deck already has a card with that front,
+    // unless the payload allows duplicates.
+    pub async fn add_card(&self, payload: &CreateCardPayload) -> Result<Card, ApiError> {
+        self.call(Method::POST, "/cards", json(payload)?, None).await
+    }
+
+    // Replaces the content of a card.
+    pub async fn update_card(&self, card_id: u32, payload: &CreateCardPayload, editor: &str) -> Result<Card, ApiError> {
+        self.call(Method::PUT, &format!("/cards/{}", card_id), json(payload)?, Some(editor)).await
+    }
+
+    pub async fn patch_card(&self, card_id: u32, payload: &UpdateCardPayload, editor: &str) -> Result<Card, ApiError> {
+        self.call(Method::PATCH, &format!("/cards/{}", card_id), json(payload)?, Some(editor)).await
+    }
+
+    // Moves the card to the trash.
+    pub async fn remove_card(&self, card_id: u32) -> Result<bool, ApiError> {
+        self.call(Method::DELETE, &format!("/cards/{}", card_id), None, None).await
+    }
+
+    pub async fn reset_card(&self, card_id: u32) -> Result<Card, ApiError> {
+        self.call(Method::POST, &format!("/cards/{}/reset", card_id), None, None).await
+    }
+
+    pub async fn patch_card_status(&self, card_id: u32, payload: &UpdateCardStatusPayload) -> Result<Card, ApiError> {
+        self.call(Method::PATCH, &format!("/cards/{}/status", card_id), json(payload)?, None).await
+    }
+
+    pub async fn review_card(&self, card_id: u32, payload: &ReviewCardPayload) -> Result<ReviewedCard, ApiError> {
+        self.call(Method::POST, &format!("/cards/{}/review", card_id), json(payload)?, None).await
+    }
+
+    pub async fn undo_review(&self, review_id: u32) -> Result<Card, ApiError> {
+        self.call(Method::POST, &format!("/reviews/{}/undo", review_id), None, None).await
+    }
+
+    pub async fn batch_cards(&self, payload: &BatchPayload, editor: &str) -> Result<Vec<BatchResult>, ApiError> {
+        self.call(Method::POST, "/cards/batch", json(payload)?, Some(editor)).await
+    }
+
+    pub async fn get_duplicates(&self, deck_id: Option<u32>) -> Result<Vec<DuplicateGroup>, ApiError> {
+        self.call(Method::GET, &format!("/cards/duplicates{}", deck_query(deck_id)), None, None).await
+    }
+
+    // Only cards with timed answers are listed.
+    pub async fn get_answer_times(&self, deck_id: Option<u32>) -> Result<Vec<CardAnswerTimes>, ApiError> {
+        self.call(Method::GET, &format!("/cards/answer-times{}", deck_query(deck_id)), None, None).await
+    }
+
+    pub async fn merge_cards(&self, payload: &MergeCardsPayload) -> Result<MergedCards, ApiError> {
+        self.call(Method::POST, "/cards/merge", json(payload)?, None).await
+    }
+
+    pub async fn get_card_revisions(&self, card_id: u32) -> Result<Vec<CardRevision>, ApiError> {
+        self.call(Method::GET, &format!("/cards/{}/revisions", card_id), None, None).await
+    }
+
+    pub async fn revert_card(&self, card_id: u32, revision_id: u32, editor: &str) -> Result<Card, ApiError> {
+        self.call(Method::POST, &format!("/cards/{}/revisions/{}/revert", card_id, revision_id), None, Some(editor)).await
+    }
+
+    pub async fn get_trash(&self) -> Result<Vec<Card>, ApiError> {
+        self.call(Method::GET, "/trash", None, None).await
+    }
+
+    pub async fn restore_card(&self, card_id: u32) -> Result<Card, ApiError> {
+        self.call(Method::POST, &format!("/trash/{}/restore", card_id), None, None).await
+    }
+
+    pub async fn get_decks(&self) -> Result<Vec<Deck>, ApiError> {
+        self.call(Method::GET, "/decks", None, None).await
+    }
+
+    pub async fn add_deck(&self, payload: &CreateDeckPayload) -> Result<Deck, ApiError> {
+        self.call(Method::POST, "/decks", json(payload)?, None).await
+    }
+
+    pub async fn get_deck(&self, deck_id: u32) -> Result<Deck, ApiError> {
+        self.call(Method::GET, &format!("/decks/{}", deck_id), None, None).await
+    }
+
+    pub async fn patch_deck(&self, deck_id: u32, payload: &UpdateDeckPayload) -> Result<Deck, ApiError> {
+        self.call(Method::PATCH, &format!("/decks/{}", deck_id), json(payload)?, None).await
+    }
+
+    // Without a deck, the queue of the default deck.
+    pub async fn get_study_queue(&self, deck_id: Option<u32>) -> Result<StudyQueue, ApiError> {
+        self.call(Method::GET, &format!("/study/queue{}", deck_query(deck_id)), None, None).await
+    }
+
+    pub async fn get_scheduler(&self) -> Result<SchedulerSettings, ApiError> {
+        self.call(Method::GET, "/scheduler", None, None).await
+    }
+
+    pub async fn start_session(&self, payload: &CreateSessionPayload) -> Result<StudySession, ApiError> {
+        self.call(Method::POST, "/sessions", json(payload)?, None).await
+    }
+
+    // How the session went so far.
+    pub async fn get_session(&self, session_id: u32) -> Result<SessionSummary, ApiError> {
+        self.call(Method::GET, &format!("/sessions/{}", session_id), None, None).await
+    }
+
+    pub async fn get_session_next(&self, session_id: u32) -> Result<SessionCard, ApiError> {
+        self.call(Method::GET, &format!("/sessions/{}/next", session_id), None, None).await
+    }
+
+    pub async fn finish_session(&self, session_id: u32) -> Result<SessionSummary, ApiError> {
+        self.call(Method::POST, &format!("/sessions/{}/finish", session_id), None, None).await
+    }
+}
+
+fn error_status(response: ApiResponse) -> ApiError {
+    let message = serde_json::from_str::<ErrorBody>(&response.body)
+        .map(|body| body.error)
+        .unwrap_or(response.body);
+
+    ApiError::Status { status: response.status, message }
+}
@@ -0,0 +1,40 @@
+// Sends requests with reqwest, for programs running outside the browser.
+use crate::{ApiError, ApiRequest, ApiResponse, Transport};
+
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    // Shares the connection pool, timeouts and so on of an existing client.
+    pub fn with_client(client: reqwest::Client) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    async fn send(&self, request: ApiRequest) -> Result<ApiResponse, ApiError> {
+
+        let mut builder = self.client.request(request.method, &request.url);
+
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        let response = builder.send()
+            .await
+            .map_err(|error| ApiError::Transport(error.to_string()))?;
+
+        let status = response.status().as_u16();
+        let body = response.text()
+            .await
+            .map_err(|error| ApiError::Transport(error.to_string()))?;
+
+        Ok(ApiResponse { status, body })
+    }
+}
@@ -0,0 +1,147 @@
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+use crate::{Card, CardDifficulty};
+
+// What a typed answer is compared with the back of a card under.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AnswerOptions {
+    pub ignore_case: bool,
+    pub ignore_accents: bool,
+}
+
+// One run of a character diff between a typed answer and the back of a card.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiffSegment {
+    // Typed as expected.
+    Same(String),
+    // Expected, but not typed.
+    Missing(String),
+    // Typed, but not expected.
+    Extra(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnswerCheck {
+    pub segments: Vec<DiffSegment>,
+    // Share of characters both answers have in common, from 0.0 to 1.0.
+    pub correctness: f64,
+}
+
+impl AnswerCheck {
+    pub fn is_correct(&self) -> bool {
+        self.segments.iter().all(|segment| matches!(segment, DiffSegment::Same(_)))
+    }
+
+    // A perfect answer is easy, a typo or two still counts as remembered, anything further
+    // off is treated as forgotten.
+    pub fn suggested_difficulty(&self) -> CardDifficulty {
+        if self.is_correct() {
+            CardDifficulty::Easy
+        } else if self.correctness >= 0.8 {
+            CardDifficulty::Medium
+        } else {
+            CardDifficulty::Hard
+        }
+    }
+}
+
+// The form a character is compared in. Characters keep their own spelling in the diff.
+fn fold(character: char, options: AnswerOptions) -> String {
+    let folded: String = if options.ignore_accents {
+        character.to_string().nfd().filter(|c| !is_combining_mark(*c)).collect()
+    } else {
+        character.to_string()
+    };
+
+    if options.ignore_case {
+        folded.to_lowercase()
+    } else {
+        folded
+    }
+}
+
+fn push(segments: &mut Vec<DiffSegment>, segment: DiffSegment) {
+    match (segments.last_mut(), segment) {
+        (Some(DiffSegment::Same(run)), DiffSegment::Same(next))
+        | (Some(DiffSegment::Missing(run)), DiffSegment::Missing(next))
+        | (Some(DiffSegment::Extra(run)), DiffSegment::Extra(next)) => run.push_str(&next),
+        (_, segment) => segments.push(segment),
+    }
+}
+
+// Compares a typed answer with the expected one character by character, using the longest
+// common subsequence. Surrounding whitespace is ignored.
+pub fn check_answer(typed: &str, expected: &str, options: AnswerOptions) -> AnswerCheck {
+    let typed: Vec<char> = typed.trim().nfc().collect();
+    let expected: Vec<char> = expected.trim().nfc().collect();
+
+    let typed_keys: Vec<String> = typed.iter().map(|c| fold(*c, options)).collect();
+    let expected_keys: Vec<String> = expected.iter().map(|c| fold(*c, options)).collect();
+
+    // common[i][j] is the length of the longest common subsequence of typed[i..] and
+    // expected[j..].
+    let mut common = vec![vec![0usize; expected.len() + 1]; typed.len() + 1];
+
+    for i in (0..typed.len()).rev() {
+        for j in (0..expected.len()).rev() {
+            common[i][j] = if typed_keys[i] == expected_keys[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut segments = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < typed.len() || j < expected.len() {
+        if i < typed.len() && j < expected.len() && typed_keys[i] == expected_keys[j] {
+            push(&mut segments, DiffSegment::Same(typed[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if j < expected.len() && (i == typed.len() || common[i][j + 1] >= common[i + 1][j]) {
+            push(&mut segments, DiffSegment::Missing(expected[j].to_string()));
+            j += 1;
+        } else {
+            push(&mut segments, DiffSegment::Extra(typed[i].to_string()));
+            i += 1;
+        }
+    }
+
+    let total = typed.len() + expected.len();
+    let correctness = if total == 0 { 1.0 } else { 2.0 * common[0][0] as f64 / total as f64 };
+
+    AnswerCheck { segments, correctness }
+}
+
+// Backs of other cards in the same deck to offer next to the right one in a quiz, those that
+// look most like it first so the choice is not given away. Each back is offered once and never
+// when it reads like the right one.
+pub fn pick_distractors<'a>(card: &Card, cards: impl IntoIterator<Item = &'a Card>, count: usize) -> Vec<String> {
+    let lenient = AnswerOptions { ignore_case: true, ignore_accents: true };
+
+    let mut candidates: Vec<(f64, &Card)> = cards.into_iter()
+        .filter(|other| other.id() != card.id() && other.deck_id() == card.deck_id())
+        .filter(|other| !check_answer(other.back(), card.back(), lenient).is_correct())
+        .map(|other| (check_answer(other.back(), card.back(), lenient).correctness, other))
+        .collect();
+
+    candidates.sort_by(|(a, first), (b, second)| b.total_cmp(a).then(first.id().cmp(second.id())));
+
+    let mut distractors: Vec<String> = Vec::new();
+
+    for (_, other) in candidates {
+        let back = other.back().trim();
+
+        if !distractors.iter().any(|picked| check_answer(picked, back, lenient).is_correct()) {
+            distractors.push(back.to_string());
+        }
+
+        if distractors.len() == count {
+            break;
+        }
+    }
+
+    distractors
+}
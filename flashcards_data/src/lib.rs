@@ -1,8 +1,19 @@
 use sqlx::FromRow;
 use serde::{Serialize,Deserialize};
 use chrono::{Utc, DateTime};
-use chrono::format::ParseError;
+use std::fmt;
 
+mod answer;
+mod scheduler;
+
+pub use answer::{check_answer, pick_distractors, AnswerCheck, AnswerOptions, DiffSegment};
+pub use scheduler::{format_interval, LearningSteps, SchedulerSettings};
+
+// Cards that are created without naming a deck end up in this one. It always exists.
+pub const DEFAULT_DECK_ID: u32 = 1;
+
+// Request header naming whoever edits a card, it is recorded with the revision.
+pub const EDITOR_HEADER: &str = "x-flashcards-editor";
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub enum CardDifficulty {
@@ -12,19 +23,169 @@ pub enum CardDifficulty {
     Hard,
 }
 
+impl CardDifficulty {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CardDifficulty::Easy => "Easy",
+            CardDifficulty::Medium => "Medium",
+            CardDifficulty::Hard => "Hard",
+        }
+    }
+}
+
+impl TryFrom<String> for CardDifficulty {
+    type Error = String;
+
+    fn try_from(difficulty: String) -> Result<Self, Self::Error> {
+        match difficulty.as_str() {
+            "Easy" => Ok(CardDifficulty::Easy),
+            "Medium" => Ok(CardDifficulty::Medium),
+            "Hard" => Ok(CardDifficulty::Hard),
+            _ => Err(format!("unknown difficulty `{}`", difficulty)),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct ReviewCardPayload {
     pub difficulty: CardDifficulty,
+    // The study session the answer was given in, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<u32>,
+    // Milliseconds from showing the front of the card to answering it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer_time_ms: Option<u32>,
+}
+
+// Where a card is in its life. New and (re)learning cards are shown again after short
+// steps measured in minutes; review cards come back after `interval` days.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardStage {
+    #[default]
+    New,
+    Learning,
+    Review,
+    Relearning,
+}
+
+impl CardStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CardStage::New => "New",
+            CardStage::Learning => "Learning",
+            CardStage::Review => "Review",
+            CardStage::Relearning => "Relearning",
+        }
+    }
+    pub fn is_learning(&self) -> bool {
+        matches!(self, CardStage::Learning | CardStage::Relearning)
+    }
+}
+
+impl TryFrom<String> for CardStage {
+    type Error = String;
+
+    fn try_from(stage: String) -> Result<Self, Self::Error> {
+        match stage.as_str() {
+            "New" => Ok(CardStage::New),
+            "Learning" => Ok(CardStage::Learning),
+            "Review" => Ok(CardStage::Review),
+            "Relearning" => Ok(CardStage::Relearning),
+            _ => Err(format!("unknown card stage `{}`", stage)),
+        }
+    }
+}
+
+// Colour marker on a card, free for the learner to give a meaning.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardFlag {
+    #[default]
+    Unflagged,
+    Red,
+    Orange,
+    Green,
+    Blue,
+}
+
+impl CardFlag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CardFlag::Unflagged => "Unflagged",
+            CardFlag::Red => "Red",
+            CardFlag::Orange => "Orange",
+            CardFlag::Green => "Green",
+            CardFlag::Blue => "Blue",
+        }
+    }
+    // The flag after this one, going round to `Unflagged` after the last colour.
+    pub fn next(&self) -> Self {
+        match self {
+            CardFlag::Unflagged => CardFlag::Red,
+            CardFlag::Red => CardFlag::Orange,
+            CardFlag::Orange => CardFlag::Green,
+            CardFlag::Green => CardFlag::Blue,
+            CardFlag::Blue => CardFlag::Unflagged,
+        }
+    }
+}
+
+impl TryFrom<String> for CardFlag {
+    type Error = String;
+
+    fn try_from(flag: String) -> Result<Self, Self::Error> {
+        match flag.as_str() {
+            "Unflagged" => Ok(CardFlag::Unflagged),
+            "Red" => Ok(CardFlag::Red),
+            "Orange" => Ok(CardFlag::Orange),
+            "Green" => Ok(CardFlag::Green),
+            "Blue" => Ok(CardFlag::Blue),
+            _ => Err(format!("unknown flag `{}`", flag)),
+        }
+    }
+}
+
+// Tags are kept lowercase with dashes for spaces, so `Irregular Verbs` and `irregular-verbs`
+// are the same tag. Nothing is left of a blank tag.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<String>>()
+        .join("-");
+
+    (!tag.is_empty()).then_some(tag)
 }
 
 #[derive(FromRow, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Card {
     id: u32,
+    deck_id: u32,
     front: String,
     back: String,
-    ease_factor: u8,
-    interval: u8,
-    next_review: String,
+    ease_factor: f64,
+    // Days until the next review.
+    interval: u32,
+    #[sqlx(try_from = "String")]
+    stage: CardStage,
+    // Index of the current learning or relearning step.
+    step: u32,
+    // Times the card was forgotten after it graduated, i.e. answered `Hard` in review.
+    lapses: u32,
+    // Timestamps are always UTC and (de)serialize as RFC 3339.
+    next_review: DateTime<Utc>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    // Suspended cards are left out of studying until they are unsuspended.
+    suspended: bool,
+    // Buried cards are left out of studying until this time, the start of the next study day.
+    buried_until: Option<DateTime<Utc>>,
+    #[sqlx(try_from = "String")]
+    flag: CardFlag,
+    // Set while the card is in the trash, it is purged for good some days later.
+    deleted_at: Option<DateTime<Utc>>,
+    // Sorted, see `normalize_tag` for what a tag looks like.
+    #[sqlx(json)]
+    #[serde(default)]
+    tags: Vec<String>,
 }
 
 #[derive(Default, PartialEq, Clone, Debug)]
@@ -38,6 +199,394 @@ pub enum CardSide {
 pub struct CreateCardPayload {
     pub front: String,
     pub back: String,
+    // Defaults to `DEFAULT_DECK_ID`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deck_id: Option<u32>,
+    // Cards whose front matches one already in the deck are refused unless this is set.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_duplicate: bool,
+}
+
+// Cards of one deck that are likely the same card, see `GET /cards/duplicates`.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub deck_id: u32,
+    // The front the cards share once case, spacing, punctuation and Unicode forms are ignored.
+    pub key: String,
+    pub cards: Vec<Card>,
+}
+
+// Body of the 409 response refusing a duplicate card.
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct DuplicateCards {
+    pub error: String,
+    pub duplicates: Vec<Card>,
+}
+
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct MergeCardsPayload {
+    pub card_ids: Vec<u32>,
+}
+
+// The card a merge kept and the ids of the cards it moved to the trash, along with their own
+// reviews.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct MergedCards {
+    pub kept: Card,
+    pub trashed: Vec<u32>,
+}
+
+// A group of cards that is studied together, with its own daily limits.
+#[derive(FromRow, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Deck {
+    pub id: u32,
+    pub name: String,
+    // How many cards may be seen for the first time per study day.
+    pub new_cards_per_day: u32,
+    // How many review cards may be answered per study day.
+    pub reviews_per_day: u32,
+}
+
+impl Deck {
+    pub fn new(id: u32, name: String) -> Self {
+        Deck {
+            id,
+            name,
+            new_cards_per_day: 20,
+            reviews_per_day: 200,
+        }
+    }
+}
+
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct CreateDeckPayload {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_cards_per_day: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviews_per_day: Option<u32>,
+}
+
+// Partial update of a deck, fields left out are kept as they are.
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateDeckPayload {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_cards_per_day: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviews_per_day: Option<u32>,
+}
+
+// One answer given to a card. `stage` is the stage the card was in when it was answered,
+// so a review of a `New` card is the first time that card was studied.
+#[derive(FromRow, Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct CardReview {
+    pub id: u32,
+    pub card_id: u32,
+    #[sqlx(try_from = "String")]
+    pub difficulty: CardDifficulty,
+    #[sqlx(try_from = "String")]
+    pub stage: CardStage,
+    pub reviewed_at: DateTime<Utc>,
+    pub session_id: Option<u32>,
+    // Capped at the server's `study.maximum_answer_seconds`.
+    pub answer_time_ms: Option<u32>,
+    // The card's scheduling state before the answer, which is what undoing the review puts
+    // back. Reviews logged before undo existed don't have it.
+    pub previous_ease_factor: Option<f64>,
+    pub previous_interval: Option<u32>,
+    pub previous_step: Option<u32>,
+    pub previous_next_review: Option<DateTime<Utc>>,
+    pub previous_lapses: Option<u32>,
+    pub previous_suspended: Option<bool>,
+    // Given in a cram session: the card was not rescheduled and the daily limits ignore it.
+    #[serde(default)]
+    pub cram: bool,
+}
+
+impl CardReview {
+    // Builds the log entry for answering `card`, which still has its state from before the answer.
+    pub fn of(card: &Card, difficulty: CardDifficulty, reviewed_at: DateTime<Utc>) -> Self {
+        Self {
+            card_id: card.id,
+            difficulty,
+            stage: card.stage,
+            reviewed_at,
+            previous_ease_factor: Some(card.ease_factor),
+            previous_interval: Some(card.interval),
+            previous_step: Some(card.step),
+            previous_next_review: Some(card.next_review),
+            previous_lapses: Some(card.lapses),
+            previous_suspended: Some(card.suspended),
+            ..Self::default()
+        }
+    }
+
+    // Puts `card` back into the state it was in before this answer. Returns false, leaving the
+    // card alone, when the review doesn't know that state.
+    pub fn restore(&self, card: &mut Card) -> bool {
+        let (Some(ease_factor), Some(interval), Some(step), Some(next_review)) = (
+            self.previous_ease_factor,
+            self.previous_interval,
+            self.previous_step,
+            self.previous_next_review,
+        ) else {
+            return false;
+        };
+
+        card.ease_factor = ease_factor;
+        card.interval = interval;
+        card.stage = self.stage;
+        card.step = step;
+        card.next_review = next_review;
+
+        // Reviews logged before leeches were tracked didn't change either.
+        if let Some(lapses) = self.previous_lapses {
+            card.lapses = lapses;
+        }
+        if let Some(suspended) = self.previous_suspended {
+            card.suspended = suspended;
+        }
+
+        true
+    }
+}
+
+// Response of a review: the card with its new schedule, plus the id of the logged review so
+// the answer can be undone.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ReviewedCard {
+    #[serde(flatten)]
+    pub card: Card,
+    pub review_id: u32,
+}
+
+// Which cards of the deck a cram session drills, whether they are due or not.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "select", rename_all = "snake_case")]
+pub enum CramSelection {
+    Deck,
+    Flag { flag: CardFlag },
+    // Cards carrying the tag, normalized when the session starts.
+    Tag { tag: String },
+    // Cards answered `Hard` since the study day began.
+    FailedToday,
+    // Cards in review that come due within `days`.
+    Ahead { days: u32 },
+    // `count` cards picked at random.
+    Random { count: u32 },
+}
+
+// Scheduled sessions study today's queue and reschedule every card answered. Cram sessions
+// log their answers but leave the cards' schedules alone, and don't count towards the daily
+// limits.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionKind {
+    #[default]
+    Scheduled,
+    Cram(CramSelection),
+}
+
+impl SessionKind {
+    pub fn is_cram(&self) -> bool {
+        matches!(self, SessionKind::Cram(_))
+    }
+}
+
+// Stored as text, e.g. `Scheduled`, `Cram Flag Red` or `Cram Random 20`. Tags have no
+// whitespace, so `Cram Tag irregular-verbs` splits back the same way.
+impl fmt::Display for SessionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionKind::Scheduled => write!(f, "Scheduled"),
+            SessionKind::Cram(CramSelection::Deck) => write!(f, "Cram Deck"),
+            SessionKind::Cram(CramSelection::Flag { flag }) => write!(f, "Cram Flag {}", flag.as_str()),
+            SessionKind::Cram(CramSelection::Tag { tag }) => write!(f, "Cram Tag {}", tag),
+            SessionKind::Cram(CramSelection::FailedToday) => write!(f, "Cram FailedToday"),
+            SessionKind::Cram(CramSelection::Ahead { days }) => write!(f, "Cram Ahead {}", days),
+            SessionKind::Cram(CramSelection::Random { count }) => write!(f, "Cram Random {}", count),
+        }
+    }
+}
+
+impl TryFrom<String> for SessionKind {
+    type Error = String;
+
+    fn try_from(kind: String) -> Result<Self, Self::Error> {
+        let parts: Vec<&str> = kind.split_whitespace().collect();
+        let number = |value: &str| value.parse::<u32>().map_err(|_| format!("unknown session kind `{}`", kind));
+
+        let selection = match parts.as_slice() {
+            ["Scheduled"] => return Ok(SessionKind::Scheduled),
+            ["Cram", "Deck"] => CramSelection::Deck,
+            ["Cram", "Flag", flag] => CramSelection::Flag { flag: CardFlag::try_from(flag.to_string())? },
+            ["Cram", "Tag", tag] => CramSelection::Tag { tag: tag.to_string() },
+            ["Cram", "FailedToday"] => CramSelection::FailedToday,
+            ["Cram", "Ahead", days] => CramSelection::Ahead { days: number(days)? },
+            ["Cram", "Random", count] => CramSelection::Random { count: number(count)? },
+            _ => return Err(format!("unknown session kind `{}`", kind)),
+        };
+
+        Ok(SessionKind::Cram(selection))
+    }
+}
+
+// A sitting in which a deck is studied. The server picks the cards, see `SessionCard`.
+#[derive(FromRow, Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct StudySession {
+    pub id: u32,
+    pub deck_id: u32,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    #[sqlx(try_from = "String")]
+    #[serde(default)]
+    pub kind: SessionKind,
+}
+
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct CreateSessionPayload {
+    // Defaults to `DEFAULT_DECK_ID`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deck_id: Option<u32>,
+    #[serde(default)]
+    pub kind: SessionKind,
+}
+
+// What to show next in a session. Without a card the session is either done, or waiting
+// for a learning card that comes back at `next_due_at`.
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct SessionCard {
+    pub card: Option<Card>,
+    pub next_due_at: Option<DateTime<Utc>>,
+    // Cards left in today's queue, including `card`.
+    pub remaining: u32,
+}
+
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub session: StudySession,
+    // Distinct cards answered.
+    pub cards_studied: u32,
+    pub answers: u32,
+    // Answers that weren't `Hard`.
+    pub correct: u32,
+    // `correct / answers`, between 0 and 1.
+    pub accuracy: f64,
+    pub time_spent_seconds: i64,
+    // Over the answers that were timed.
+    pub average_answer_time_ms: Option<u32>,
+}
+
+// How long answering a card takes, over every timed answer it was given.
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct CardAnswerTimes {
+    pub card_id: u32,
+    // Timed answers, the others don't count.
+    pub answers: u32,
+    pub average_ms: u32,
+    pub maximum_ms: u32,
+}
+
+// The cards to study right now in a deck, after the daily limits have been applied.
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct StudyQueue {
+    pub deck_id: u32,
+    // Learning cards first, then reviews, then new cards.
+    pub cards: Vec<Card>,
+    // What was already studied since the current study day began.
+    pub new_studied: u32,
+    pub reviews_studied: u32,
+    // What is left of today's limits.
+    pub new_remaining: u32,
+    pub reviews_remaining: u32,
+}
+
+// Partial update of a card's content. Fields left out are kept as they are, and the
+// scheduling state of the card is never touched.
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateCardPayload {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub front: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub back: Option<String>,
+}
+
+impl UpdateCardPayload {
+    pub fn changes(&self, card: &Card) -> bool {
+        let front_changed = self.front.as_deref().is_some_and(|front| front != card.front());
+        let back_changed = self.back.as_deref().is_some_and(|back| back != card.back());
+
+        front_changed || back_changed
+    }
+}
+
+// Takes a card out of rotation or puts it back, and sets its flag. Fields left out are kept
+// as they are.
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateCardStatusPayload {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suspended: Option<bool>,
+    // Burying lasts until the next study day starts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buried: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flag: Option<CardFlag>,
+}
+
+// One step of a batch, tagged by `op`, e.g. `{ "op": "move", "id": 3, "deck_id": 2 }`.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperation {
+    Create(CreateCardPayload),
+    Update {
+        id: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        front: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        back: Option<String>,
+    },
+    // Moves the card to the trash, like `DELETE /cards/{id}`.
+    Delete { id: u32 },
+    Move { id: u32, deck_id: u32 },
+    Flag { id: u32, flag: CardFlag },
+    Suspend { id: u32, suspended: bool },
+    // Adds and removes tags, e.g. `{ "op": "tag", "id": 3, "add": ["verbs"], "remove": [] }`.
+    Tag {
+        id: u32,
+        #[serde(default)]
+        add: Vec<String>,
+        #[serde(default)]
+        remove: Vec<String>,
+    },
+}
+
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct BatchPayload {
+    pub operations: Vec<BatchOperation>,
+}
+
+// What became of one operation of a batch. Failed operations change nothing, the others are
+// still carried out.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BatchResult {
+    Done { card: Card },
+    Failed { error: String },
+}
+
+// Content of a card before an edit, with when the edit was made and by whom.
+#[derive(FromRow, Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct CardRevision {
+    pub id: u32,
+    pub card_id: u32,
+    pub front: String,
+    pub back: String,
+    pub created_at: DateTime<Utc>,
+    // Unknown for edits made before editors were recorded, or without naming one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
 }
 
 #[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
@@ -82,66 +631,139 @@ impl CardState {
 impl Card {
     pub fn new(id: u32, front: String, back: String) -> Self {
 
-        let dt = Utc::now();
-        let date_time_string = dt.format("%Y-%m-%d %H:%M:%S%.9f %Z").to_string();
-        println!("{}", date_time_string);
+        let now = Utc::now();
 
         Card {
             id,
+            deck_id: DEFAULT_DECK_ID,
             front,
             back,
-            next_review: date_time_string, //Utc::now().to_string(),
-//String::new(),
-            ease_factor: 3,
+            next_review: now,
+            created_at: now,
+            updated_at: now,
+            ease_factor: 3.0,
             interval: 1,
+            stage: CardStage::New,
+            step: 0,
+            lapses: 0,
+            suspended: false,
+            buried_until: None,
+            flag: CardFlag::Unflagged,
+            deleted_at: None,
+            tags: Vec::new(),
         }
     }
     pub fn id(&self) -> &u32 {
         &self.id
     }
+    pub fn deck_id(&self) -> &u32 {
+        &self.deck_id
+    }
     pub fn front(&self) -> &str {
         &self.front
     }
     pub fn back(&self) -> &str {
         &self.back
     }
-    pub fn next_review(&self) -> &str {
+    pub fn next_review(&self) -> &DateTime<Utc> {
         &self.next_review
     }
-    pub fn ease_factor(&self) -> &u8 {
+    pub fn created_at(&self) -> &DateTime<Utc> {
+        &self.created_at
+    }
+    pub fn updated_at(&self) -> &DateTime<Utc> {
+        &self.updated_at
+    }
+    pub fn ease_factor(&self) -> &f64 {
         &self.ease_factor
     }
-    pub fn interval(&self) -> &u8 {
+    pub fn interval(&self) -> &u32 {
         &self.interval
     }
+    pub fn stage(&self) -> &CardStage {
+        &self.stage
+    }
+    pub fn step(&self) -> &u32 {
+        &self.step
+    }
+    pub fn lapses(&self) -> &u32 {
+        &self.lapses
+    }
+    pub fn suspended(&self) -> &bool {
+        &self.suspended
+    }
+    pub fn buried_until(&self) -> &Option<DateTime<Utc>> {
+        &self.buried_until
+    }
+    pub fn flag(&self) -> &CardFlag {
+        &self.flag
+    }
+    pub fn deleted_at(&self) -> &Option<DateTime<Utc>> {
+        &self.deleted_at
+    }
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+    pub fn set_deck_id(&mut self, deck_id: u32) {
+        self.deck_id = deck_id;
+    }
     pub fn set_front(&mut self, front: &str) {
         self.front = String::from(front);
     }
     pub fn set_back(&mut self, back: &str) {
         self.back = String::from(back);
     }
-    pub fn set_next_review(&mut self, next_review: &str) {
-        self.next_review = String::from(next_review);
+    pub fn set_next_review(&mut self, next_review: DateTime<Utc>) {
+        self.next_review = next_review;
     }
-    pub fn set_ease_factor(&mut self, ease_factor: u8) {
+    pub fn set_created_at(&mut self, created_at: DateTime<Utc>) {
+        self.created_at = created_at;
+    }
+    pub fn set_updated_at(&mut self, updated_at: DateTime<Utc>) {
+        self.updated_at = updated_at;
+    }
+    pub fn set_ease_factor(&mut self, ease_factor: f64) {
         self.ease_factor = ease_factor;
     }
-    pub fn set_interval(&mut self, interval: u8) {
+    pub fn set_interval(&mut self, interval: u32) {
         self.interval = interval;
     }
+    pub fn set_stage(&mut self, stage: CardStage) {
+        self.stage = stage;
+    }
+    pub fn set_step(&mut self, step: u32) {
+        self.step = step;
+    }
+    pub fn set_lapses(&mut self, lapses: u32) {
+        self.lapses = lapses;
+    }
+    pub fn set_suspended(&mut self, suspended: bool) {
+        self.suspended = suspended;
+    }
+    pub fn set_buried_until(&mut self, buried_until: Option<DateTime<Utc>>) {
+        self.buried_until = buried_until;
+    }
+    pub fn set_flag(&mut self, flag: CardFlag) {
+        self.flag = flag;
+    }
+    pub fn set_deleted_at(&mut self, deleted_at: Option<DateTime<Utc>>) {
+        self.deleted_at = deleted_at;
+    }
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+        self.tags.sort();
+        self.tags.dedup();
+    }
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|card_tag| card_tag == tag)
+    }
+    pub fn is_due_at(&self, now: DateTime<Utc>) -> bool {
+        self.next_review < now
+    }
+    pub fn is_buried_at(&self, now: DateTime<Utc>) -> bool {
+        self.buried_until.is_some_and(|buried_until| buried_until > now)
+    }
     pub fn needs_review(&self) -> bool {
-        let current_date = Utc::now();
-        let card_review_date: Result<DateTime<Utc>, ParseError> = self.next_review().parse(); //.expect("Valid date");
-
-        if let Ok(card_review_date) = card_review_date {
-            println!("Card Review Date Success: {}", self.id());
-            return card_review_date < current_date;
-        } else {
-            println!("Could not parse {}", self.id());
-        }
-            
-
-        return false;
-        //card_review_date < current_date
+        self.is_due_at(Utc::now())
     }
 }
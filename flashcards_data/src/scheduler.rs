@@ -0,0 +1,323 @@
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use chrono::{DateTime, Days, TimeDelta, Utc};
+use std::fmt;
+use std::str::FromStr;
+use crate::{Card, CardDifficulty, CardStage};
+
+// Short delays a card goes through before it graduates to daily reviews, written the way
+// they appear in the config file: `1m 10m 1h`. Units are s, m, h and d.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LearningSteps(Vec<TimeDelta>);
+
+impl LearningSteps {
+    pub fn new(steps: Vec<TimeDelta>) -> Self {
+        Self(steps)
+    }
+    pub fn get(&self, step: u32) -> Option<&TimeDelta> {
+        self.0.get(step as usize)
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromStr for LearningSteps {
+    type Err = String;
+
+    fn from_str(steps: &str) -> Result<Self, Self::Err> {
+        steps.split_whitespace()
+            .map(|step| {
+                let split = step.len() - step.chars().last().map_or(0, char::len_utf8);
+                let (amount, unit) = step.split_at(split);
+
+                let amount: i64 = amount.parse()
+                    .map_err(|_| format!("learning step `{}` must be a number followed by s, m, h or d", step))?;
+
+                let delta = match unit {
+                    "s" => TimeDelta::try_seconds(amount),
+                    "m" => TimeDelta::try_minutes(amount),
+                    "h" => TimeDelta::try_hours(amount),
+                    "d" => TimeDelta::try_days(amount),
+                    _ => None,
+                };
+
+                match delta {
+                    Some(delta) if delta > TimeDelta::zero() => Ok(delta),
+                    _ => Err(format!("learning step `{}` must be a positive number followed by s, m, h or d", step)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+}
+
+impl fmt::Display for LearningSteps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let steps: Vec<String> = self.0.iter()
+            .map(|step| {
+                let seconds = step.num_seconds();
+                match seconds {
+                    _ if seconds % 86400 == 0 => format!("{}d", seconds / 86400),
+                    _ if seconds % 3600 == 0 => format!("{}h", seconds / 3600),
+                    _ if seconds % 60 == 0 => format!("{}m", seconds / 60),
+                    _ => format!("{}s", seconds),
+                }
+            })
+            .collect();
+
+        write!(f, "{}", steps.join(" "))
+    }
+}
+
+impl Serialize for LearningSteps {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for LearningSteps {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let steps = String::deserialize(deserializer)?;
+        steps.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// Tunables of the spaced repetition scheduler. The server reads them from the `[scheduler]`
+// section of its config file; the defaults match what the app has always used.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchedulerSettings {
+    pub starting_ease_factor: f64,
+    // Days between reviews for a new card.
+    pub starting_interval: u32,
+    pub minimum_ease_factor: f64,
+    pub maximum_ease_factor: f64,
+    // Upper bound, in days, for any interval the scheduler hands out.
+    pub maximum_interval: u32,
+    // Steps for new cards, and for review cards that were forgotten.
+    pub learning_steps: LearningSteps,
+    pub relearning_steps: LearningSteps,
+    // Lapses after which a card counts as a leech, 0 turns leech detection off.
+    pub leech_threshold: u32,
+    // Suspend a card the moment it becomes a leech.
+    pub suspend_leeches: bool,
+    // `Easy` answers that took longer than this many seconds count as `Medium`, 0 turns this
+    // off.
+    pub slow_answer_seconds: u32,
+}
+
+impl Default for SchedulerSettings {
+    fn default() -> Self {
+        Self {
+            starting_ease_factor: 3.0,
+            starting_interval: 1,
+            minimum_ease_factor: 1.0,
+            maximum_ease_factor: 5.0,
+            maximum_interval: 36500,
+            learning_steps: LearningSteps(vec![TimeDelta::minutes(1), TimeDelta::minutes(10)]),
+            relearning_steps: LearningSteps(vec![TimeDelta::minutes(10)]),
+            leech_threshold: 8,
+            suspend_leeches: false,
+            slow_answer_seconds: 0,
+        }
+    }
+}
+
+impl SchedulerSettings {
+    // Human readable problems with the settings, empty when they are usable.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.starting_interval == 0 {
+            problems.push(String::from("starting_interval must be at least 1 day"));
+        }
+
+        if self.maximum_interval < self.starting_interval {
+            problems.push(String::from("maximum_interval must not be shorter than starting_interval"));
+        }
+
+        if self.maximum_interval > i32::MAX as u32 {
+            problems.push(format!("maximum_interval must not exceed {} days", i32::MAX));
+        }
+
+        let ease_factors = [self.starting_ease_factor, self.minimum_ease_factor, self.maximum_ease_factor];
+
+        if ease_factors.iter().any(|ease_factor| !ease_factor.is_finite()) {
+            problems.push(String::from("ease factors must be finite numbers"));
+            return problems;
+        }
+
+        if self.minimum_ease_factor < 1.0 {
+            problems.push(String::from("minimum_ease_factor must be at least 1"));
+        }
+
+        if self.minimum_ease_factor > self.maximum_ease_factor {
+            problems.push(String::from("minimum_ease_factor must not exceed maximum_ease_factor"));
+        }
+
+        if self.starting_ease_factor < self.minimum_ease_factor || self.starting_ease_factor > self.maximum_ease_factor {
+            problems.push(String::from("starting_ease_factor must be between the minimum and maximum ease factor"));
+        }
+
+        problems
+    }
+
+    // Gives a card the scheduling state of a brand new card, due right away.
+    pub fn reset(&self, card: &mut Card, now: DateTime<Utc>) {
+        card.set_ease_factor(self.starting_ease_factor);
+        card.set_interval(self.starting_interval);
+        card.set_stage(CardStage::New);
+        card.set_step(0);
+        card.set_lapses(0);
+        card.set_next_review(now);
+    }
+
+    pub fn is_leech(&self, card: &Card) -> bool {
+        self.leech_threshold > 0 && *card.lapses() >= self.leech_threshold
+    }
+
+    // Applies an answer to the card and works out when it is due next. The answer time is
+    // only known for answers that were timed.
+    pub fn review(&self, card: &mut Card, difficulty: &CardDifficulty, answer_time_ms: Option<u32>, now: DateTime<Utc>) {
+        let difficulty = &self.rate(difficulty, answer_time_ms);
+
+        match card.stage() {
+            CardStage::New | CardStage::Learning => self.review_learning(card, difficulty, now),
+            CardStage::Relearning => self.review_relearning(card, difficulty, now),
+            CardStage::Review => self.review_graduated(card, difficulty, now),
+        }
+    }
+
+    // The difficulty an answer is scheduled with: a slow `Easy` wasn't that easy after all.
+    pub fn rate(&self, difficulty: &CardDifficulty, answer_time_ms: Option<u32>) -> CardDifficulty {
+        let slow = self.slow_answer_seconds > 0
+            && answer_time_ms.is_some_and(|answer_time_ms| u64::from(answer_time_ms) > u64::from(self.slow_answer_seconds) * 1000);
+
+        match difficulty {
+            CardDifficulty::Easy if slow => CardDifficulty::Medium,
+            _ => difficulty.clone(),
+        }
+    }
+
+    fn review_learning(&self, card: &mut Card, difficulty: &CardDifficulty, now: DateTime<Utc>) {
+        let step = match difficulty {
+            CardDifficulty::Easy => None,
+            CardDifficulty::Medium => Some(card.step() + 1),
+            CardDifficulty::Hard => Some(0),
+        };
+
+        match step.and_then(|step| self.learning_steps.get(step).map(|delay| (step, *delay))) {
+            Some((step, delay)) => self.enter_step(card, CardStage::Learning, step, delay, now),
+            None => {
+                card.set_interval(self.starting_interval);
+                self.graduate(card, now);
+            },
+        }
+    }
+
+    fn review_relearning(&self, card: &mut Card, difficulty: &CardDifficulty, now: DateTime<Utc>) {
+        let step = match difficulty {
+            CardDifficulty::Easy => None,
+            CardDifficulty::Medium => Some(card.step() + 1),
+            CardDifficulty::Hard => Some(0),
+        };
+
+        match step.and_then(|step| self.relearning_steps.get(step).map(|delay| (step, *delay))) {
+            Some((step, delay)) => self.enter_step(card, CardStage::Relearning, step, delay, now),
+            None => self.graduate(card, now),
+        }
+    }
+
+    fn review_graduated(&self, card: &mut Card, difficulty: &CardDifficulty, now: DateTime<Utc>) {
+        let ease_factor = *card.ease_factor();
+
+        match difficulty {
+            CardDifficulty::Easy => {
+                card.set_interval(self.grow_interval(*card.interval(), ease_factor));
+                card.set_ease_factor(self.clamp_ease(ease_factor + 1.0));
+            },
+            CardDifficulty::Medium => {
+                card.set_ease_factor(self.clamp_ease(ease_factor + 1.0));
+            },
+            CardDifficulty::Hard => {
+                card.set_interval(self.starting_interval);
+                card.set_ease_factor(self.clamp_ease(ease_factor - 1.0));
+                self.lapse(card);
+
+                // A forgotten card is drilled again before it goes back to daily reviews.
+                if let Some(delay) = self.relearning_steps.get(0) {
+                    self.enter_step(card, CardStage::Relearning, 0, *delay, now);
+                    return;
+                }
+            },
+        }
+
+        card.set_next_review(self.due_after(now, *card.interval()));
+    }
+
+    // Counts a forgotten review card. Leeches are only suspended as they cross the threshold,
+    // so a leech that was unsuspended to be rewritten stays in rotation.
+    fn lapse(&self, card: &mut Card) {
+        card.set_lapses(card.lapses().saturating_add(1));
+
+        if self.suspend_leeches && self.leech_threshold > 0 && *card.lapses() == self.leech_threshold {
+            card.set_suspended(true);
+        }
+    }
+
+    fn enter_step(&self, card: &mut Card, stage: CardStage, step: u32, delay: TimeDelta, now: DateTime<Utc>) {
+        card.set_stage(stage);
+        card.set_step(step);
+        card.set_next_review(now.checked_add_signed(delay).unwrap_or(DateTime::<Utc>::MAX_UTC));
+    }
+
+    fn graduate(&self, card: &mut Card, now: DateTime<Utc>) {
+        card.set_stage(CardStage::Review);
+        card.set_step(0);
+        card.set_next_review(self.due_after(now, *card.interval()));
+    }
+
+    // How long until the card would be due again after this answer, without changing it.
+    pub fn preview(&self, card: &Card, difficulty: &CardDifficulty, now: DateTime<Utc>) -> TimeDelta {
+        let mut answered = card.clone();
+        self.review(&mut answered, difficulty, None, now);
+
+        *answered.next_review() - now
+    }
+
+    pub fn grow_interval(&self, interval: u32, ease_factor: f64) -> u32 {
+        // Float to int casts saturate, so a huge product can't wrap around.
+        let grown = (interval as f64 * ease_factor).round() as u32;
+
+        grown.clamp(self.starting_interval.min(self.maximum_interval), self.maximum_interval)
+    }
+
+    pub fn due_after(&self, now: DateTime<Utc>, interval: u32) -> DateTime<Utc> {
+        let interval = interval.min(self.maximum_interval);
+
+        now.checked_add_days(Days::new(interval as u64))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    fn clamp_ease(&self, ease_factor: f64) -> f64 {
+        ease_factor.clamp(self.minimum_ease_factor, self.maximum_ease_factor)
+    }
+}
+
+// A delay the way the rating buttons show it: `<1m`, `10m`, `5h`, `3d`, `2.1mo` or `1.5y`.
+pub fn format_interval(interval: TimeDelta) -> String {
+    let minutes = (interval.num_seconds() as f64 / 60.0).round() as i64;
+    let days = interval.num_seconds() as f64 / 86_400.0;
+
+    match minutes {
+        ..1 => String::from("<1m"),
+        1..60 => format!("{}m", minutes),
+        60..1440 => format!("{}h", (minutes as f64 / 60.0).round() as i64),
+        _ if days < 30.0 => format!("{}d", days.round() as i64),
+        _ if days < 365.0 => format!("{:.1}mo", days / 30.0),
+        _ => format!("{:.1}y", days / 365.0),
+    }
+}
@@ -0,0 +1,57 @@
+use flashcards_data::{check_answer, pick_distractors, AnswerOptions, Card, CardDifficulty, DiffSegment};
+
+#[test]
+fn exact_answers_are_easy() {
+    let check = check_answer(" Straße ", "Straße", AnswerOptions::default());
+
+    assert!(check.is_correct());
+    assert_eq!(check.correctness, 1.0);
+    assert_eq!(check.suggested_difficulty(), CardDifficulty::Easy);
+}
+
+#[test]
+fn typos_show_up_in_the_diff() {
+    let check = check_answer("recieve", "receive", AnswerOptions::default());
+
+    assert_eq!(check.segments, vec![
+        DiffSegment::Same(String::from("rec")),
+        DiffSegment::Missing(String::from("e")),
+        DiffSegment::Same(String::from("i")),
+        DiffSegment::Extra(String::from("e")),
+        DiffSegment::Same(String::from("ve")),
+    ]);
+    assert_eq!(check.suggested_difficulty(), CardDifficulty::Medium);
+
+    let check = check_answer("house", "receive", AnswerOptions::default());
+    assert_eq!(check.suggested_difficulty(), CardDifficulty::Hard);
+}
+
+#[test]
+fn case_and_accents_can_be_ignored() {
+    let strict = AnswerOptions::default();
+    let lenient = AnswerOptions { ignore_case: true, ignore_accents: true };
+
+    assert!(!check_answer("cafe", "Café", strict).is_correct());
+    assert!(check_answer("cafe", "Café", lenient).is_correct());
+    assert!(check_answer("CAFÉ", "cafe\u{301}", AnswerOptions { ignore_case: true, ignore_accents: false }).is_correct());
+}
+
+#[test]
+fn distractors_come_from_the_same_deck_and_look_alike() {
+    let card = Card::new(1, String::from("to run"), String::from("courir"));
+    let mut elsewhere = Card::new(6, String::from("to cook"), String::from("cuire"));
+    elsewhere.set_deck_id(2);
+
+    let cards = vec![
+        card.clone(),
+        Card::new(2, String::from("to laugh"), String::from("rire")),
+        Card::new(3, String::from("to cover"), String::from("couvrir")),
+        Card::new(4, String::from("to sprint"), String::from("Courir")),
+        Card::new(5, String::from("to hurt"), String::from("blesser")),
+        Card::new(7, String::from("to open"), String::from("couvrir ")),
+        elsewhere,
+    ];
+
+    assert_eq!(pick_distractors(&card, &cards, 3), vec!["couvrir", "rire", "blesser"]);
+    assert_eq!(pick_distractors(&card, &cards, 1), vec!["couvrir"]);
+}
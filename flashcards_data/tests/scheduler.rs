@@ -0,0 +1,231 @@
+use chrono::{DateTime, Days, TimeDelta, Utc};
+use flashcards_data::{format_interval, Card, CardDifficulty, CardStage, LearningSteps, SchedulerSettings};
+
+#[test]
+fn default_settings_are_valid() {
+    assert!(SchedulerSettings::default().problems().is_empty());
+}
+
+#[test]
+fn inconsistent_settings_are_reported() {
+    let settings = SchedulerSettings {
+        starting_ease_factor: 6.0,
+        starting_interval: 10,
+        maximum_interval: 5,
+        ..SchedulerSettings::default()
+    };
+
+    assert_eq!(settings.problems().len(), 2);
+}
+
+#[test]
+fn growing_an_interval_saturates_at_the_maximum() {
+    let settings = SchedulerSettings::default();
+
+    assert_eq!(settings.grow_interval(200, 2.5), 500);
+    assert_eq!(settings.grow_interval(u32::MAX, 5.0), settings.maximum_interval);
+}
+
+#[test]
+fn far_off_reviews_do_not_overflow_the_calendar() {
+    let settings = SchedulerSettings {
+        maximum_interval: i32::MAX as u32,
+        ..SchedulerSettings::default()
+    };
+
+    let now = Utc::now();
+    assert_eq!(settings.due_after(now, 400), now.checked_add_days(Days::new(400)).unwrap());
+    assert_eq!(settings.due_after(now, u32::MAX), DateTime::<Utc>::MAX_UTC);
+}
+
+#[test]
+fn fractional_ease_factors_are_kept() {
+    let settings = SchedulerSettings {
+        starting_ease_factor: 2.5,
+        ..SchedulerSettings::default()
+    };
+    let now = Utc::now();
+
+    let mut card = Card::new(1, String::from("front"), String::from("back"));
+    settings.reset(&mut card, now);
+    card.set_stage(CardStage::Review);
+    card.set_interval(10);
+
+    settings.review(&mut card, &CardDifficulty::Easy, None, now);
+    assert_eq!(*card.interval(), 25);
+    assert_eq!(*card.ease_factor(), 3.5);
+
+    settings.review(&mut card, &CardDifficulty::Hard, None, now);
+    assert_eq!(*card.interval(), settings.starting_interval);
+    assert_eq!(*card.ease_factor(), 2.5);
+}
+
+#[test]
+fn learning_steps_parse_and_print_in_config_form() {
+    let steps: LearningSteps = "1m 10m 1h 2d 90s".parse().unwrap();
+
+    assert_eq!(steps.len(), 5);
+    assert_eq!(steps.get(2), Some(&TimeDelta::hours(1)));
+    assert_eq!(steps.to_string(), "1m 10m 1h 2d 90s");
+
+    assert!("10".parse::<LearningSteps>().is_err());
+    assert!("0m".parse::<LearningSteps>().is_err());
+    assert!("5w".parse::<LearningSteps>().is_err());
+}
+
+#[test]
+fn new_cards_walk_through_the_learning_steps() {
+    let settings = SchedulerSettings {
+        learning_steps: "1m 10m 1h".parse().unwrap(),
+        ..SchedulerSettings::default()
+    };
+    let now = Utc::now();
+    let mut card = Card::new(1, String::from("front"), String::from("back"));
+
+    settings.review(&mut card, &CardDifficulty::Medium, None, now);
+    assert_eq!(*card.stage(), CardStage::Learning);
+    assert_eq!(*card.next_review(), now + TimeDelta::minutes(10));
+
+    settings.review(&mut card, &CardDifficulty::Hard, None, now);
+    assert_eq!(*card.step(), 0);
+    assert_eq!(*card.next_review(), now + TimeDelta::minutes(1));
+
+    settings.review(&mut card, &CardDifficulty::Medium, None, now);
+    settings.review(&mut card, &CardDifficulty::Medium, None, now);
+    assert_eq!(*card.next_review(), now + TimeDelta::hours(1));
+
+    settings.review(&mut card, &CardDifficulty::Medium, None, now);
+    assert_eq!(*card.stage(), CardStage::Review);
+    assert_eq!(*card.interval(), settings.starting_interval);
+    assert_eq!(*card.next_review(), now + TimeDelta::days(1));
+}
+
+#[test]
+fn easy_skips_the_remaining_steps() {
+    let settings = SchedulerSettings::default();
+    let now = Utc::now();
+    let mut card = Card::new(1, String::from("front"), String::from("back"));
+
+    settings.review(&mut card, &CardDifficulty::Easy, None, now);
+    assert_eq!(*card.stage(), CardStage::Review);
+    assert!(!card.is_due_at(now + TimeDelta::hours(23)));
+}
+
+#[test]
+fn forgotten_cards_are_relearned_within_minutes() {
+    let settings = SchedulerSettings::default();
+    let now = Utc::now();
+    let mut card = Card::new(1, String::from("front"), String::from("back"));
+    card.set_stage(CardStage::Review);
+    card.set_interval(30);
+
+    settings.review(&mut card, &CardDifficulty::Hard, None, now);
+    assert_eq!(*card.stage(), CardStage::Relearning);
+    assert_eq!(*card.next_review(), now + TimeDelta::minutes(10));
+
+    settings.review(&mut card, &CardDifficulty::Medium, None, now);
+    assert_eq!(*card.stage(), CardStage::Review);
+    assert_eq!(*card.next_review(), now + TimeDelta::days(settings.starting_interval as i64));
+}
+
+#[test]
+fn without_steps_cards_graduate_immediately() {
+    let settings = SchedulerSettings {
+        learning_steps: LearningSteps::default(),
+        relearning_steps: LearningSteps::default(),
+        ..SchedulerSettings::default()
+    };
+    let now = Utc::now();
+    let mut card = Card::new(1, String::from("front"), String::from("back"));
+
+    settings.review(&mut card, &CardDifficulty::Hard, None, now);
+    assert_eq!(*card.stage(), CardStage::Review);
+
+    settings.review(&mut card, &CardDifficulty::Hard, None, now);
+    assert_eq!(*card.stage(), CardStage::Review);
+    assert_eq!(*card.next_review(), now + TimeDelta::days(1));
+}
+
+#[test]
+fn leeches_are_suspended_as_they_cross_the_threshold() {
+    let settings = SchedulerSettings {
+        leech_threshold: 2,
+        suspend_leeches: true,
+        ..SchedulerSettings::default()
+    };
+    let now = Utc::now();
+
+    let mut card = Card::new(1, String::from("front"), String::from("back"));
+    card.set_stage(CardStage::Review);
+
+    // Only forgetting a graduated card is a lapse.
+    settings.review(&mut card, &CardDifficulty::Hard, None, now);
+    assert_eq!(*card.lapses(), 1);
+    settings.review(&mut card, &CardDifficulty::Hard, None, now);
+    assert_eq!(*card.lapses(), 1);
+    assert!(!settings.is_leech(&card));
+
+    card.set_stage(CardStage::Review);
+    settings.review(&mut card, &CardDifficulty::Hard, None, now);
+    assert!(settings.is_leech(&card));
+    assert!(*card.suspended());
+
+    // Unsuspended to be rewritten, further lapses leave it in rotation.
+    card.set_suspended(false);
+    card.set_stage(CardStage::Review);
+    settings.review(&mut card, &CardDifficulty::Hard, None, now);
+    assert_eq!(*card.lapses(), 3);
+    assert!(!*card.suspended());
+
+    settings.reset(&mut card, now);
+    assert!(!settings.is_leech(&card));
+}
+
+#[test]
+fn previews_leave_the_card_alone_and_read_well() {
+    let settings = SchedulerSettings::default();
+    let now = Utc::now();
+    let mut card = Card::new(1, String::from("front"), String::from("back"));
+
+    let previews: Vec<String> = [CardDifficulty::Hard, CardDifficulty::Medium, CardDifficulty::Easy].iter()
+        .map(|difficulty| format_interval(settings.preview(&card, difficulty, now)))
+        .collect();
+    assert_eq!(previews, vec!["1m", "10m", "1d"]);
+    assert_eq!(*card.stage(), CardStage::New);
+
+    card.set_stage(CardStage::Review);
+    card.set_interval(21);
+    assert_eq!(format_interval(settings.preview(&card, &CardDifficulty::Easy, now)), "2.1mo");
+
+    assert_eq!(format_interval(TimeDelta::seconds(20)), "<1m");
+    assert_eq!(format_interval(TimeDelta::hours(5)), "5h");
+    assert_eq!(format_interval(TimeDelta::days(548)), "1.5y");
+}
+
+#[test]
+fn slow_easy_answers_are_scheduled_as_medium() {
+    let settings = SchedulerSettings {
+        slow_answer_seconds: 10,
+        ..SchedulerSettings::default()
+    };
+    let now = Utc::now();
+
+    let mut card = Card::new(1, String::from("front"), String::from("back"));
+    card.set_stage(CardStage::Review);
+    card.set_interval(10);
+
+    // Medium keeps the interval, Easy would have grown it to 30 days.
+    let mut slow = card.clone();
+    settings.review(&mut slow, &CardDifficulty::Easy, Some(12_000), now);
+    assert_eq!(*slow.interval(), 10);
+    assert_eq!(*slow.next_review(), now + TimeDelta::days(10));
+
+    let mut quick = card.clone();
+    settings.review(&mut quick, &CardDifficulty::Easy, Some(10_000), now);
+    assert_eq!(*quick.interval(), 30);
+
+    // Untimed answers, other answers and the default settings are left as they are.
+    assert_eq!(settings.rate(&CardDifficulty::Easy, None), CardDifficulty::Easy);
+    assert_eq!(settings.rate(&CardDifficulty::Hard, Some(60_000)), CardDifficulty::Hard);
+    assert_eq!(SchedulerSettings::default().rate(&CardDifficulty::Easy, Some(60_000)), CardDifficulty::Easy);
+}